@@ -0,0 +1,370 @@
+use ethers_core::types::H256;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::{
+    BlobTransactionSidecar, PooledTransaction, PooledTransactions, TypedTransaction,
+    BYTES_PER_BLOB, CELLS_PER_EXT_BLOB,
+};
+
+/// The version of a versioned hash which commits to a blob with a KZG commitment.
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// Returns the versioned hash of a KZG commitment, as included in blob transactions.
+pub fn kzg_to_versioned_hash(commitment: &[u8; 48]) -> H256 {
+    let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    H256(hash)
+}
+
+/// Ways in which the sidecar of a blob transaction can fail to match the transaction.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum BlobSidecarError {
+    /// The blob transaction has no versioned hashes, while it must carry at least one blob.
+    #[error("blob transaction without blobs")]
+    NoBlobs,
+    /// The blob transaction was sent without its blobs.
+    #[error("blob transaction without a sidecar")]
+    MissingSidecar,
+    /// The number of blobs, commitments and proofs does not match the number of versioned
+    /// hashes in the transaction. Version 1 sidecars have [`CELLS_PER_EXT_BLOB`] proofs per blob.
+    #[error(
+        "{versioned_hashes} versioned hashes, but {blobs} blobs, {commitments} commitments and {proofs} proofs"
+    )]
+    LengthMismatch {
+        versioned_hashes: usize,
+        blobs: usize,
+        commitments: usize,
+        proofs: usize,
+    },
+    /// A blob does not have the size of a blob.
+    #[error("blob {index} has {length} bytes, but blobs have {BYTES_PER_BLOB} bytes")]
+    InvalidBlobLength {
+        /// The index of the blob in the sidecar.
+        index: usize,
+        /// The length of the blob.
+        length: usize,
+    },
+    /// A versioned hash of the transaction is not the hash of its commitment.
+    #[error("versioned hash {index} does not match its commitment")]
+    VersionedHashMismatch {
+        /// The index of the versioned hash in the transaction.
+        index: usize,
+    },
+    /// The blobs do not match their commitments and proofs.
+    #[error("invalid KZG proof")]
+    InvalidProof,
+    /// The cell proofs of version 1 sidecars cannot be verified with the KZG library in use. The
+    /// sidecar may well be valid, so this is not the fault of the peer.
+    #[error("cannot verify the proofs of a version {0} sidecar")]
+    UnsupportedVersion(u8),
+}
+
+/// A blob transaction of a [`PooledTransactions`] response with an invalid sidecar.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("invalid sidecar for transaction {index}: {error}")]
+pub struct InvalidBlobTransaction {
+    /// The index of the transaction in the response.
+    pub index: usize,
+    /// The hash of the transaction.
+    pub hash: [u8; 32],
+    /// Why the sidecar is invalid.
+    #[source]
+    pub error: BlobSidecarError,
+}
+
+impl PooledTransaction {
+    /// Checks that the sidecar of a blob transaction is well formed, and that the versioned
+    /// hashes of the transaction match the commitments of the sidecar.
+    ///
+    /// This does not verify the blobs against the commitments, see
+    /// [`PooledTransaction::verify_blobs`].
+    pub fn validate_blobs(&self) -> Result<(), BlobSidecarError> {
+        let tx = match &self.transaction {
+            TypedTransaction::EIP4844(tx) => tx,
+            _ => return Ok(()),
+        };
+        if tx.blob_versioned_hashes.is_empty() {
+            return Err(BlobSidecarError::NoBlobs);
+        }
+        let sidecar = self
+            .sidecar
+            .as_ref()
+            .ok_or(BlobSidecarError::MissingSidecar)?;
+
+        let versioned_hashes = tx.blob_versioned_hashes.len();
+        let proofs_per_blob = if sidecar.version == 0 {
+            1
+        } else {
+            CELLS_PER_EXT_BLOB
+        };
+        if sidecar.blobs.len() != versioned_hashes
+            || sidecar.commitments.len() != versioned_hashes
+            || sidecar.proofs.len() != versioned_hashes * proofs_per_blob
+        {
+            return Err(BlobSidecarError::LengthMismatch {
+                versioned_hashes,
+                blobs: sidecar.blobs.len(),
+                commitments: sidecar.commitments.len(),
+                proofs: sidecar.proofs.len(),
+            });
+        }
+        if let Some((index, blob)) = sidecar
+            .blobs
+            .iter()
+            .enumerate()
+            .find(|(_, blob)| blob.len() != BYTES_PER_BLOB)
+        {
+            return Err(BlobSidecarError::InvalidBlobLength {
+                index,
+                length: blob.len(),
+            });
+        }
+        if let Some(index) = tx
+            .blob_versioned_hashes
+            .iter()
+            .zip(sidecar.versioned_hashes())
+            .position(|(expected, got)| *expected != got)
+        {
+            return Err(BlobSidecarError::VersionedHashMismatch { index });
+        }
+        Ok(())
+    }
+
+    /// Checks the sidecar of a blob transaction like [`PooledTransaction::validate_blobs`], and
+    /// verifies the blobs against their commitments and proofs using the trusted setup of
+    /// mainnet.
+    ///
+    /// Only version 0 sidecars can be verified, the cell proofs of version 1 sidecars are
+    /// reported as [`BlobSidecarError::UnsupportedVersion`] once the sidecar is validated.
+    #[cfg(feature = "kzg")]
+    pub fn verify_blobs(&self) -> Result<(), BlobSidecarError> {
+        use c_kzg::{ethereum_kzg_settings, Blob, Bytes48, KzgProof};
+
+        self.validate_blobs()?;
+        let sidecar = match (&self.transaction, &self.sidecar) {
+            (TypedTransaction::EIP4844(_), Some(sidecar)) => sidecar,
+            _ => return Ok(()),
+        };
+        if sidecar.version != 0 {
+            return Err(BlobSidecarError::UnsupportedVersion(sidecar.version));
+        }
+
+        // blobs are large, so avoid moving them through iterator adapters on the stack
+        let mut blobs = Vec::with_capacity(sidecar.blobs.len());
+        for blob in &sidecar.blobs {
+            blobs.push(Blob::from_bytes(blob).map_err(|_| BlobSidecarError::InvalidProof)?);
+        }
+        let commitments = sidecar
+            .commitments
+            .iter()
+            .map(|commitment| Bytes48::new(*commitment))
+            .collect::<Vec<_>>();
+        let proofs = sidecar
+            .proofs
+            .iter()
+            .map(|proof| Bytes48::new(*proof))
+            .collect::<Vec<_>>();
+        // invalid field elements and points are reported as errors rather than failed proofs
+        match KzgProof::verify_blob_kzg_proof_batch(
+            &blobs,
+            &commitments,
+            &proofs,
+            ethereum_kzg_settings(),
+        ) {
+            Ok(true) => Ok(()),
+            _ => Err(BlobSidecarError::InvalidProof),
+        }
+    }
+}
+
+impl BlobTransactionSidecar {
+    /// Returns the versioned hashes of the commitments, in order.
+    pub fn versioned_hashes(&self) -> impl Iterator<Item = H256> + '_ {
+        self.commitments.iter().map(kzg_to_versioned_hash)
+    }
+}
+
+impl PooledTransactions {
+    /// Verifies the sidecars of the blob transactions in the response, returning the
+    /// transactions with invalid sidecars.
+    ///
+    /// Peers which send invalid blob transactions should be penalized, unless the error is
+    /// [`BlobSidecarError::UnsupportedVersion`].
+    #[cfg(feature = "kzg")]
+    pub fn verify_blobs(&self) -> Vec<InvalidBlobTransaction> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(index, tx)| {
+                tx.verify_blobs().err().map(|error| InvalidBlobTransaction {
+                    index,
+                    hash: tx.hash().0,
+                    error,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ethers_core::types::{Bytes, H256, U256};
+    use hex_literal::hex;
+
+    use super::{kzg_to_versioned_hash, BlobSidecarError};
+    use crate::{
+        BlobTransactionSidecar, EIP4844Transaction, PooledTransaction, TypedTransaction,
+        BYTES_PER_BLOB, CELLS_PER_EXT_BLOB,
+    };
+
+    // The commitment and proof of the empty blob are the point at infinity.
+    const INFINITY: [u8; 48] = {
+        let mut point = [0; 48];
+        point[0] = 0xc0;
+        point
+    };
+
+    fn blob_transaction(blobs: usize) -> PooledTransaction {
+        let sidecar = BlobTransactionSidecar {
+            version: 0,
+            blobs: vec![Bytes::from(vec![0; BYTES_PER_BLOB]); blobs],
+            commitments: vec![INFINITY; blobs],
+            proofs: vec![INFINITY; blobs],
+        };
+        let tx = EIP4844Transaction {
+            chain_id: 1,
+            nonce: 0u64.into(),
+            max_priority_fee_per_gas: 1u64.into(),
+            max_fee_per_gas: 1u64.into(),
+            gas_limit: 21_000u64.into(),
+            to: [0x11; 20].into(),
+            value: U256::zero(),
+            input: Bytes::default(),
+            access_list: Default::default(),
+            max_fee_per_blob_gas: 1u64.into(),
+            blob_versioned_hashes: sidecar.versioned_hashes().collect(),
+            odd_y_parity: false,
+            r: H256::repeat_byte(0x22),
+            s: H256::repeat_byte(0x33),
+        };
+        PooledTransaction {
+            transaction: TypedTransaction::EIP4844(tx),
+            sidecar: Some(sidecar),
+        }
+    }
+
+    #[test]
+    fn versioned_hash() {
+        assert_eq!(
+            kzg_to_versioned_hash(&INFINITY),
+            H256(hex!(
+                "010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014"
+            ))
+        );
+    }
+
+    #[test]
+    fn validate_blobs() {
+        assert_eq!(blob_transaction(2).validate_blobs(), Ok(()));
+        assert_eq!(
+            blob_transaction(0).validate_blobs(),
+            Err(BlobSidecarError::NoBlobs)
+        );
+
+        let mut tx = blob_transaction(2);
+        tx.sidecar = None;
+        assert_eq!(tx.validate_blobs(), Err(BlobSidecarError::MissingSidecar));
+
+        let mut tx = blob_transaction(2);
+        tx.sidecar.as_mut().unwrap().proofs.pop();
+        assert_eq!(
+            tx.validate_blobs(),
+            Err(BlobSidecarError::LengthMismatch {
+                versioned_hashes: 2,
+                blobs: 2,
+                commitments: 2,
+                proofs: 1,
+            })
+        );
+
+        // version 1 sidecars have a proof per cell
+        let mut tx = blob_transaction(2);
+        let sidecar = tx.sidecar.as_mut().unwrap();
+        sidecar.version = 1;
+        assert_eq!(
+            tx.validate_blobs(),
+            Err(BlobSidecarError::LengthMismatch {
+                versioned_hashes: 2,
+                blobs: 2,
+                commitments: 2,
+                proofs: 2,
+            })
+        );
+        tx.sidecar.as_mut().unwrap().proofs = vec![INFINITY; 2 * CELLS_PER_EXT_BLOB];
+        assert_eq!(tx.validate_blobs(), Ok(()));
+
+        let mut tx = blob_transaction(2);
+        tx.sidecar.as_mut().unwrap().blobs[1] = vec![0; 32].into();
+        assert_eq!(
+            tx.validate_blobs(),
+            Err(BlobSidecarError::InvalidBlobLength {
+                index: 1,
+                length: 32
+            })
+        );
+
+        let mut tx = blob_transaction(2);
+        tx.sidecar.as_mut().unwrap().commitments[1][47] = 1;
+        assert_eq!(
+            tx.validate_blobs(),
+            Err(BlobSidecarError::VersionedHashMismatch { index: 1 })
+        );
+    }
+
+    #[cfg(feature = "kzg")]
+    #[test]
+    fn verify_blobs() {
+        use super::InvalidBlobTransaction;
+        use crate::PooledTransactions;
+
+        // a blob which no longer matches its commitment
+        let mut tampered = blob_transaction(2);
+        let mut blob = vec![0; BYTES_PER_BLOB];
+        blob[31] = 1;
+        tampered.sidecar.as_mut().unwrap().blobs[1] = blob.into();
+
+        // a valid version 1 sidecar, whose cell proofs cannot be verified
+        let mut cells = blob_transaction(1);
+        let sidecar = cells.sidecar.as_mut().unwrap();
+        sidecar.version = 1;
+        sidecar.proofs = vec![INFINITY; CELLS_PER_EXT_BLOB];
+
+        let response = PooledTransactions(vec![
+            blob_transaction(1),
+            tampered.clone(),
+            blob_transaction(0),
+            cells.clone(),
+        ]);
+        assert_eq!(
+            response.verify_blobs(),
+            vec![
+                InvalidBlobTransaction {
+                    index: 1,
+                    hash: tampered.hash().0,
+                    error: BlobSidecarError::InvalidProof,
+                },
+                InvalidBlobTransaction {
+                    index: 2,
+                    hash: blob_transaction(0).hash().0,
+                    error: BlobSidecarError::NoBlobs,
+                },
+                InvalidBlobTransaction {
+                    index: 3,
+                    hash: cells.hash().0,
+                    error: BlobSidecarError::UnsupportedVersion(1),
+                }
+            ]
+        );
+    }
+}
@@ -1,11 +1,18 @@
-use anvil_core::eth::{
-    block::{Block, Header},
-    transaction::TypedTransaction,
+use ethers_core::{
+    types::{H256, U256},
+    utils::keccak256,
 };
 use open_fastrlp::{
-    Decodable, Encodable, RlpDecodable, RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper,
+    BufMut, Decodable, DecodeError, Encodable, RlpDecodable, RlpDecodableWrapper, RlpEncodable,
+    RlpEncodableWrapper,
 };
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    primitives::{decode_list, encode_list_payload, ensure_consumed, list_length},
+    trie, Block, EthereumPrimitives, Header, NetworkPrimitives, TypedTransaction, Withdrawal,
+};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 /// Either a block hash _or_ a block number
@@ -85,17 +92,137 @@ pub struct GetBlockHeaders {
 
 /// The response to [`GetBlockHeaders`], containing headers if any headers were found.
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper)]
-pub struct BlockHeaders(
+pub struct BlockHeaders<N: NetworkPrimitives = EthereumPrimitives>(
     /// The requested headers.
-    pub Vec<Header>,
+    pub Vec<N::Header>,
 );
 
-impl From<Vec<Header>> for BlockHeaders {
-    fn from(headers: Vec<Header>) -> Self {
+impl<N: NetworkPrimitives> From<Vec<N::Header>> for BlockHeaders<N> {
+    fn from(headers: Vec<N::Header>) -> Self {
         BlockHeaders(headers)
     }
 }
 
+/// The outcome of validating a [`BlockHeaders`] response which matches its request.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlockHeadersVerdict {
+    /// The response contains all requested headers.
+    Complete,
+    /// The response contains fewer headers than requested, for example because the peer does not
+    /// have the later blocks yet.
+    Partial {
+        /// The number of headers in the response.
+        received: usize,
+    },
+    /// The response is empty, so the peer does not know the start block.
+    Empty,
+}
+
+/// Ways in which a [`BlockHeaders`] response can fail to match its [`GetBlockHeaders`] request.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum BlockHeadersError {
+    /// The response contains more headers than the request limit.
+    #[error("received {received} headers, but at most {limit} were requested")]
+    TooManyHeaders {
+        /// The request limit.
+        limit: u64,
+        /// The number of headers in the response.
+        received: usize,
+    },
+    /// The first header is not the requested start block.
+    #[error("first header does not match the requested start block")]
+    StartMismatch,
+    /// A header does not have the expected block number, given the start, skip and direction of
+    /// the request.
+    #[error("header {index} has number {got}, expected {expected}")]
+    UnexpectedNumber {
+        /// The index of the header in the response.
+        index: usize,
+        /// The expected block number.
+        expected: U256,
+        /// The block number of the header.
+        got: U256,
+    },
+    /// A header is not the parent of the header following it in the chain, in a request without
+    /// skipped headers.
+    #[error("header {index} is not linked to its neighbour by parent hash")]
+    BrokenParentLink {
+        /// The index of the header in the response.
+        index: usize,
+    },
+}
+
+impl BlockHeaders {
+    /// Check that the headers are a valid response to the given request.
+    ///
+    /// The response may contain fewer headers than requested, which is reported as
+    /// [`BlockHeadersVerdict::Partial`] or [`BlockHeadersVerdict::Empty`] rather than as an
+    /// error.
+    ///
+    /// # Errors
+    /// Returns a [`BlockHeadersError`] if the response does not match the request.
+    pub fn validate(
+        &self,
+        request: &GetBlockHeaders,
+    ) -> Result<BlockHeadersVerdict, BlockHeadersError> {
+        let headers = &self.0;
+        if headers.len() as u64 > request.limit {
+            return Err(BlockHeadersError::TooManyHeaders {
+                limit: request.limit,
+                received: headers.len(),
+            });
+        }
+
+        let first = match headers.first() {
+            Some(first) => first,
+            None => return Ok(BlockHeadersVerdict::Empty),
+        };
+        let start_matches = match request.start_block {
+            BlockHashOrNumber::Hash(hash) => first.hash().0 == hash,
+            BlockHashOrNumber::Number(number) => first.number == number.into(),
+        };
+        if !start_matches {
+            return Err(BlockHeadersError::StartMismatch);
+        }
+
+        let step = U256::from(request.skip) + 1;
+        for (index, pair) in headers.windows(2).enumerate() {
+            let (previous, header) = (&pair[0], &pair[1]);
+            let expected = if request.reverse {
+                previous.number.checked_sub(step)
+            } else {
+                previous.number.checked_add(step)
+            };
+            if expected != Some(header.number) {
+                return Err(BlockHeadersError::UnexpectedNumber {
+                    index: index + 1,
+                    expected: expected.unwrap_or_default(),
+                    got: header.number,
+                });
+            }
+
+            if request.skip == 0 {
+                let (parent, child) = if request.reverse {
+                    (header, previous)
+                } else {
+                    (previous, header)
+                };
+                if child.parent_hash != parent.hash() {
+                    return Err(BlockHeadersError::BrokenParentLink { index: index + 1 });
+                }
+            }
+        }
+
+        if headers.len() as u64 == request.limit {
+            Ok(BlockHeadersVerdict::Complete)
+        } else {
+            Ok(BlockHeadersVerdict::Partial {
+                received: headers.len(),
+            })
+        }
+    }
+}
+
 /// A request for a peer to return block bodies for the given block hashes.
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper)]
 pub struct GetBlockBodies(
@@ -110,52 +237,389 @@ impl From<Vec<[u8; 32]>> for GetBlockBodies {
 }
 
 /// A response to [`GetBlockBodies`], containing bodies if any bodies were found.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, RlpEncodable, RlpDecodable)]
-pub struct BlockBody {
-    pub transactions: Vec<TypedTransaction>,
-    pub ommers: Vec<Header>,
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "N::Header: Serialize, N::Transaction: Serialize",
+    deserialize = "N::Header: Deserialize<'de>, N::Transaction: Deserialize<'de>"
+))]
+pub struct BlockBody<N: NetworkPrimitives = EthereumPrimitives> {
+    pub transactions: Vec<N::Transaction>,
+    pub ommers: Vec<N::Header>,
+    /// The withdrawals of the block, from Shanghai onwards.
+    pub withdrawals: Option<Vec<Withdrawal>>,
 }
 
-impl BlockBody {
+impl<N: NetworkPrimitives> BlockBody<N> {
     /// Create a [`Block`] from the body and its header.
-    pub fn create_block(&self, header: &Header) -> Block {
+    pub fn create_block(&self, header: &N::Header) -> Block<N> {
         Block {
             header: header.clone(),
             transactions: self.transactions.clone(),
             ommers: self.ommers.clone(),
+            withdrawals: self.withdrawals.clone(),
+        }
+    }
+
+    fn encode_payload(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        self.transactions.encode(&mut payload);
+        self.ommers.encode(&mut payload);
+        if let Some(withdrawals) = &self.withdrawals {
+            withdrawals.encode(&mut payload);
         }
+        payload
     }
 }
 
+impl BlockBody {
+    /// Computes the root of the transactions trie of the body.
+    pub fn transactions_root(&self) -> H256 {
+        transactions_root(&self.transactions)
+    }
+
+    /// Computes the hash of the ommers list of the body.
+    pub fn ommers_hash(&self) -> H256 {
+        ommers_hash(&self.ommers)
+    }
+
+    /// Check that the body belongs to the given header, by comparing the transactions root and
+    /// ommers hash of the header with those computed from the body.
+    ///
+    /// This should be done before [`BlockBody::create_block`] for bodies received from peers,
+    /// since a peer could otherwise attach arbitrary transactions to a valid header.
+    pub fn validate(&self, header: &Header) -> Result<(), BlockBodyError> {
+        validate_body(
+            header,
+            &self.transactions,
+            &self.ommers,
+            self.withdrawals.as_deref(),
+        )
+    }
+}
+
+impl<N: NetworkPrimitives> Encodable for BlockBody<N> {
+    fn length(&self) -> usize {
+        list_length(self.encode_payload().len())
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_list_payload(&self.encode_payload(), out)
+    }
+}
+
+impl<N: NetworkPrimitives> Decodable for BlockBody<N> {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let payload = &mut decode_list(buf)?;
+        let body = BlockBody {
+            transactions: Decodable::decode(payload)?,
+            ommers: Decodable::decode(payload)?,
+            withdrawals: if payload.is_empty() {
+                None
+            } else {
+                Some(Decodable::decode(payload)?)
+            },
+        };
+        ensure_consumed(payload)?;
+        Ok(body)
+    }
+}
+
+fn transactions_root(transactions: &[TypedTransaction]) -> H256 {
+    trie::ordered_trie_root(transactions.iter().map(trie::envelope))
+}
+
+fn ommers_hash(ommers: &[Header]) -> H256 {
+    let mut encoded = vec![];
+    open_fastrlp::encode_list::<Header, _>(ommers, &mut encoded);
+    H256(keccak256(encoded))
+}
+
+fn withdrawals_root(withdrawals: &[Withdrawal]) -> H256 {
+    trie::ordered_trie_root(withdrawals.iter().map(|withdrawal| {
+        let mut encoded = vec![];
+        withdrawal.encode(&mut encoded);
+        encoded
+    }))
+}
+
+/// Check the transactions, ommers and withdrawals of a block against its header.
+pub(crate) fn validate_body(
+    header: &Header,
+    transactions: &[TypedTransaction],
+    ommers: &[Header],
+    withdrawals: Option<&[Withdrawal]>,
+) -> Result<(), BlockBodyError> {
+    let transactions_root = transactions_root(transactions);
+    if transactions_root != header.transactions_root {
+        return Err(BlockBodyError::TransactionsRoot {
+            expected: header.transactions_root,
+            got: transactions_root,
+        });
+    }
+
+    let ommers_hash = ommers_hash(ommers);
+    if ommers_hash != header.ommers_hash {
+        return Err(BlockBodyError::OmmersHash {
+            expected: header.ommers_hash,
+            got: ommers_hash,
+        });
+    }
+
+    let withdrawals_root = withdrawals.map(withdrawals_root);
+    if withdrawals_root != header.withdrawals_root {
+        return Err(BlockBodyError::WithdrawalsRoot {
+            expected: header.withdrawals_root,
+            got: withdrawals_root,
+        });
+    }
+    Ok(())
+}
+
+/// Ways in which a [`BlockBody`] can fail to match its [`Header`].
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum BlockBodyError {
+    /// The transactions root of the body does not match the header.
+    #[error("transactions root mismatch: expected {expected:?}, got {got:?}")]
+    TransactionsRoot {
+        /// The transactions root in the header.
+        expected: H256,
+        /// The transactions root computed from the body.
+        got: H256,
+    },
+    /// The ommers hash of the body does not match the header.
+    #[error("ommers hash mismatch: expected {expected:?}, got {got:?}")]
+    OmmersHash {
+        /// The ommers hash in the header.
+        expected: H256,
+        /// The ommers hash computed from the body.
+        got: H256,
+    },
+    /// The withdrawals root of the body does not match the header, or only one of them has
+    /// withdrawals.
+    #[error("withdrawals root mismatch: expected {expected:?}, got {got:?}")]
+    WithdrawalsRoot {
+        /// The withdrawals root in the header.
+        expected: Option<H256>,
+        /// The withdrawals root computed from the body.
+        got: Option<H256>,
+    },
+}
+
+/// Ways in which a [`BlockBodies`] response can fail to match the headers of the requested
+/// blocks.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum BlockBodiesError {
+    /// The response contains more bodies than were requested.
+    #[error("received {received} bodies, but only {requested} were requested")]
+    TooManyBodies {
+        /// The number of requested bodies.
+        requested: usize,
+        /// The number of bodies in the response.
+        received: usize,
+    },
+    /// A body does not match the header of the block it was requested for.
+    #[error("body {index} does not match its header: {error}")]
+    InvalidBody {
+        /// The index of the first mismatching body in the response.
+        index: usize,
+        /// Why the body does not match.
+        #[source]
+        error: BlockBodyError,
+    },
+}
+
 /// The response to [`GetBlockBodies`], containing the block bodies that the peer knows about if
 /// any were found.
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper)]
-pub struct BlockBodies(
+pub struct BlockBodies<N: NetworkPrimitives = EthereumPrimitives>(
     /// The requested block bodies, each of which should correspond to a hash in the request.
-    pub Vec<BlockBody>,
+    pub Vec<BlockBody<N>>,
 );
 
-impl From<Vec<BlockBody>> for BlockBodies {
-    fn from(bodies: Vec<BlockBody>) -> Self {
+impl<N: NetworkPrimitives> From<Vec<BlockBody<N>>> for BlockBodies<N> {
+    fn from(bodies: Vec<BlockBody<N>>) -> Self {
         BlockBodies(bodies)
     }
 }
 
+impl BlockBodies {
+    /// Check each body against the header of the block it was requested for, in request order.
+    ///
+    /// The response may contain fewer bodies than headers, in which case only the bodies which
+    /// were returned are checked.
+    ///
+    /// # Errors
+    /// Returns a [`BlockBodiesError`] with the index of the first body which does not match its
+    /// header.
+    pub fn validate(&self, headers: &[Header]) -> Result<(), BlockBodiesError> {
+        if self.0.len() > headers.len() {
+            return Err(BlockBodiesError::TooManyBodies {
+                requested: headers.len(),
+                received: self.0.len(),
+            });
+        }
+        for (index, (body, header)) in self.0.iter().zip(headers).enumerate() {
+            body.validate(header)
+                .map_err(|error| BlockBodiesError::InvalidBody { index, error })?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
 
-    use anvil_core::eth::{
-        block::Header,
-        transaction::{LegacyTransaction, TransactionKind, TypedTransaction},
-    };
-    use ethers::core::types::{Bytes, Signature, H64, U256};
+    use ethers_core::types::{Bytes, Signature, H64, U256};
     use hex_literal::hex;
     use open_fastrlp::{Decodable, Encodable};
 
-    use crate::{message::RequestPair, BlockBodies, BlockHeaders, GetBlockBodies, GetBlockHeaders};
+    use crate::{
+        message::RequestPair, BlockBodies, BlockHeaders, GetBlockBodies, GetBlockHeaders, Header,
+        LegacyTransaction, TransactionKind, TypedTransaction, Withdrawal,
+    };
+
+    use super::{
+        BlockBodiesError, BlockBody, BlockBodyError, BlockHashOrNumber, BlockHeadersError,
+        BlockHeadersVerdict,
+    };
+
+    /// Builds a chain of empty headers starting at `start`.
+    fn chain(start: u64, len: usize) -> Vec<Header> {
+        let mut headers: Vec<Header> = Vec::with_capacity(len);
+        for number in start..start + len as u64 {
+            headers.push(Header {
+                parent_hash: headers.last().map(Header::hash).unwrap_or_default(),
+                ommers_hash: Default::default(),
+                beneficiary: Default::default(),
+                state_root: Default::default(),
+                transactions_root: Default::default(),
+                receipts_root: Default::default(),
+                logs_bloom: Default::default(),
+                difficulty: U256::one(),
+                number: number.into(),
+                gas_limit: 30_000_000u64.into(),
+                gas_used: U256::zero(),
+                timestamp: number * 12,
+                extra_data: Default::default(),
+                mix_hash: Default::default(),
+                nonce: Default::default(),
+                base_fee_per_gas: None,
+                withdrawals_root: None,
+                blob_gas_used: None,
+                excess_blob_gas: None,
+                parent_beacon_block_root: None,
+                requests_hash: None,
+            });
+        }
+        headers
+    }
+
+    fn request(
+        start_block: BlockHashOrNumber,
+        limit: u64,
+        skip: u32,
+        reverse: bool,
+    ) -> GetBlockHeaders {
+        GetBlockHeaders {
+            start_block,
+            limit,
+            skip,
+            reverse,
+        }
+    }
+
+    #[test]
+    fn validate_block_headers() {
+        let headers = chain(100, 10);
+
+        // contiguous, by number and by hash
+        let forward = BlockHeaders(headers[..5].to_vec());
+        assert_eq!(
+            forward.validate(&request(BlockHashOrNumber::Number(100), 5, 0, false)),
+            Ok(BlockHeadersVerdict::Complete)
+        );
+        assert_eq!(
+            forward.validate(&request(
+                BlockHashOrNumber::Hash(headers[0].hash().0),
+                8,
+                0,
+                false
+            )),
+            Ok(BlockHeadersVerdict::Partial { received: 5 })
+        );
+
+        // reverse
+        let reverse = BlockHeaders(headers.iter().rev().cloned().collect());
+        assert_eq!(
+            reverse.validate(&request(BlockHashOrNumber::Number(109), 10, 0, true)),
+            Ok(BlockHeadersVerdict::Complete)
+        );
+
+        // skipping, where parent hashes are not checked
+        let skipping = BlockHeaders(vec![
+            headers[0].clone(),
+            headers[3].clone(),
+            headers[6].clone(),
+        ]);
+        assert_eq!(
+            skipping.validate(&request(BlockHashOrNumber::Number(100), 3, 2, false)),
+            Ok(BlockHeadersVerdict::Complete)
+        );
+
+        assert_eq!(
+            BlockHeaders(vec![]).validate(&request(BlockHashOrNumber::Number(100), 3, 0, false)),
+            Ok(BlockHeadersVerdict::Empty)
+        );
+    }
+
+    #[test]
+    fn reject_invalid_block_headers() {
+        let headers = chain(100, 10);
+        let forward = BlockHeaders(headers[..5].to_vec());
+
+        assert_eq!(
+            forward.validate(&request(BlockHashOrNumber::Number(100), 4, 0, false)),
+            Err(BlockHeadersError::TooManyHeaders {
+                limit: 4,
+                received: 5
+            })
+        );
+        assert_eq!(
+            forward.validate(&request(BlockHashOrNumber::Number(101), 5, 0, false)),
+            Err(BlockHeadersError::StartMismatch)
+        );
+        assert_eq!(
+            forward.validate(&request(BlockHashOrNumber::Hash([0; 32]), 5, 0, false)),
+            Err(BlockHeadersError::StartMismatch)
+        );
+        // a forward response to a reverse request
+        assert_eq!(
+            forward.validate(&request(BlockHashOrNumber::Number(100), 5, 0, true)),
+            Err(BlockHeadersError::UnexpectedNumber {
+                index: 1,
+                expected: 99u64.into(),
+                got: 101u64.into()
+            })
+        );
+        // a contiguous response to a skipping request
+        assert_eq!(
+            forward.validate(&request(BlockHashOrNumber::Number(100), 5, 1, false)),
+            Err(BlockHeadersError::UnexpectedNumber {
+                index: 1,
+                expected: 102u64.into(),
+                got: 101u64.into()
+            })
+        );
 
-    use super::{BlockBody, BlockHashOrNumber};
+        // a header from another chain
+        let mut forked = headers[..5].to_vec();
+        forked[3].extra_data = vec![0x01].into();
+        assert_eq!(
+            BlockHeaders(forked).validate(&request(BlockHashOrNumber::Number(100), 5, 0, false)),
+            Err(BlockHeadersError::BrokenParentLink { index: 4 })
+        );
+    }
 
     #[test]
     fn decode_hash() {
@@ -311,6 +775,11 @@ mod test {
                     mix_hash: hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                     nonce: H64::from_low_u64_be(0x0000000000000000u64),
                     base_fee_per_gas: None,
+                    withdrawals_root: None,
+                    blob_gas_used: None,
+                    excess_blob_gas: None,
+                    parent_beacon_block_root: None,
+                    requests_hash: None,
                 },
             ]),
         }.encode(&mut data);
@@ -341,6 +810,11 @@ mod test {
                     mix_hash: hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                     nonce: H64::from_low_u64_be(0x0000000000000000u64),
                     base_fee_per_gas: None,
+                    withdrawals_root: None,
+                    blob_gas_used: None,
+                    excess_blob_gas: None,
+                    parent_beacon_block_root: None,
+                    requests_hash: None,
                 },
             ]),
         };
@@ -434,8 +908,14 @@ mod test {
                             mix_hash: hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                             nonce: H64::from_low_u64_be(0x0000000000000000u64),
                             base_fee_per_gas: None,
+                            withdrawals_root: None,
+                            blob_gas_used: None,
+                            excess_blob_gas: None,
+                            parent_beacon_block_root: None,
+                            requests_hash: None,
                         },
                     ],
+                    withdrawals: None,
                 }
             ]),
         };
@@ -497,12 +977,145 @@ mod test {
                             mix_hash: hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                             nonce: H64::from_low_u64_be(0x0000000000000000u64),
                             base_fee_per_gas: None,
+                            withdrawals_root: None,
+                            blob_gas_used: None,
+                            excess_blob_gas: None,
+                            parent_beacon_block_root: None,
+                            requests_hash: None,
                         },
                     ],
+                    withdrawals: None,
                 }
             ]),
         };
         let result = RequestPair::decode(&mut &data[..]).unwrap();
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn validate_block_bodies() {
+        // the body from the EIP-2481 test vector
+        let data = hex!("f902d3f8d2f867088504a817c8088302e2489435353535353535353535353535353535353535358202008025a064b1702d9298fee62dfeccc57d322a463ad55ca201256d01f62b45b2e1c21c12a064b1702d9298fee62dfeccc57d322a463ad55ca201256d01f62b45b2e1c21c10f867098504a817c809830334509435353535353535353535353535353535353535358202d98025a052f8f61201b2b11a78d6e866abc9c3db2ae8631fa656bfe5cb53668255367afba052f8f61201b2b11a78d6e866abc9c3db2ae8631fa656bfe5cb53668255367afbf901fcf901f9a00000000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000000000000000000000000000000000000940000000000000000000000000000000000000000a00000000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000000000000000000000000000000000000b90100000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000008208ae820d0582115c8215b3821a0a827788a00000000000000000000000000000000000000000000000000000000000000000880000000000000000");
+        let body = BlockBody::decode(&mut &data[..]).unwrap();
+        let mut header = chain(1, 1).remove(0);
+        header.transactions_root =
+            hex!("2da3a338791cab0995ab6ff92abbd61498e5c23ee3cf69599f74e2ae1936b282").into();
+        header.ommers_hash =
+            hex!("078a498306c8c969f4e5b0cf94985a8b93cfd79c09eb30d5f30613eef1245b92").into();
+        assert_eq!(body.validate(&header), Ok(()));
+
+        // an empty body
+        let mut empty_header = chain(2, 1).remove(0);
+        empty_header.transactions_root =
+            hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421").into();
+        empty_header.ommers_hash =
+            hex!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347").into();
+        let empty = BlockBody {
+            transactions: vec![],
+            ommers: vec![],
+            withdrawals: None,
+        };
+        assert_eq!(empty.validate(&empty_header), Ok(()));
+
+        let headers = [header.clone(), empty_header.clone()];
+        assert_eq!(
+            BlockBodies(vec![body.clone(), empty.clone()]).validate(&headers),
+            Ok(())
+        );
+        assert_eq!(BlockBodies(vec![body.clone()]).validate(&headers), Ok(()));
+        assert_eq!(
+            BlockBodies(vec![body.clone(), empty.clone(), empty.clone()]).validate(&headers),
+            Err(BlockBodiesError::TooManyBodies {
+                requested: 2,
+                received: 3
+            })
+        );
+
+        // bodies swapped between headers
+        assert_eq!(
+            BlockBodies(vec![empty.clone(), body.clone()]).validate(&headers),
+            Err(BlockBodiesError::InvalidBody {
+                index: 0,
+                error: BlockBodyError::TransactionsRoot {
+                    expected: header.transactions_root,
+                    got: empty_header.transactions_root
+                }
+            })
+        );
+
+        // a transaction removed from the body
+        let mut tampered = body.clone();
+        tampered.transactions.pop();
+        assert!(matches!(
+            BlockBodies(vec![tampered]).validate(&headers),
+            Err(BlockBodiesError::InvalidBody {
+                index: 0,
+                error: BlockBodyError::TransactionsRoot { .. }
+            })
+        ));
+
+        // an ommer removed from the body
+        let mut tampered = body;
+        tampered.ommers.clear();
+        assert_eq!(
+            BlockBodies(vec![tampered]).validate(&headers),
+            Err(BlockBodiesError::InvalidBody {
+                index: 0,
+                error: BlockBodyError::OmmersHash {
+                    expected: header.ommers_hash,
+                    got: empty_header.ommers_hash
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn block_body_withdrawals() {
+        let mut body = BlockBody {
+            transactions: vec![],
+            ommers: vec![],
+            withdrawals: Some(vec![]),
+        };
+        // a post-Shanghai body without transactions or withdrawals
+        let mut data = vec![];
+        body.encode(&mut data);
+        assert_eq!(data, hex!("c3c0c0c0"));
+        assert_eq!(BlockBody::decode(&mut &data[..]).unwrap(), body);
+
+        let mut header = Header {
+            transactions_root: crate::trie::EMPTY_ROOT,
+            ommers_hash: hex!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347")
+                .into(),
+            withdrawals_root: Some(crate::trie::EMPTY_ROOT),
+            ..Default::default()
+        };
+        assert_eq!(body.validate(&header), Ok(()));
+
+        body.withdrawals = Some(vec![Withdrawal {
+            index: 1,
+            validator_index: 2,
+            address: hex!("0000000000000000000000000000000000000003").into(),
+            amount: 4,
+        }]);
+        let mut data = vec![];
+        body.encode(&mut data);
+        assert_eq!(data.len(), body.length());
+        assert_eq!(BlockBody::decode(&mut &data[..]).unwrap(), body);
+        assert!(matches!(
+            body.validate(&header),
+            Err(BlockBodyError::WithdrawalsRoot { .. })
+        ));
+
+        // withdrawals are required once the header has a withdrawals root
+        body.withdrawals = None;
+        assert_eq!(
+            body.validate(&header),
+            Err(BlockBodyError::WithdrawalsRoot {
+                expected: Some(crate::trie::EMPTY_ROOT),
+                got: None
+            })
+        );
+        header.withdrawals_root = None;
+        assert_eq!(body.validate(&header), Ok(()));
+    }
 }
@@ -1,6 +1,10 @@
-use anvil_core::eth::{block::Block, transaction::TypedTransaction};
-use ethers::types::U128;
+use std::collections::HashMap;
+
+use ethers_core::types::{H256, U128, U256};
 use open_fastrlp::{RlpDecodable, RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper};
+use thiserror::Error;
+
+use crate::{blocks::validate_body, Block, BlockBodyError, EthereumPrimitives, NetworkPrimitives};
 
 /// This informs peers of new blocks that have appeared on the network.
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper)]
@@ -34,29 +38,103 @@ impl From<NewBlockHashes> for Vec<BlockHashNumber> {
 /// A new block with the current total difficulty, which includes the difficulty of the returned
 /// block.
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
-pub struct NewBlock {
+pub struct NewBlock<N: NetworkPrimitives = EthereumPrimitives> {
     /// A new block.
-    pub block: Block,
+    pub block: Block<N>,
     /// The current total difficulty.
     pub td: U128,
 }
 
+/// The largest total difficulty that is not considered absurd, `2^96`.
+///
+/// Mainnet reached its terminal total difficulty at about `2^76`, so this leaves plenty of room
+/// for any proof of work chain while catching garbage in the `td` field.
+pub const MAX_TOTAL_DIFFICULTY: U128 = U128([0, 1 << 32]);
+
+/// Provides the total difficulty of known blocks, so that the total difficulty of a [`NewBlock`]
+/// can be checked against its parent.
+pub trait TotalDifficultyLookup {
+    /// Returns the total difficulty of the block with the given hash, if it is known.
+    fn total_difficulty(&self, hash: H256) -> Option<U256>;
+}
+
+impl TotalDifficultyLookup for HashMap<H256, U256> {
+    fn total_difficulty(&self, hash: H256) -> Option<U256> {
+        self.get(&hash).copied()
+    }
+}
+
+impl NewBlock {
+    /// Check that the block is consistent before it is imported or propagated.
+    ///
+    /// The transactions and ommers of the block are checked against its header, and the total
+    /// difficulty must include the difficulty of the block and be below [`MAX_TOTAL_DIFFICULTY`].
+    /// If the parent is known to `lookup`, the total difficulty must also be at least that of
+    /// the parent plus the difficulty of the block.
+    pub fn validate<L: TotalDifficultyLookup + ?Sized>(
+        &self,
+        lookup: &L,
+    ) -> Result<(), NewBlockError> {
+        let header = &self.block.header;
+        validate_body(
+            header,
+            &self.block.transactions,
+            &self.block.ommers,
+            self.block.withdrawals.as_deref(),
+        )?;
+
+        let td = U256::from(self.td.as_u128());
+        if self.td > MAX_TOTAL_DIFFICULTY || td < header.difficulty {
+            return Err(NewBlockError::ImplausibleTotalDifficulty(self.td));
+        }
+
+        if let Some(parent_td) = lookup.total_difficulty(header.parent_hash) {
+            let minimum = parent_td.saturating_add(header.difficulty);
+            if td < minimum {
+                return Err(NewBlockError::TotalDifficultyTooLow { minimum, got: td });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Ways in which a [`NewBlock`] can be inconsistent.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum NewBlockError {
+    /// The transactions or ommers of the block do not match its header.
+    #[error(transparent)]
+    Body(#[from] BlockBodyError),
+    /// The total difficulty is below the difficulty of the block, or above
+    /// [`MAX_TOTAL_DIFFICULTY`].
+    #[error("implausible total difficulty: {0}")]
+    ImplausibleTotalDifficulty(U128),
+    /// The total difficulty is below the total difficulty of the parent plus the difficulty of
+    /// the block.
+    #[error("total difficulty {got} is below the minimum of {minimum}")]
+    TotalDifficultyTooLow {
+        /// The total difficulty of the parent plus the difficulty of the block.
+        minimum: U256,
+        /// The total difficulty of the announcement.
+        got: U256,
+    },
+}
+
 /// This informs peers of transactions that have appeared on the network and are not yet included
 /// in a block.
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper)]
-pub struct Transactions(
+pub struct Transactions<N: NetworkPrimitives = EthereumPrimitives>(
     /// New transactions for the peer to include in its mempool.
-    pub Vec<TypedTransaction>,
+    pub Vec<N::Transaction>,
 );
 
-impl From<Vec<TypedTransaction>> for Transactions {
-    fn from(txs: Vec<TypedTransaction>) -> Self {
+impl<N: NetworkPrimitives> From<Vec<N::Transaction>> for Transactions<N> {
+    fn from(txs: Vec<N::Transaction>) -> Self {
         Transactions(txs)
     }
 }
 
-impl From<Transactions> for Vec<TypedTransaction> {
-    fn from(txs: Transactions) -> Self {
+impl<N: NetworkPrimitives> From<Transactions<N>> for Vec<N::Transaction> {
+    fn from(txs: Transactions<N>) -> Self {
         txs.0
     }
 }
@@ -79,9 +157,14 @@ impl From<Vec<[u8; 32]>> for NewPooledTransactionHashes {
 
 #[cfg(test)]
 mod test {
-    use crate::{BlockHashNumber, NewBlockHashes, NewPooledTransactionHashes, Transactions};
-    use anvil_core::eth::transaction::{LegacyTransaction, TransactionKind, TypedTransaction};
-    use ethers::prelude::Signature;
+    use std::collections::HashMap;
+
+    use crate::{
+        Block, BlockBodyError, BlockHashNumber, Header, LegacyTransaction, NewBlock, NewBlockError,
+        NewBlockHashes, NewPooledTransactionHashes, TransactionKind, Transactions,
+        TypedTransaction, MAX_TOTAL_DIFFICULTY,
+    };
+    use ethers_core::types::{Signature, H256, U128, U256};
     use hex_literal::hex;
     use open_fastrlp::{Decodable, Encodable};
 
@@ -231,4 +314,95 @@ mod test {
         let encoded_str = hex::encode(encoded);
         assert_eq!(expected_str, encoded_str);
     }
+
+    fn new_block(td: u64) -> NewBlock {
+        NewBlock {
+            block: Block {
+                header: Header {
+                    parent_hash: H256::repeat_byte(0x01),
+                    ommers_hash: hex!(
+                        "1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"
+                    )
+                    .into(),
+                    beneficiary: Default::default(),
+                    state_root: Default::default(),
+                    transactions_root: hex!(
+                        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+                    )
+                    .into(),
+                    receipts_root: Default::default(),
+                    logs_bloom: Default::default(),
+                    difficulty: 10u64.into(),
+                    number: 2u64.into(),
+                    gas_limit: 30_000_000u64.into(),
+                    gas_used: U256::zero(),
+                    timestamp: 24,
+                    extra_data: Default::default(),
+                    mix_hash: Default::default(),
+                    nonce: Default::default(),
+                    base_fee_per_gas: None,
+                    withdrawals_root: None,
+                    blob_gas_used: None,
+                    excess_blob_gas: None,
+                    parent_beacon_block_root: None,
+                    requests_hash: None,
+                },
+                transactions: vec![],
+                ommers: vec![],
+                withdrawals: None,
+            },
+            td: td.into(),
+        }
+    }
+
+    #[test]
+    fn validate_new_block() {
+        let parents = HashMap::from([(H256::repeat_byte(0x01), U256::from(100u64))]);
+        assert_eq!(new_block(110).validate(&parents), Ok(()));
+        assert_eq!(new_block(120).validate(&parents), Ok(()));
+        assert_eq!(
+            new_block(105).validate(&parents),
+            Err(NewBlockError::TotalDifficultyTooLow {
+                minimum: 110u64.into(),
+                got: 105u64.into()
+            })
+        );
+        // the parent is not known
+        assert_eq!(new_block(105).validate(&HashMap::new()), Ok(()));
+
+        assert_eq!(
+            new_block(5).validate(&parents),
+            Err(NewBlockError::ImplausibleTotalDifficulty(5u64.into()))
+        );
+        let mut absurd = new_block(0);
+        absurd.td = MAX_TOTAL_DIFFICULTY + 1;
+        assert_eq!(
+            absurd.validate(&HashMap::new()),
+            Err(NewBlockError::ImplausibleTotalDifficulty(absurd.td))
+        );
+        assert_eq!(MAX_TOTAL_DIFFICULTY, U128::one() << 96);
+
+        // a transaction attached to a header without any
+        let mut block = new_block(110);
+        block
+            .block
+            .transactions
+            .push(TypedTransaction::Legacy(LegacyTransaction {
+                nonce: 0u64.into(),
+                gas_price: 1u64.into(),
+                gas_limit: 21000u64.into(),
+                kind: TransactionKind::Call(Default::default()),
+                value: 0u64.into(),
+                input: Default::default(),
+                signature: Signature {
+                    r: 1u64.into(),
+                    s: 1u64.into(),
+                    v: 27,
+                },
+            }));
+        assert!(matches!(
+            block.validate(&parents),
+            Err(NewBlockError::Body(BlockBodyError::TransactionsRoot { .. }))
+        ));
+    }
 }
@@ -0,0 +1,302 @@
+use ethers_core::{types::H256, utils::keccak256};
+use open_fastrlp::{BufMut, Decodable, DecodeError, Encodable, Header, RlpDecodable, RlpEncodable};
+use thiserror::Error;
+
+use crate::{primitives::decode_list, VoteData, BLS_PUBLIC_KEY_LENGTH};
+
+/// The name of the `bsc` capability, which BNB Smart Chain peers use to propagate fast finality
+/// votes.
+pub const BSC_PROTOCOL_NAME: &str = "bsc";
+
+/// The version of the `bsc` capability.
+pub const BSC_PROTOCOL_VERSION: u64 = 1;
+
+/// The number of message ids used by version 1 of the `bsc` capability.
+pub const BSC_MESSAGE_COUNT: usize = 2;
+
+/// The length of a BLS signature.
+pub const BLS_SIGNATURE_LENGTH: usize = 96;
+
+/// Represents message IDs for `bsc` protocol messages.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BscMessageID {
+    BscCap = 0x00,
+    Votes = 0x01,
+}
+
+impl Encodable for BscMessageID {
+    fn length(&self) -> usize {
+        1
+    }
+    fn encode(&self, out: &mut dyn BufMut) {
+        out.put_u8(*self as u8);
+    }
+}
+
+impl Decodable for BscMessageID {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let id = *buf.first().ok_or(DecodeError::InputTooShort)?;
+        let id = BscMessageID::try_from(id as usize)
+            .map_err(|_| DecodeError::Custom("Invalid message ID"))?;
+        *buf = &buf[1..];
+        Ok(id)
+    }
+}
+
+impl TryFrom<usize> for BscMessageID {
+    type Error = &'static str;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(BscMessageID::BscCap),
+            0x01 => Ok(BscMessageID::Votes),
+            _ => Err("Invalid message ID"),
+        }
+    }
+}
+
+/// The handshake of the `bsc` capability, which each peer sends once the capability is
+/// negotiated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BscCap {
+    /// The version of the capability, which must match on both sides.
+    pub protocol_version: u64,
+    /// A raw RLP item reserved for extensions.
+    pub extra: Vec<u8>,
+}
+
+impl Default for BscCap {
+    fn default() -> Self {
+        Self {
+            protocol_version: BSC_PROTOCOL_VERSION,
+            // the zero byte, as sent by the reference client
+            extra: vec![0x00],
+        }
+    }
+}
+
+/// The `bsc` handshake of a peer did not match ours.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[error("bsc protocol version mismatch: expected {expected}, got {got}")]
+pub struct BscVersionMismatch {
+    /// Our version of the capability.
+    pub expected: u64,
+    /// The version in the handshake of the peer.
+    pub got: u64,
+}
+
+impl BscCap {
+    /// Checks the handshake of a peer against the version we negotiated.
+    pub fn validate(&self, version: u64) -> Result<(), BscVersionMismatch> {
+        if self.protocol_version != version {
+            return Err(BscVersionMismatch {
+                expected: version,
+                got: self.protocol_version,
+            });
+        }
+        Ok(())
+    }
+
+    fn payload_length(&self) -> usize {
+        self.protocol_version.length() + self.extra.len()
+    }
+}
+
+impl Encodable for BscCap {
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + open_fastrlp::length_of_length(payload_length)
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header {
+            list: true,
+            payload_length: self.payload_length(),
+        }
+        .encode(out);
+        self.protocol_version.encode(out);
+        out.put_slice(&self.extra);
+    }
+}
+
+impl Decodable for BscCap {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let payload = &mut decode_list(buf)?;
+        let protocol_version = u64::decode(payload)?;
+
+        // the extra data is kept as the raw item, whatever it is
+        let item = &mut &payload[..];
+        let header = Header::decode(item)?;
+        let length = payload.len() - item.len() + header.payload_length;
+        if length != payload.len() {
+            return Err(DecodeError::UnexpectedLength);
+        }
+        Ok(Self {
+            protocol_version,
+            extra: payload.to_vec(),
+        })
+    }
+}
+
+/// A fast finality vote of a validator, signed with its BLS key.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct VoteEnvelope {
+    /// The BLS public key of the validator.
+    pub vote_address: [u8; BLS_PUBLIC_KEY_LENGTH],
+    /// The BLS signature of the hash of the vote data.
+    pub signature: [u8; BLS_SIGNATURE_LENGTH],
+    pub data: VoteData,
+}
+
+impl VoteEnvelope {
+    /// Returns the hash of the vote, which peers use to deduplicate votes.
+    pub fn hash(&self) -> H256 {
+        let mut encoded = vec![];
+        self.encode(&mut encoded);
+        H256(keccak256(encoded))
+    }
+}
+
+/// Votes of validators to be propagated to peers.
+#[derive(Clone, Debug, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct Votes {
+    pub votes: Vec<VoteEnvelope>,
+}
+
+/// A message of the `bsc` capability.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BscMessage {
+    BscCap(BscCap),
+    Votes(Votes),
+}
+
+impl BscMessage {
+    /// Returns the message's ID.
+    pub fn message_id(&self) -> BscMessageID {
+        match self {
+            BscMessage::BscCap(_) => BscMessageID::BscCap,
+            BscMessage::Votes(_) => BscMessageID::Votes,
+        }
+    }
+
+    /// Decodes the payload of a message with the given message id.
+    pub fn decode_message(
+        message_type: BscMessageID,
+        buf: &mut &[u8],
+    ) -> Result<Self, DecodeError> {
+        Ok(match message_type {
+            BscMessageID::BscCap => BscMessage::BscCap(BscCap::decode(buf)?),
+            BscMessageID::Votes => BscMessage::Votes(Votes::decode(buf)?),
+        })
+    }
+}
+
+/// Encodes the payload of the message, without the message id.
+impl Encodable for BscMessage {
+    fn length(&self) -> usize {
+        match self {
+            BscMessage::BscCap(cap) => cap.length(),
+            BscMessage::Votes(votes) => votes.length(),
+        }
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        match self {
+            BscMessage::BscCap(cap) => cap.encode(out),
+            BscMessage::Votes(votes) => votes.encode(out),
+        }
+    }
+}
+
+impl From<BscCap> for BscMessage {
+    fn from(cap: BscCap) -> Self {
+        BscMessage::BscCap(cap)
+    }
+}
+
+impl From<Votes> for BscMessage {
+    fn from(votes: Votes) -> Self {
+        BscMessage::Votes(votes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ethers_core::types::H256;
+    use hex_literal::hex;
+    use open_fastrlp::{Decodable, Encodable};
+
+    use super::{BscCap, BscMessage, BscMessageID, BscVersionMismatch, VoteEnvelope, Votes};
+    use crate::VoteData;
+
+    fn encode<T: Encodable>(value: &T) -> Vec<u8> {
+        let mut out = vec![];
+        value.encode(&mut out);
+        assert_eq!(out.len(), value.length());
+        out
+    }
+
+    #[test]
+    fn bsc_cap() {
+        let cap = BscCap::default();
+        let encoded = encode(&cap);
+        assert_eq!(encoded, hex!("c20100"));
+        assert_eq!(BscCap::decode(&mut &encoded[..]).unwrap(), cap);
+        assert_eq!(cap.validate(1), Ok(()));
+        assert_eq!(
+            cap.validate(2),
+            Err(BscVersionMismatch {
+                expected: 2,
+                got: 1
+            })
+        );
+
+        // the extra data is kept as is
+        let cap = BscCap {
+            protocol_version: 1,
+            extra: hex!("c3010203").to_vec(),
+        };
+        assert_eq!(BscCap::decode(&mut &encode(&cap)[..]).unwrap(), cap);
+
+        // but must be a single item
+        assert!(BscCap::decode(&mut &hex!("c0")[..]).is_err());
+        assert!(BscCap::decode(&mut &hex!("c3010000")[..]).is_err());
+    }
+
+    #[test]
+    fn votes() {
+        let vote = VoteEnvelope {
+            vote_address: [0xaa; 48],
+            signature: [0xbb; 96],
+            data: VoteData {
+                source_number: 1,
+                source_hash: H256::repeat_byte(0x01),
+                target_number: 2,
+                target_hash: H256::repeat_byte(0x02),
+            },
+        };
+        let votes = Votes {
+            votes: vec![vote.clone(), vote.clone()],
+        };
+        let encoded = encode(&votes);
+        assert_eq!(Votes::decode(&mut &encoded[..]).unwrap(), votes);
+
+        // the votes are wrapped in a list, as the packet is a struct
+        let mut list = vec![];
+        open_fastrlp::encode_list::<VoteEnvelope, _>(&votes.votes, &mut list);
+        assert_eq!(encoded[encoded.len() - list.len()..], list[..]);
+        assert_ne!(encoded.len(), list.len());
+
+        let message = BscMessage::from(votes);
+        assert_eq!(message.message_id(), BscMessageID::Votes);
+        let mut id = &[0x01][..];
+        let id = BscMessageID::decode(&mut id).unwrap();
+        assert_eq!(
+            BscMessage::decode_message(id, &mut &encode(&message)[..]).unwrap(),
+            message
+        );
+
+        assert_ne!(vote.hash(), vote.data.hash());
+    }
+}
@@ -0,0 +1,747 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use ethers_core::types::{Address, Signature, H256, H64, U256};
+use thiserror::Error;
+
+use crate::{BlockHeaders, Header};
+
+/// The length of the vanity prefix of the extra data.
+pub const CLIQUE_EXTRA_VANITY: usize = 32;
+
+/// The length of the signer seal at the end of the extra data.
+pub const CLIQUE_EXTRA_SEAL: usize = 65;
+
+/// The difficulty of a block sealed by the signer in turn.
+pub const DIFF_IN_TURN: u64 = 2;
+
+/// The difficulty of a block sealed by a signer out of turn.
+pub const DIFF_NO_TURN: u64 = 1;
+
+/// The nonce of a block voting to add its beneficiary as a signer.
+pub const NONCE_AUTH_VOTE: H64 = H64([0xff; 8]);
+
+/// The nonce of a block voting to remove its beneficiary as a signer.
+pub const NONCE_DROP_VOTE: H64 = H64([0x00; 8]);
+
+/// The default number of blocks per epoch.
+const EPOCH_LENGTH: u64 = 30_000;
+
+/// The parameters of a chain using Clique, the proof of authority consensus of
+/// [EIP-225](https://eips.ethereum.org/EIPS/eip-225).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Clique {
+    /// The number of blocks after which votes are reset and the signers are checkpointed. An
+    /// epoch of 0 is treated as the default of 30000 blocks, as geth does.
+    pub epoch: u64,
+}
+
+/// Ways in which a Clique header can be invalid.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum CliqueError {
+    /// The extra data is too short for the vanity and the seal.
+    #[error("extra data of {0} bytes is too short")]
+    ExtraTooShort(usize),
+    /// The signer list of a checkpoint block is not a list of addresses, or a block which is not
+    /// a checkpoint has a signer list.
+    #[error("invalid signer list of {0} bytes")]
+    InvalidSignerList(usize),
+    /// The signer list of a checkpoint block does not match the signers at that block.
+    #[error("checkpoint signers do not match the signers of the snapshot")]
+    CheckpointSignersMismatch,
+    /// A checkpoint block casts a vote.
+    #[error("checkpoint block casts a vote")]
+    CheckpointVote,
+    /// The nonce of a block is neither [`NONCE_AUTH_VOTE`] nor [`NONCE_DROP_VOTE`].
+    #[error("invalid vote nonce {0:?}")]
+    InvalidVote(H64),
+    /// No signer could be recovered from the seal.
+    #[error("invalid seal")]
+    InvalidSeal,
+    /// The block was sealed by an account which is not a signer.
+    #[error("block sealed by {0:?}, which is not a signer")]
+    UnauthorizedSigner(Address),
+    /// The block was sealed by a signer which sealed one of the recent blocks.
+    #[error("{0:?} has signed recently")]
+    RecentlySigned(Address),
+    /// The difficulty does not match whether the signer was in turn.
+    #[error("difficulty {got} does not match the expected {expected}")]
+    WrongDifficulty {
+        /// The difficulty for the signer at that block.
+        expected: U256,
+        /// The difficulty of the block.
+        got: U256,
+    },
+    /// The header does not extend the snapshot.
+    #[error("header {number} does not extend the snapshot at block {head}")]
+    NotChild {
+        /// The number of the header.
+        number: U256,
+        /// The number of the last block applied to the snapshot.
+        head: u64,
+    },
+    /// The number of a checkpoint block does not fit in 64 bits.
+    #[error("checkpoint number {0} is too large")]
+    NumberTooLarge(U256),
+}
+
+/// A header of a [`BlockHeaders`] response which was rejected by a [`CliqueSnapshot`].
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("invalid header {index}: {error}")]
+pub struct InvalidCliqueHeader {
+    /// The index of the header in the response.
+    pub index: usize,
+    /// Why the header was rejected.
+    #[source]
+    pub error: CliqueError,
+}
+
+impl Clique {
+    /// The Görli testnet.
+    pub fn goerli() -> Self {
+        Self {
+            epoch: EPOCH_LENGTH,
+        }
+    }
+
+    /// Returns whether the block with the given number is a checkpoint, which lists the signers
+    /// and does not cast a vote.
+    pub fn is_checkpoint(&self, number: u64) -> bool {
+        let epoch = if self.epoch == 0 {
+            EPOCH_LENGTH
+        } else {
+            self.epoch
+        };
+        number.is_multiple_of(epoch)
+    }
+
+    /// Returns the hash which the signer signs to seal the block, which is the hash of the
+    /// header without the seal.
+    pub fn seal_hash(&self, header: &Header) -> Result<H256, CliqueError> {
+        let extra = &header.extra_data;
+        if extra.len() < CLIQUE_EXTRA_SEAL {
+            return Err(CliqueError::ExtraTooShort(extra.len()));
+        }
+        let mut unsealed = header.clone();
+        unsealed.extra_data = extra.0.slice(..extra.len() - CLIQUE_EXTRA_SEAL).into();
+        Ok(unsealed.hash())
+    }
+
+    /// Recovers the signer of the block from the seal in its extra data.
+    pub fn recover_signer(&self, header: &Header) -> Result<Address, CliqueError> {
+        let seal_hash = self.seal_hash(header)?;
+        let extra = &header.extra_data[..];
+        let seal = &extra[extra.len() - CLIQUE_EXTRA_SEAL..];
+        let signature = Signature {
+            r: U256::from_big_endian(&seal[..32]),
+            s: U256::from_big_endian(&seal[32..64]),
+            v: seal[64].into(),
+        };
+        if signature.v > 1 {
+            return Err(CliqueError::InvalidSeal);
+        }
+        signature
+            .recover(seal_hash)
+            .map_err(|_| CliqueError::InvalidSeal)
+    }
+
+    /// Returns the signers listed in the extra data of a block, which is empty unless the block
+    /// is a checkpoint.
+    pub fn signers(&self, header: &Header) -> Result<Vec<Address>, CliqueError> {
+        let extra = &header.extra_data[..];
+        if extra.len() < CLIQUE_EXTRA_VANITY + CLIQUE_EXTRA_SEAL {
+            return Err(CliqueError::ExtraTooShort(extra.len()));
+        }
+        let signers = &extra[CLIQUE_EXTRA_VANITY..extra.len() - CLIQUE_EXTRA_SEAL];
+        // blocks beyond 64 bits cannot be applied to a snapshot anyway
+        let checkpoint =
+            header.number <= U256::from(u64::MAX) && self.is_checkpoint(header.number.as_u64());
+        if !signers.len().is_multiple_of(Address::len_bytes())
+            || (!checkpoint && !signers.is_empty())
+        {
+            return Err(CliqueError::InvalidSignerList(signers.len()));
+        }
+        Ok(signers
+            .chunks_exact(Address::len_bytes())
+            .map(Address::from_slice)
+            .collect())
+    }
+}
+
+/// A vote of a signer to add or remove an account.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Vote {
+    signer: Address,
+    address: Address,
+    authorize: bool,
+}
+
+/// The votes for an account, which are either all to add or all to remove it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Tally {
+    authorize: bool,
+    votes: usize,
+}
+
+/// The state of a Clique chain at a block: its signers, the signers of the recent blocks, and the
+/// pending votes.
+///
+/// A snapshot starts from a checkpoint block, such as the genesis, and headers are applied to it
+/// in order. Applying a header checks its seal and difficulty, and counts its vote.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CliqueSnapshot {
+    clique: Clique,
+    number: u64,
+    hash: H256,
+    signers: BTreeSet<Address>,
+    recents: BTreeMap<u64, Address>,
+    votes: Vec<Vote>,
+    tally: HashMap<Address, Tally>,
+}
+
+impl CliqueSnapshot {
+    /// Creates a snapshot from a trusted checkpoint block, taking the signers from its extra
+    /// data.
+    pub fn new(clique: Clique, checkpoint: &Header) -> Result<Self, CliqueError> {
+        if checkpoint.number > U256::from(u64::MAX) {
+            return Err(CliqueError::NumberTooLarge(checkpoint.number));
+        }
+        Ok(Self {
+            clique,
+            number: checkpoint.number.as_u64(),
+            hash: checkpoint.hash(),
+            signers: clique.signers(checkpoint)?.into_iter().collect(),
+            recents: BTreeMap::new(),
+            votes: vec![],
+            tally: HashMap::new(),
+        })
+    }
+
+    /// Returns the number of the last block applied to the snapshot.
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// Returns the hash of the last block applied to the snapshot.
+    pub fn hash(&self) -> H256 {
+        self.hash
+    }
+
+    /// Returns the signers, in ascending order.
+    pub fn signers(&self) -> impl Iterator<Item = &Address> {
+        self.signers.iter()
+    }
+
+    /// Returns whether the signer is in turn to seal the block with the given number.
+    pub fn is_in_turn(&self, number: u64, signer: &Address) -> bool {
+        self.signers
+            .iter()
+            .position(|s| s == signer)
+            .is_some_and(|index| number % self.signers.len() as u64 == index as u64)
+    }
+
+    /// Checks the next header against the snapshot and applies it, returning its signer.
+    ///
+    /// The snapshot is left unchanged if the header is invalid.
+    pub fn apply(&mut self, header: &Header) -> Result<Address, CliqueError> {
+        let number = self
+            .number
+            .checked_add(1)
+            .filter(|number| {
+                header.number == U256::from(*number) && header.parent_hash == self.hash
+            })
+            .ok_or(CliqueError::NotChild {
+                number: header.number,
+                head: self.number,
+            })?;
+
+        let checkpoint = self.clique.is_checkpoint(number);
+        let signers = self.clique.signers(header)?;
+        if checkpoint {
+            if !header.beneficiary.is_zero() || header.nonce != NONCE_DROP_VOTE {
+                return Err(CliqueError::CheckpointVote);
+            }
+            if !signers.iter().eq(self.signers.iter()) {
+                return Err(CliqueError::CheckpointSignersMismatch);
+            }
+        }
+        let authorize = if header.nonce == NONCE_AUTH_VOTE {
+            true
+        } else if header.nonce == NONCE_DROP_VOTE {
+            false
+        } else {
+            return Err(CliqueError::InvalidVote(header.nonce));
+        };
+
+        let signer = self.clique.recover_signer(header)?;
+        if !self.signers.contains(&signer) {
+            return Err(CliqueError::UnauthorizedSigner(signer));
+        }
+        // a signer may seal one of any `signers / 2 + 1` consecutive blocks
+        let limit = (self.signers.len() / 2 + 1) as u64;
+        if self
+            .recents
+            .iter()
+            .any(|(block, recent)| *recent == signer && *block + limit > number)
+        {
+            return Err(CliqueError::RecentlySigned(signer));
+        }
+        let expected = if self.is_in_turn(number, &signer) {
+            DIFF_IN_TURN
+        } else {
+            DIFF_NO_TURN
+        };
+        if header.difficulty != U256::from(expected) {
+            return Err(CliqueError::WrongDifficulty {
+                expected: expected.into(),
+                got: header.difficulty,
+            });
+        }
+
+        // the header is valid, so apply it
+        if checkpoint {
+            self.votes.clear();
+            self.tally.clear();
+        }
+        if number >= limit {
+            self.recents.remove(&(number - limit));
+        }
+        self.recents.insert(number, signer);
+
+        // a new vote of the signer for the account replaces its previous one
+        let address = header.beneficiary;
+        if let Some(index) = self
+            .votes
+            .iter()
+            .position(|vote| vote.signer == signer && vote.address == address)
+        {
+            let vote = self.votes.remove(index);
+            self.uncast(vote.address, vote.authorize);
+        }
+        if self.cast(address, authorize) {
+            self.votes.push(Vote {
+                signer,
+                address,
+                authorize,
+            });
+        }
+
+        // the tally decides the outcome, as the vote of this header may not have been counted
+        let passed = self
+            .tally
+            .get(&address)
+            .filter(|tally| tally.votes > self.signers.len() / 2)
+            .map(|tally| tally.authorize);
+        if let Some(authorize) = passed {
+            if authorize {
+                self.signers.insert(address);
+            } else {
+                self.signers.remove(&address);
+                // the signer limit shrinks, which frees up the oldest recent signer
+                let limit = (self.signers.len() / 2 + 1) as u64;
+                if number >= limit {
+                    self.recents.remove(&(number - limit));
+                }
+                // the votes of the removed signer no longer count
+                for vote in std::mem::take(&mut self.votes) {
+                    if vote.signer == address {
+                        self.uncast(vote.address, vote.authorize);
+                    } else {
+                        self.votes.push(vote);
+                    }
+                }
+            }
+            self.votes.retain(|vote| vote.address != address);
+            self.tally.remove(&address);
+        }
+
+        self.number = number;
+        self.hash = header.hash();
+        Ok(signer)
+    }
+
+    /// Checks and applies the headers of a [`BlockHeaders`] response, which must extend the
+    /// snapshot. Headers of a response to a reverse request are applied in reverse.
+    ///
+    /// The snapshot is left unchanged if any header is invalid.
+    ///
+    /// # Errors
+    /// Returns an [`InvalidCliqueHeader`] with the index of the first invalid header in the
+    /// response.
+    pub fn validate_headers(&mut self, headers: &BlockHeaders) -> Result<(), InvalidCliqueHeader> {
+        let headers = &headers.0;
+        let reverse = headers.len() > 1 && headers[0].number > headers[headers.len() - 1].number;
+        let order: Vec<usize> = if reverse {
+            (0..headers.len()).rev().collect()
+        } else {
+            (0..headers.len()).collect()
+        };
+
+        let mut snapshot = self.clone();
+        for index in order {
+            snapshot
+                .apply(&headers[index])
+                .map_err(|error| InvalidCliqueHeader { index, error })?;
+        }
+        *self = snapshot;
+        Ok(())
+    }
+
+    /// Counts a vote, returning whether it is meaningful: signers can only be removed, and other
+    /// accounts can only be added.
+    fn cast(&mut self, address: Address, authorize: bool) -> bool {
+        if self.signers.contains(&address) == authorize {
+            return false;
+        }
+        match self.tally.get_mut(&address) {
+            Some(tally) if tally.authorize == authorize => tally.votes += 1,
+            Some(_) => return false,
+            None => {
+                self.tally.insert(
+                    address,
+                    Tally {
+                        authorize,
+                        votes: 1,
+                    },
+                );
+            }
+        }
+        true
+    }
+
+    /// Removes a previously cast vote.
+    fn uncast(&mut self, address: Address, authorize: bool) {
+        if let Some(tally) = self.tally.get_mut(&address) {
+            if tally.authorize == authorize {
+                tally.votes -= 1;
+                if tally.votes == 0 {
+                    self.tally.remove(&address);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ethers_core::{
+        types::{Address, Bytes, H256, U256},
+        utils::secret_key_to_address,
+    };
+    use k256::ecdsa::SigningKey;
+
+    use super::{
+        Clique, CliqueError, CliqueSnapshot, InvalidCliqueHeader, DIFF_IN_TURN, DIFF_NO_TURN,
+        NONCE_AUTH_VOTE, NONCE_DROP_VOTE,
+    };
+    use crate::{BlockHeaders, Header};
+
+    const CLIQUE: Clique = Clique { epoch: 10 };
+
+    fn keys() -> Vec<SigningKey> {
+        let mut keys = (1..=4u8)
+            .map(|byte| SigningKey::from_slice(&[byte; 32]).unwrap())
+            .collect::<Vec<_>>();
+        keys.sort_by_key(secret_key_to_address);
+        keys
+    }
+
+    fn genesis(signers: &[Address]) -> Header {
+        let mut extra = vec![0; 32];
+        for signer in signers {
+            extra.extend_from_slice(signer.as_bytes());
+        }
+        extra.extend_from_slice(&[0; 65]);
+        Header {
+            extra_data: extra.into(),
+            ..Default::default()
+        }
+    }
+
+    fn seal(mut header: Header, key: &SigningKey) -> Header {
+        let hash = CLIQUE.seal_hash(&header).unwrap();
+        let (signature, recovery_id) = key.sign_prehash_recoverable(hash.as_bytes()).unwrap();
+        let mut extra = header.extra_data.to_vec();
+        let length = extra.len();
+        extra[length - 65..length - 1].copy_from_slice(&signature.to_bytes());
+        extra[length - 1] = recovery_id.to_byte();
+        header.extra_data = extra.into();
+        header
+    }
+
+    /// Creates the child of a header, sealed by the given key, with the right difficulty for the
+    /// snapshot.
+    fn child(
+        snapshot: &CliqueSnapshot,
+        parent: &Header,
+        key: &SigningKey,
+        vote: Option<(Address, bool)>,
+    ) -> Header {
+        let number = parent.number.as_u64() + 1;
+        let signer = secret_key_to_address(key);
+        let difficulty = if snapshot.is_in_turn(number, &signer) {
+            DIFF_IN_TURN
+        } else {
+            DIFF_NO_TURN
+        };
+        let mut extra = vec![0; 32];
+        if CLIQUE.is_checkpoint(number) {
+            for signer in snapshot.signers() {
+                extra.extend_from_slice(signer.as_bytes());
+            }
+        }
+        extra.extend_from_slice(&[0; 65]);
+        let (beneficiary, nonce) = match vote {
+            Some((address, true)) => (address, NONCE_AUTH_VOTE),
+            Some((address, false)) => (address, NONCE_DROP_VOTE),
+            None => (Address::zero(), NONCE_DROP_VOTE),
+        };
+        let header = Header {
+            parent_hash: parent.hash(),
+            beneficiary,
+            difficulty: difficulty.into(),
+            number: number.into(),
+            timestamp: parent.timestamp + 15,
+            extra_data: extra.into(),
+            nonce,
+            ..Default::default()
+        };
+        seal(header, key)
+    }
+
+    #[test]
+    fn recover_signer() {
+        let key = &keys()[0];
+        let header = seal(genesis(&[]), key);
+        assert_eq!(
+            CLIQUE.recover_signer(&header),
+            Ok(secret_key_to_address(key))
+        );
+
+        let mut tampered = header.clone();
+        tampered.gas_used = U256::one();
+        assert_ne!(
+            CLIQUE.recover_signer(&tampered),
+            Ok(secret_key_to_address(key))
+        );
+
+        tampered.extra_data = Bytes::from(vec![0; 64]);
+        assert_eq!(
+            CLIQUE.recover_signer(&tampered),
+            Err(CliqueError::ExtraTooShort(64))
+        );
+    }
+
+    #[test]
+    fn default_epoch() {
+        let clique = Clique { epoch: 0 };
+        assert!(clique.is_checkpoint(0));
+        assert!(!clique.is_checkpoint(10));
+        assert!(clique.is_checkpoint(30_000));
+
+        let signers = keys().iter().map(secret_key_to_address).collect::<Vec<_>>();
+        let mut checkpoint = genesis(&signers);
+        assert_eq!(clique.signers(&checkpoint), Ok(signers));
+        checkpoint.number = 10.into();
+        assert_eq!(
+            clique.signers(&checkpoint),
+            Err(CliqueError::InvalidSignerList(80))
+        );
+        checkpoint.number = U256::MAX;
+        assert_eq!(
+            clique.signers(&checkpoint),
+            Err(CliqueError::InvalidSignerList(80))
+        );
+    }
+
+    #[test]
+    fn large_numbers() {
+        let signers = keys().iter().map(secret_key_to_address).collect::<Vec<_>>();
+        let mut checkpoint = genesis(&signers);
+        checkpoint.number = U256::from(u64::MAX) + 1;
+        assert_eq!(
+            CliqueSnapshot::new(CLIQUE, &checkpoint),
+            Err(CliqueError::NumberTooLarge(checkpoint.number))
+        );
+
+        // the snapshot cannot be extended past the largest block number
+        let clique = Clique { epoch: 5 };
+        checkpoint.number = u64::MAX.into();
+        let mut snapshot = CliqueSnapshot::new(clique, &checkpoint).unwrap();
+        let header = Header {
+            parent_hash: checkpoint.hash(),
+            number: U256::from(u64::MAX) + 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            snapshot.apply(&header),
+            Err(CliqueError::NotChild {
+                number: header.number,
+                head: u64::MAX
+            })
+        );
+    }
+
+    #[test]
+    fn signers_take_turns() {
+        let keys = keys();
+        let signers = keys[..3]
+            .iter()
+            .map(secret_key_to_address)
+            .collect::<Vec<_>>();
+        let genesis = genesis(&signers);
+        let mut snapshot = CliqueSnapshot::new(CLIQUE, &genesis).unwrap();
+        assert!(snapshot.signers().eq(signers.iter()));
+
+        // each signer seals in turn, through a checkpoint
+        let mut headers = vec![];
+        let mut parent = genesis.clone();
+        let mut builder = snapshot.clone();
+        for number in 1..=12 {
+            let key = &keys[number % 3];
+            let header = child(&builder, &parent, key, None);
+            assert_eq!(header.difficulty, U256::from(DIFF_IN_TURN));
+            builder.apply(&header).unwrap();
+            parent = header.clone();
+            headers.push(header);
+        }
+        snapshot
+            .validate_headers(&BlockHeaders(headers.clone()))
+            .unwrap();
+        assert_eq!(snapshot, builder);
+        assert_eq!(snapshot.number(), 12);
+        assert_eq!(snapshot.hash(), parent.hash());
+
+        // a reverse response is applied from its last header
+        let mut reverse = CliqueSnapshot::new(CLIQUE, &genesis).unwrap();
+        headers.reverse();
+        reverse.validate_headers(&BlockHeaders(headers)).unwrap();
+        assert_eq!(reverse, snapshot);
+    }
+
+    #[test]
+    fn reject_forged_headers() {
+        let keys = keys();
+        let signers = keys[..3]
+            .iter()
+            .map(secret_key_to_address)
+            .collect::<Vec<_>>();
+        let genesis = genesis(&signers);
+        let snapshot = CliqueSnapshot::new(CLIQUE, &genesis).unwrap();
+
+        // sealed by an account which is not a signer
+        let outsider = secret_key_to_address(&keys[3]);
+        let forged = child(&snapshot, &genesis, &keys[3], None);
+        assert_eq!(
+            snapshot.clone().apply(&forged),
+            Err(CliqueError::UnauthorizedSigner(outsider))
+        );
+
+        // claiming to be in turn when out of turn
+        let mut wrong = child(&snapshot, &genesis, &keys[0], None);
+        wrong.difficulty = DIFF_IN_TURN.into();
+        let wrong = seal(wrong, &keys[0]);
+        assert_eq!(
+            snapshot.clone().apply(&wrong),
+            Err(CliqueError::WrongDifficulty {
+                expected: DIFF_NO_TURN.into(),
+                got: DIFF_IN_TURN.into(),
+            })
+        );
+
+        // sealing twice within the limit of two blocks
+        let mut applied = snapshot.clone();
+        let first = child(&applied, &genesis, &keys[1], None);
+        applied.apply(&first).unwrap();
+        let second = child(&applied, &first, &keys[1], None);
+        assert_eq!(
+            applied.clone().apply(&second),
+            Err(CliqueError::RecentlySigned(signers[1]))
+        );
+
+        // an invalid header in a response leaves the snapshot unchanged
+        let valid = child(&applied, &first, &keys[2], None);
+        let mut validated = snapshot.clone();
+        assert_eq!(
+            validated.validate_headers(&BlockHeaders(vec![first, second, valid])),
+            Err(InvalidCliqueHeader {
+                index: 1,
+                error: CliqueError::RecentlySigned(signers[1]),
+            })
+        );
+        assert_eq!(validated, snapshot);
+
+        // headers must extend the snapshot
+        let mut orphan = child(&snapshot, &genesis, &keys[1], None);
+        orphan.parent_hash = H256::repeat_byte(1);
+        assert!(matches!(
+            snapshot.clone().apply(&orphan),
+            Err(CliqueError::NotChild { head: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn vote_signers_in_and_out() {
+        let keys = keys();
+        let addresses = keys.iter().map(secret_key_to_address).collect::<Vec<_>>();
+        let genesis = genesis(&addresses[..2]);
+        let mut snapshot = CliqueSnapshot::new(CLIQUE, &genesis).unwrap();
+
+        // with two signers, both need to vote to add a third
+        let first = child(&snapshot, &genesis, &keys[1], Some((addresses[2], true)));
+        snapshot.apply(&first).unwrap();
+        assert_eq!(snapshot.signers().count(), 2);
+        let second = child(&snapshot, &first, &keys[0], Some((addresses[2], true)));
+        snapshot.apply(&second).unwrap();
+        assert!(snapshot.signers().any(|signer| *signer == addresses[2]));
+
+        // the new signer can seal blocks
+        let third = child(&snapshot, &second, &keys[2], None);
+        snapshot.apply(&third).unwrap();
+
+        // two of the three signers vote to remove the first signer
+        let fourth = child(&snapshot, &third, &keys[1], Some((addresses[0], false)));
+        snapshot.apply(&fourth).unwrap();
+        assert_eq!(snapshot.signers().count(), 3);
+        let fifth = child(&snapshot, &fourth, &keys[0], Some((addresses[0], false)));
+        snapshot.apply(&fifth).unwrap();
+        assert!(snapshot.signers().eq(addresses[1..3].iter()));
+
+        // the removed signer can no longer seal blocks
+        let removed = child(&snapshot, &fifth, &keys[0], None);
+        assert_eq!(
+            snapshot.apply(&removed),
+            Err(CliqueError::UnauthorizedSigner(addresses[0]))
+        );
+    }
+
+    #[test]
+    fn pending_vote_passes_after_signer_removal() {
+        let keys = keys();
+        let addresses = keys.iter().map(secret_key_to_address).collect::<Vec<_>>();
+        let account = Address::repeat_byte(0xaa);
+        let genesis = genesis(&addresses);
+        let mut snapshot = CliqueSnapshot::new(CLIQUE, &genesis).unwrap();
+
+        // two of the four signers vote to add the account, which is not a majority
+        let mut parent = genesis;
+        let votes = [
+            (0, (account, true)),
+            (1, (account, true)),
+            (2, (addresses[3], false)),
+            (0, (addresses[3], false)),
+            (1, (addresses[3], false)),
+        ];
+        for (signer, vote) in votes {
+            let header = child(&snapshot, &parent, &keys[signer], Some(vote));
+            snapshot.apply(&header).unwrap();
+            parent = header;
+        }
+        assert!(snapshot.signers().eq(addresses[..3].iter()));
+
+        // with three signers the two votes are a majority, which the next vote on the account
+        // settles even if it is a vote to drop it
+        let header = child(&snapshot, &parent, &keys[2], Some((account, false)));
+        snapshot.apply(&header).unwrap();
+        assert!(snapshot.signers().any(|signer| *signer == account));
+    }
+}
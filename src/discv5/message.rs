@@ -0,0 +1,355 @@
+use crate::enr::Enr;
+use bytes::{Buf, BufMut, Bytes};
+use open_fastrlp::{
+    Decodable, DecodeError, Encodable, Header, RlpDecodable, RlpEncodable, RlpEncodableWrapper,
+};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// The maximum length of a request id, in bytes.
+pub const MAX_REQUEST_ID_LENGTH: usize = 8;
+
+/// An opaque request id, chosen by the requester and echoed in every response.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, RlpEncodableWrapper)]
+pub struct RequestId(pub Bytes);
+
+impl From<u64> for RequestId {
+    fn from(id: u64) -> Self {
+        RequestId(Bytes::copy_from_slice(&id.to_be_bytes()))
+    }
+}
+
+/// Decodes a request id, rejecting ids longer than [`MAX_REQUEST_ID_LENGTH`] bytes.
+impl Decodable for RequestId {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let id = Bytes::decode(buf)?;
+        if id.len() > MAX_REQUEST_ID_LENGTH {
+            return Err(DecodeError::Custom("request id longer than 8 bytes"));
+        }
+        Ok(RequestId(id))
+    }
+}
+
+/// Checks that the recipient knows the sender's latest node record sequence number.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct Ping {
+    /// The request id.
+    pub request_id: RequestId,
+    /// The sequence number of the sender's node record.
+    pub enr_seq: u64,
+}
+
+/// The response to a [`Ping`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pong {
+    /// The request id of the [`Ping`].
+    pub request_id: RequestId,
+    /// The sequence number of the responder's node record.
+    pub enr_seq: u64,
+    /// The IP address the [`Ping`] was received from.
+    pub recipient_ip: IpAddr,
+    /// The UDP port the [`Ping`] was received from.
+    pub recipient_port: u16,
+}
+
+fn ip_length(ip: &IpAddr) -> usize {
+    match ip {
+        IpAddr::V4(ip) => ip.octets().length(),
+        IpAddr::V6(ip) => ip.octets().length(),
+    }
+}
+
+impl Pong {
+    fn payload_length(&self) -> usize {
+        self.request_id.length()
+            + self.enr_seq.length()
+            + ip_length(&self.recipient_ip)
+            + self.recipient_port.length()
+    }
+}
+
+impl Encodable for Pong {
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + open_fastrlp::length_of_length(payload_length)
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header {
+            list: true,
+            payload_length: self.payload_length(),
+        }
+        .encode(out);
+        self.request_id.encode(out);
+        self.enr_seq.encode(out);
+        match self.recipient_ip {
+            IpAddr::V4(ip) => ip.octets().encode(out),
+            IpAddr::V6(ip) => ip.octets().encode(out),
+        }
+        self.recipient_port.encode(out);
+    }
+}
+
+impl Decodable for Pong {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(DecodeError::UnexpectedString);
+        }
+        let payload = &mut &buf[..header.payload_length];
+
+        let request_id = RequestId::decode(payload)?;
+        let enr_seq = u64::decode(payload)?;
+        let ip = Bytes::decode(payload)?;
+        let recipient_ip = match ip.len() {
+            4 => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(&ip[..]).unwrap())),
+            16 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&ip[..]).unwrap())),
+            _ => return Err(DecodeError::Custom("invalid recipient ip length")),
+        };
+        let recipient_port = u16::decode(payload)?;
+        if !payload.is_empty() {
+            return Err(DecodeError::ListLengthMismatch {
+                expected: header.payload_length,
+                got: header.payload_length - payload.len(),
+            });
+        }
+
+        buf.advance(header.payload_length);
+        Ok(Self {
+            request_id,
+            enr_seq,
+            recipient_ip,
+            recipient_port,
+        })
+    }
+}
+
+/// Requests the node records in the given log2 distances from the recipient's node id.
+///
+/// A distance of zero requests the recipient's own record.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct FindNode {
+    /// The request id.
+    pub request_id: RequestId,
+    /// The requested distances, each in the range `0..=256`.
+    pub distances: Vec<u16>,
+}
+
+/// A response to [`FindNode`]. Large responses are split into multiple `Nodes` messages, each
+/// carrying the total number of messages in the response.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct Nodes {
+    /// The request id of the [`FindNode`].
+    pub request_id: RequestId,
+    /// The total number of `Nodes` messages in the response.
+    pub total: u64,
+    /// The node records.
+    pub enrs: Vec<Enr>,
+}
+
+/// An application-level request, tunnelled over discv5.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct TalkReq {
+    /// The request id.
+    pub request_id: RequestId,
+    /// The name of the application protocol.
+    pub protocol: Bytes,
+    /// The application request.
+    pub request: Bytes,
+}
+
+/// The response to a [`TalkReq`]. An empty response means the protocol is unknown to the
+/// recipient.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct TalkResp {
+    /// The request id of the [`TalkReq`].
+    pub request_id: RequestId,
+    /// The application response.
+    pub response: Bytes,
+}
+
+/// Represents message types for discv5 messages.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Discv5MessageID {
+    Ping = 0x01,
+    Pong = 0x02,
+    FindNode = 0x03,
+    Nodes = 0x04,
+    TalkReq = 0x05,
+    TalkResp = 0x06,
+}
+
+impl Encodable for Discv5MessageID {
+    fn length(&self) -> usize {
+        1
+    }
+    fn encode(&self, out: &mut dyn BufMut) {
+        out.put_u8(*self as u8);
+    }
+}
+
+impl Decodable for Discv5MessageID {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let id = buf.first().ok_or(DecodeError::InputTooShort)?;
+        let id = match id {
+            0x01 => Discv5MessageID::Ping,
+            0x02 => Discv5MessageID::Pong,
+            0x03 => Discv5MessageID::FindNode,
+            0x04 => Discv5MessageID::Nodes,
+            0x05 => Discv5MessageID::TalkReq,
+            0x06 => Discv5MessageID::TalkResp,
+            _ => return Err(DecodeError::Custom("Invalid message ID")),
+        };
+        buf.advance(1);
+        Ok(id)
+    }
+}
+
+/// A discv5 message, the plaintext of an encrypted [`Packet`](super::Packet).
+///
+/// Messages are encoded as `message-type || rlp(message-data)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Discv5Message {
+    Ping(Ping),
+    Pong(Pong),
+    FindNode(FindNode),
+    Nodes(Nodes),
+    TalkReq(TalkReq),
+    TalkResp(TalkResp),
+}
+
+impl Discv5Message {
+    /// Returns the message's type.
+    pub fn message_id(&self) -> Discv5MessageID {
+        match self {
+            Discv5Message::Ping(_) => Discv5MessageID::Ping,
+            Discv5Message::Pong(_) => Discv5MessageID::Pong,
+            Discv5Message::FindNode(_) => Discv5MessageID::FindNode,
+            Discv5Message::Nodes(_) => Discv5MessageID::Nodes,
+            Discv5Message::TalkReq(_) => Discv5MessageID::TalkReq,
+            Discv5Message::TalkResp(_) => Discv5MessageID::TalkResp,
+        }
+    }
+
+    /// Returns the request id of the message.
+    pub fn request_id(&self) -> &RequestId {
+        match self {
+            Discv5Message::Ping(ping) => &ping.request_id,
+            Discv5Message::Pong(pong) => &pong.request_id,
+            Discv5Message::FindNode(find_node) => &find_node.request_id,
+            Discv5Message::Nodes(nodes) => &nodes.request_id,
+            Discv5Message::TalkReq(talk_req) => &talk_req.request_id,
+            Discv5Message::TalkResp(talk_resp) => &talk_resp.request_id,
+        }
+    }
+}
+
+impl Encodable for Discv5Message {
+    fn length(&self) -> usize {
+        1 + match self {
+            Discv5Message::Ping(ping) => ping.length(),
+            Discv5Message::Pong(pong) => pong.length(),
+            Discv5Message::FindNode(find_node) => find_node.length(),
+            Discv5Message::Nodes(nodes) => nodes.length(),
+            Discv5Message::TalkReq(talk_req) => talk_req.length(),
+            Discv5Message::TalkResp(talk_resp) => talk_resp.length(),
+        }
+    }
+    fn encode(&self, out: &mut dyn BufMut) {
+        self.message_id().encode(out);
+        match self {
+            Discv5Message::Ping(ping) => ping.encode(out),
+            Discv5Message::Pong(pong) => pong.encode(out),
+            Discv5Message::FindNode(find_node) => find_node.encode(out),
+            Discv5Message::Nodes(nodes) => nodes.encode(out),
+            Discv5Message::TalkReq(talk_req) => talk_req.encode(out),
+            Discv5Message::TalkResp(talk_resp) => talk_resp.encode(out),
+        }
+    }
+}
+
+/// Decodes a message, using the first byte to determine the message type.
+impl Decodable for Discv5Message {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(match Discv5MessageID::decode(buf)? {
+            Discv5MessageID::Ping => Discv5Message::Ping(Ping::decode(buf)?),
+            Discv5MessageID::Pong => Discv5Message::Pong(Pong::decode(buf)?),
+            Discv5MessageID::FindNode => Discv5Message::FindNode(FindNode::decode(buf)?),
+            Discv5MessageID::Nodes => Discv5Message::Nodes(Nodes::decode(buf)?),
+            Discv5MessageID::TalkReq => Discv5Message::TalkReq(TalkReq::decode(buf)?),
+            Discv5MessageID::TalkResp => Discv5Message::TalkResp(TalkResp::decode(buf)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Discv5Message, FindNode, Ping, Pong, RequestId, TalkReq};
+    use hex_literal::hex;
+    use open_fastrlp::{Decodable, Encodable};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn encode<T: Encodable>(value: T) -> Vec<u8> {
+        let mut buf = vec![];
+        value.encode(&mut buf);
+        buf
+    }
+
+    #[test]
+    fn encode_ping() {
+        // Message from the discv5 wire test vectors
+        let ping = Discv5Message::Ping(Ping {
+            request_id: RequestId(hex!("00000001").as_slice().into()),
+            enr_seq: 2,
+        });
+        assert_eq!(encode(ping), hex!("01c6840000000102"));
+    }
+
+    #[test]
+    fn decode_ping() {
+        let data = hex!("01c6840000000102");
+        let expected = Discv5Message::Ping(Ping {
+            request_id: RequestId(hex!("00000001").as_slice().into()),
+            enr_seq: 2,
+        });
+        assert_eq!(Discv5Message::decode(&mut &data[..]).unwrap(), expected);
+    }
+
+    #[test]
+    fn pong_roundtrip() {
+        let pong = Discv5Message::Pong(Pong {
+            request_id: RequestId::from(1u64),
+            enr_seq: 3,
+            recipient_ip: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+            recipient_port: 30303,
+        });
+        let encoded = encode(pong.clone());
+        assert_eq!(pong.length(), encoded.len());
+        assert_eq!(Discv5Message::decode(&mut &encoded[..]).unwrap(), pong);
+    }
+
+    #[test]
+    fn find_node_and_talk_req_roundtrip() {
+        let find_node = Discv5Message::FindNode(FindNode {
+            request_id: RequestId::from(7u64),
+            distances: vec![0, 255, 256],
+        });
+        let encoded = encode(find_node.clone());
+        assert_eq!(Discv5Message::decode(&mut &encoded[..]).unwrap(), find_node);
+
+        let talk_req = Discv5Message::TalkReq(TalkReq {
+            request_id: RequestId::from(8u64),
+            protocol: b"portal".as_slice().into(),
+            request: hex!("deadbeef").as_slice().into(),
+        });
+        let encoded = encode(talk_req.clone());
+        assert_eq!(Discv5Message::decode(&mut &encoded[..]).unwrap(), talk_req);
+    }
+
+    #[test]
+    fn reject_long_request_id() {
+        // ping with a 9 byte request id
+        let data = hex!("01cb8900000000000000000102");
+        assert!(Discv5Message::decode(&mut &data[..]).is_err());
+    }
+}
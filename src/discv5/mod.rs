@@ -0,0 +1,66 @@
+//! Types for the [Node Discovery v5](https://github.com/ethereum/devp2p/blob/master/discv5/discv5-wire.md)
+//! wire protocol.
+use open_fastrlp::DecodeError;
+use thiserror::Error;
+
+mod message;
+pub use message::{
+    Discv5Message, Discv5MessageID, FindNode, Nodes, Ping, Pong, RequestId, TalkReq, TalkResp,
+    MAX_REQUEST_ID_LENGTH,
+};
+
+mod packet;
+pub use packet::{
+    Packet, PacketHeader, PacketKind, MASKING_IV_SIZE, MAX_PACKET_SIZE, MIN_PACKET_SIZE,
+    NONCE_SIZE, PROTOCOL_ID, PROTOCOL_VERSION, STATIC_HEADER_SIZE,
+};
+
+mod session;
+pub use session::{
+    decrypt_message, derive_session_keys, encrypt_message, id_sign, id_verify, Session, SessionKeys,
+};
+
+/// Errors that can occur while decoding or handling discv5 packets.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum Discv5Error {
+    /// The packet is smaller than [`MIN_PACKET_SIZE`].
+    #[error("packet is smaller than {MIN_PACKET_SIZE} bytes")]
+    PacketTooSmall,
+    /// The packet is larger than [`MAX_PACKET_SIZE`].
+    #[error("packet is larger than {MAX_PACKET_SIZE} bytes")]
+    PacketTooLarge,
+    /// The packet header does not start with [`PROTOCOL_ID`], usually because the packet was not
+    /// meant for the local node.
+    #[error("invalid protocol id")]
+    InvalidProtocolId,
+    /// The packet header has an unknown protocol version.
+    #[error("unsupported protocol version: {0}")]
+    UnsupportedVersion(u16),
+    /// The packet header has an unknown flag.
+    #[error("invalid packet flag: {0}")]
+    InvalidFlag(u8),
+    /// The authdata does not match the packet flag.
+    #[error("invalid packet authdata")]
+    InvalidAuthData,
+    /// The message data is invalid for the kind of packet.
+    #[error("invalid packet message")]
+    InvalidMessage,
+    /// The packet is not of the kind expected at this point of the handshake.
+    #[error("unexpected packet kind")]
+    UnexpectedPacket,
+    /// The record of the remote node is not known, or does not match its node id.
+    #[error("unknown node record")]
+    UnknownRecord,
+    /// The ephemeral public key of a handshake is invalid.
+    #[error("invalid ephemeral public key")]
+    InvalidPublicKey,
+    /// The id signature of a handshake is invalid.
+    #[error("invalid id signature")]
+    InvalidIdSignature,
+    /// The message could not be decrypted with the session keys.
+    #[error("message decryption failed")]
+    DecryptionFailed,
+    /// The decrypted message or a node record could not be decoded.
+    #[error(transparent)]
+    Rlp(#[from] DecodeError),
+}
@@ -0,0 +1,359 @@
+use super::Discv5Error;
+use crate::enr::{Enr, NodeId};
+use aes::Aes128;
+use bytes::Bytes;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use open_fastrlp::{Decodable, Encodable};
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+/// The protocol id at the start of every packet header.
+pub const PROTOCOL_ID: &[u8; 6] = b"discv5";
+
+/// The protocol version, encoded in every packet header.
+pub const PROTOCOL_VERSION: u16 = 0x0001;
+
+/// The size of the masking iv, which prefixes every packet.
+pub const MASKING_IV_SIZE: usize = 16;
+
+/// The size of the static part of the packet header: protocol id, version, flag, nonce and
+/// authdata size.
+pub const STATIC_HEADER_SIZE: usize = 23;
+
+/// The smallest valid packet size.
+pub const MIN_PACKET_SIZE: usize = 63;
+
+/// The largest valid packet size.
+pub const MAX_PACKET_SIZE: usize = 1280;
+
+/// The size of a packet nonce.
+pub const NONCE_SIZE: usize = 12;
+
+/// The size of the AES-GCM authentication tag appended to every encrypted message.
+const TAG_SIZE: usize = 16;
+
+/// The kind of packet, together with its authdata.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PacketKind {
+    /// An ordinary message packet, flag `0`.
+    Message {
+        /// The node id of the sender.
+        src_id: NodeId,
+    },
+    /// A `WHOAREYOU` challenge, flag `1`, sent when a message packet can not be decrypted.
+    WhoAreYou {
+        /// Random data to be signed by the handshake initiator.
+        id_nonce: [u8; 16],
+        /// The highest sequence number of the initiator's node record known by the sender, or
+        /// zero if the record is unknown.
+        enr_seq: u64,
+    },
+    /// A handshake message packet, flag `2`, sent in response to a `WHOAREYOU` challenge.
+    Handshake {
+        /// The node id of the sender.
+        src_id: NodeId,
+        /// The signature over the challenge, proving the sender owns `src_id`.
+        id_signature: Bytes,
+        /// The compressed ephemeral public key used for the key agreement.
+        ephemeral_pubkey: Bytes,
+        /// The sender's node record, included when the challenge `enr_seq` is out of date.
+        record: Option<Enr>,
+    },
+}
+
+impl PacketKind {
+    /// Returns the flag identifying this kind of packet.
+    pub const fn flag(&self) -> u8 {
+        match self {
+            PacketKind::Message { .. } => 0,
+            PacketKind::WhoAreYou { .. } => 1,
+            PacketKind::Handshake { .. } => 2,
+        }
+    }
+
+    /// Encodes the authdata of the packet.
+    pub fn authdata(&self) -> Vec<u8> {
+        match self {
+            PacketKind::Message { src_id } => src_id.to_vec(),
+            PacketKind::WhoAreYou { id_nonce, enr_seq } => {
+                let mut authdata = Vec::with_capacity(24);
+                authdata.extend_from_slice(id_nonce);
+                authdata.extend_from_slice(&enr_seq.to_be_bytes());
+                authdata
+            }
+            PacketKind::Handshake {
+                src_id,
+                id_signature,
+                ephemeral_pubkey,
+                record,
+            } => {
+                let mut authdata = Vec::with_capacity(34 + id_signature.len() + 33);
+                authdata.extend_from_slice(src_id);
+                authdata.push(id_signature.len() as u8);
+                authdata.push(ephemeral_pubkey.len() as u8);
+                authdata.extend_from_slice(id_signature);
+                authdata.extend_from_slice(ephemeral_pubkey);
+                if let Some(record) = record {
+                    record.encode(&mut authdata);
+                }
+                authdata
+            }
+        }
+    }
+
+    /// Decodes the authdata of a packet with the given flag.
+    pub fn decode(flag: u8, authdata: &[u8]) -> Result<Self, Discv5Error> {
+        match flag {
+            0 => {
+                let src_id = authdata
+                    .try_into()
+                    .map_err(|_| Discv5Error::InvalidAuthData)?;
+                Ok(PacketKind::Message { src_id })
+            }
+            1 => {
+                if authdata.len() != 24 {
+                    return Err(Discv5Error::InvalidAuthData);
+                }
+                let mut id_nonce = [0; 16];
+                id_nonce.copy_from_slice(&authdata[..16]);
+                let mut enr_seq = [0; 8];
+                enr_seq.copy_from_slice(&authdata[16..]);
+                Ok(PacketKind::WhoAreYou {
+                    id_nonce,
+                    enr_seq: u64::from_be_bytes(enr_seq),
+                })
+            }
+            2 => {
+                if authdata.len() < 34 {
+                    return Err(Discv5Error::InvalidAuthData);
+                }
+                let mut src_id = [0; 32];
+                src_id.copy_from_slice(&authdata[..32]);
+                let signature_size = authdata[32] as usize;
+                let key_size = authdata[33] as usize;
+
+                let rest = &authdata[34..];
+                if rest.len() < signature_size + key_size {
+                    return Err(Discv5Error::InvalidAuthData);
+                }
+                let id_signature = Bytes::copy_from_slice(&rest[..signature_size]);
+                let ephemeral_pubkey =
+                    Bytes::copy_from_slice(&rest[signature_size..signature_size + key_size]);
+
+                let mut record_data = &rest[signature_size + key_size..];
+                let record = if record_data.is_empty() {
+                    None
+                } else {
+                    let record = Enr::decode(&mut record_data)?;
+                    if !record_data.is_empty() {
+                        return Err(Discv5Error::InvalidAuthData);
+                    }
+                    Some(record)
+                };
+
+                Ok(PacketKind::Handshake {
+                    src_id,
+                    id_signature,
+                    ephemeral_pubkey,
+                    record,
+                })
+            }
+            flag => Err(Discv5Error::InvalidFlag(flag)),
+        }
+    }
+}
+
+/// An unmasked packet header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PacketHeader {
+    /// The nonce of the packet. For message packets, this is the AES-GCM nonce of the message.
+    pub nonce: [u8; NONCE_SIZE],
+    /// The kind of packet, including its authdata.
+    pub kind: PacketKind,
+}
+
+impl PacketHeader {
+    /// Encodes the header as `static-header || authdata`.
+    pub fn encode(&self) -> Vec<u8> {
+        let authdata = self.kind.authdata();
+        let mut header = Vec::with_capacity(STATIC_HEADER_SIZE + authdata.len());
+        header.extend_from_slice(PROTOCOL_ID);
+        header.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+        header.push(self.kind.flag());
+        header.extend_from_slice(&self.nonce);
+        header.extend_from_slice(&(authdata.len() as u16).to_be_bytes());
+        header.extend_from_slice(&authdata);
+        header
+    }
+}
+
+/// A discv5 packet, as defined in the
+/// [wire protocol specification](https://github.com/ethereum/devp2p/blob/master/discv5/discv5-wire.md).
+///
+/// Packets are encoded as `masking-iv || masked-header || message`, where the header is masked
+/// with AES-128-CTR, keyed by the first 16 bytes of the recipient's node id. The message is the
+/// AES-GCM encrypted [`Discv5Message`](super::Discv5Message), and is empty for `WHOAREYOU`
+/// packets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Packet {
+    /// The random iv used to mask the header.
+    pub masking_iv: [u8; MASKING_IV_SIZE],
+    /// The unmasked header.
+    pub header: PacketHeader,
+    /// The encrypted message.
+    pub message: Bytes,
+}
+
+impl Packet {
+    /// Creates a `WHOAREYOU` packet, in response to a packet with the given nonce.
+    pub fn new_whoareyou(
+        masking_iv: [u8; MASKING_IV_SIZE],
+        request_nonce: [u8; NONCE_SIZE],
+        id_nonce: [u8; 16],
+        enr_seq: u64,
+    ) -> Self {
+        Self {
+            masking_iv,
+            header: PacketHeader {
+                nonce: request_nonce,
+                kind: PacketKind::WhoAreYou { id_nonce, enr_seq },
+            },
+            message: Bytes::new(),
+        }
+    }
+
+    /// Returns `masking-iv || header`, which is used as the additional data when encrypting
+    /// the message.
+    ///
+    /// For `WHOAREYOU` packets this is the `challenge-data` which is used in the handshake.
+    pub fn authenticated_data(&self) -> Vec<u8> {
+        let header = self.header.encode();
+        let mut data = Vec::with_capacity(MASKING_IV_SIZE + header.len());
+        data.extend_from_slice(&self.masking_iv);
+        data.extend_from_slice(&header);
+        data
+    }
+
+    /// Encodes the packet for the node with the given id.
+    pub fn encode(&self, dest_id: &NodeId) -> Vec<u8> {
+        let mut header = self.header.encode();
+        let mut cipher = Aes128Ctr::new(dest_id[..16].into(), &self.masking_iv.into());
+        cipher.apply_keystream(&mut header);
+
+        let mut packet = Vec::with_capacity(MASKING_IV_SIZE + header.len() + self.message.len());
+        packet.extend_from_slice(&self.masking_iv);
+        packet.extend_from_slice(&header);
+        packet.extend_from_slice(&self.message);
+        packet
+    }
+
+    /// Decodes a packet sent to the node with the given id.
+    pub fn decode(local_id: &NodeId, data: &[u8]) -> Result<Self, Discv5Error> {
+        if data.len() < MIN_PACKET_SIZE {
+            return Err(Discv5Error::PacketTooSmall);
+        }
+        if data.len() > MAX_PACKET_SIZE {
+            return Err(Discv5Error::PacketTooLarge);
+        }
+
+        let mut masking_iv = [0; MASKING_IV_SIZE];
+        masking_iv.copy_from_slice(&data[..MASKING_IV_SIZE]);
+        let mut cipher = Aes128Ctr::new(local_id[..16].into(), &masking_iv.into());
+
+        let header_start = MASKING_IV_SIZE;
+        let authdata_start = header_start + STATIC_HEADER_SIZE;
+        let mut static_header = [0; STATIC_HEADER_SIZE];
+        static_header.copy_from_slice(&data[header_start..authdata_start]);
+        cipher.apply_keystream(&mut static_header);
+
+        if static_header[..6] != PROTOCOL_ID[..] {
+            return Err(Discv5Error::InvalidProtocolId);
+        }
+        let version = u16::from_be_bytes([static_header[6], static_header[7]]);
+        if version != PROTOCOL_VERSION {
+            return Err(Discv5Error::UnsupportedVersion(version));
+        }
+        let flag = static_header[8];
+        let mut nonce = [0; NONCE_SIZE];
+        nonce.copy_from_slice(&static_header[9..21]);
+        let authdata_size = u16::from_be_bytes([static_header[21], static_header[22]]) as usize;
+
+        let message_start = authdata_start + authdata_size;
+        if data.len() < message_start {
+            return Err(Discv5Error::InvalidAuthData);
+        }
+        let mut authdata = data[authdata_start..message_start].to_vec();
+        cipher.apply_keystream(&mut authdata);
+        let kind = PacketKind::decode(flag, &authdata)?;
+
+        let message = Bytes::copy_from_slice(&data[message_start..]);
+        match kind {
+            PacketKind::WhoAreYou { .. } if !message.is_empty() => {
+                return Err(Discv5Error::InvalidMessage)
+            }
+            PacketKind::Message { .. } | PacketKind::Handshake { .. }
+                if message.len() < TAG_SIZE =>
+            {
+                return Err(Discv5Error::InvalidMessage)
+            }
+            _ => {}
+        }
+
+        Ok(Self {
+            masking_iv,
+            header: PacketHeader { nonce, kind },
+            message,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Packet, PacketHeader, PacketKind};
+    use hex_literal::hex;
+
+    const SRC_ID: [u8; 32] =
+        hex!("aaaa8419e9f49d0083561b48287df592939a8d19947d8c0ef88f2a4856a69fbb");
+    const DEST_ID: [u8; 32] =
+        hex!("bbbb9d047f0488c0b5a93c1c3f2d8bafc7c8ff337024a55434a0d0555de64db9");
+
+    #[test]
+    // Test vector from the discv5 wire test vectors
+    fn decode_whoareyou_packet() {
+        let data = hex!("00000000000000000000000000000000088b3d434277464933a1ccc59f5967ad1d6035f15e528627dde75cd68292f9e6c27d6b66c8100a873fcbaed4e16b8d");
+        let packet = Packet::decode(&DEST_ID, &data).unwrap();
+        let expected = Packet::new_whoareyou(
+            [0; 16],
+            hex!("0102030405060708090a0b0c"),
+            hex!("0102030405060708090a0b0c0d0e0f10"),
+            0,
+        );
+        assert_eq!(packet, expected);
+        assert_eq!(packet.encode(&DEST_ID), data);
+        assert_eq!(
+            packet.authenticated_data(),
+            hex!("000000000000000000000000000000006469736376350001010102030405060708090a0b0c00180102030405060708090a0b0c0d0e0f100000000000000000")
+        );
+    }
+
+    #[test]
+    // Test vector from the discv5 wire test vectors
+    fn decode_ping_message_packet() {
+        let data = hex!("00000000000000000000000000000000088b3d4342774649325f313964a39e55ea96c005ad52be8c7560413a7008f16c9e6d2f43bbea8814a546b7409ce783d34c4f53245d08dab84102ed931f66d1492acb308fa1c6715b9d139b81acbdcc");
+        let packet = Packet::decode(&DEST_ID, &data).unwrap();
+        assert_eq!(
+            packet.header,
+            PacketHeader {
+                nonce: hex!("ffffffffffffffffffffffff"),
+                kind: PacketKind::Message { src_id: SRC_ID },
+            }
+        );
+        assert_eq!(packet.encode(&DEST_ID), data);
+    }
+
+    #[test]
+    fn reject_packet_for_other_node() {
+        let data = hex!("00000000000000000000000000000000088b3d434277464933a1ccc59f5967ad1d6035f15e528627dde75cd68292f9e6c27d6b66c8100a873fcbaed4e16b8d");
+        assert!(Packet::decode(&SRC_ID, &data).is_err());
+    }
+}
@@ -0,0 +1,484 @@
+use super::{Discv5Error, Discv5Message, Packet, PacketHeader, PacketKind};
+use crate::enr::{Enr, NodeId};
+use aes_gcm::{
+    aead::{Aead, Payload},
+    Aes128Gcm, KeyInit,
+};
+use bytes::Bytes;
+use hkdf::Hkdf;
+use k256::{
+    ecdsa::{
+        signature::hazmat::{PrehashSigner, PrehashVerifier},
+        Signature, SigningKey, VerifyingKey,
+    },
+    elliptic_curve::sec1::ToEncodedPoint,
+    ProjectivePoint,
+};
+use open_fastrlp::{Decodable, Encodable};
+use sha2::{Digest, Sha256};
+
+const KEY_AGREEMENT_STRING: &[u8] = b"discovery v5 key agreement";
+const ID_SIGNATURE_TEXT: &[u8] = b"discovery v5 identity proof";
+
+/// The symmetric keys of a session, derived during the handshake.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionKeys {
+    /// The key used by the handshake initiator to encrypt messages.
+    pub initiator_key: [u8; 16],
+    /// The key used by the handshake recipient to encrypt messages.
+    pub recipient_key: [u8; 16],
+}
+
+/// Computes the compressed 33 byte shared secret of `secret` and `public`.
+fn ecdh(secret: &SigningKey, public: &VerifyingKey) -> [u8; 33] {
+    let shared = (ProjectivePoint::from(*public.as_affine()) * secret.as_nonzero_scalar().as_ref())
+        .to_affine();
+    let mut out = [0; 33];
+    out.copy_from_slice(shared.to_encoded_point(true).as_bytes());
+    out
+}
+
+/// Derives the session keys from the key agreement between `secret` and `public`.
+///
+/// The initiator calls this with its ephemeral key and the recipient's static public key, while
+/// the recipient uses its static key and the initiator's ephemeral public key.
+pub fn derive_session_keys(
+    secret: &SigningKey,
+    public: &VerifyingKey,
+    initiator_id: &NodeId,
+    recipient_id: &NodeId,
+    challenge_data: &[u8],
+) -> SessionKeys {
+    let shared_secret = ecdh(secret, public);
+
+    let mut info = Vec::with_capacity(KEY_AGREEMENT_STRING.len() + 64);
+    info.extend_from_slice(KEY_AGREEMENT_STRING);
+    info.extend_from_slice(initiator_id);
+    info.extend_from_slice(recipient_id);
+
+    let mut key_data = [0; 32];
+    Hkdf::<Sha256>::new(Some(challenge_data), &shared_secret)
+        .expand(&info, &mut key_data)
+        .expect("32 bytes is a valid hkdf output length; qed");
+
+    let mut keys = SessionKeys {
+        initiator_key: [0; 16],
+        recipient_key: [0; 16],
+    };
+    keys.initiator_key.copy_from_slice(&key_data[..16]);
+    keys.recipient_key.copy_from_slice(&key_data[16..]);
+    keys
+}
+
+fn id_signature_hash(challenge_data: &[u8], ephemeral_pubkey: &[u8], dest_id: &NodeId) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(ID_SIGNATURE_TEXT);
+    hasher.update(challenge_data);
+    hasher.update(ephemeral_pubkey);
+    hasher.update(dest_id);
+    hasher.finalize().to_vec()
+}
+
+/// Creates the handshake signature proving ownership of `static_key`.
+pub fn id_sign(
+    static_key: &SigningKey,
+    challenge_data: &[u8],
+    ephemeral_pubkey: &[u8],
+    dest_id: &NodeId,
+) -> Result<[u8; 64], Discv5Error> {
+    let hash = id_signature_hash(challenge_data, ephemeral_pubkey, dest_id);
+    let signature: Signature = static_key
+        .sign_prehash(&hash)
+        .map_err(|_| Discv5Error::InvalidIdSignature)?;
+    let mut out = [0; 64];
+    out.copy_from_slice(&signature.to_bytes());
+    Ok(out)
+}
+
+/// Verifies a handshake signature, created with [`id_sign`].
+pub fn id_verify(
+    public_key: &VerifyingKey,
+    id_signature: &[u8],
+    challenge_data: &[u8],
+    ephemeral_pubkey: &[u8],
+    dest_id: &NodeId,
+) -> Result<(), Discv5Error> {
+    let signature =
+        Signature::from_slice(id_signature).map_err(|_| Discv5Error::InvalidIdSignature)?;
+    let hash = id_signature_hash(challenge_data, ephemeral_pubkey, dest_id);
+    public_key
+        .verify_prehash(&hash, &signature)
+        .map_err(|_| Discv5Error::InvalidIdSignature)
+}
+
+/// Encrypts a message with AES-128-GCM.
+pub fn encrypt_message(
+    key: &[u8; 16],
+    nonce: &[u8; 12],
+    message: &[u8],
+    authenticated_data: &[u8],
+) -> Vec<u8> {
+    Aes128Gcm::new(key.into())
+        .encrypt(
+            nonce.into(),
+            Payload {
+                msg: message,
+                aad: authenticated_data,
+            },
+        )
+        .expect("messages fit in a single packet; qed")
+}
+
+/// Decrypts a message encrypted with [`encrypt_message`].
+pub fn decrypt_message(
+    key: &[u8; 16],
+    nonce: &[u8; 12],
+    ciphertext: &[u8],
+    authenticated_data: &[u8],
+) -> Result<Vec<u8>, Discv5Error> {
+    Aes128Gcm::new(key.into())
+        .decrypt(
+            nonce.into(),
+            Payload {
+                msg: ciphertext,
+                aad: authenticated_data,
+            },
+        )
+        .map_err(|_| Discv5Error::DecryptionFailed)
+}
+
+/// An established session with a remote node.
+///
+/// Sessions are created by the handshake: the node that receives a `WHOAREYOU` challenge calls
+/// [`Session::initiate`] to answer it with a handshake packet, and the node that sent the
+/// challenge calls [`Session::accept`] when the handshake packet arrives. Both sides then use
+/// [`Session::encrypt`] and [`Session::decrypt`] for message packets.
+///
+/// Randomness, such as masking ivs, nonces and the ephemeral key, is provided by the caller.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Session {
+    local_id: NodeId,
+    remote_id: NodeId,
+    write_key: [u8; 16],
+    read_key: [u8; 16],
+}
+
+impl Session {
+    /// Answers a `WHOAREYOU` challenge from `remote`, returning the new session and the
+    /// handshake packet which carries `message`.
+    ///
+    /// The local record is included in the handshake if the challenge shows that the remote node
+    /// does not know its latest version.
+    #[allow(clippy::too_many_arguments)]
+    pub fn initiate(
+        static_key: &SigningKey,
+        ephemeral_key: &SigningKey,
+        local_record: &Enr,
+        remote: &Enr,
+        whoareyou: &Packet,
+        masking_iv: [u8; 16],
+        nonce: [u8; 12],
+        message: &Discv5Message,
+    ) -> Result<(Self, Packet), Discv5Error> {
+        let enr_seq = match whoareyou.header.kind {
+            PacketKind::WhoAreYou { enr_seq, .. } => enr_seq,
+            _ => return Err(Discv5Error::UnexpectedPacket),
+        };
+
+        let local_id = local_record.node_id();
+        let remote_id = remote.node_id();
+        let challenge_data = whoareyou.authenticated_data();
+        let keys = derive_session_keys(
+            ephemeral_key,
+            &remote.public_key(),
+            &local_id,
+            &remote_id,
+            &challenge_data,
+        );
+
+        let ephemeral_pubkey = ephemeral_key.verifying_key().to_encoded_point(true);
+        let id_signature = id_sign(
+            static_key,
+            &challenge_data,
+            ephemeral_pubkey.as_bytes(),
+            &remote_id,
+        )?;
+
+        let record = (enr_seq < local_record.seq()).then(|| local_record.clone());
+        let session = Self {
+            local_id,
+            remote_id,
+            write_key: keys.initiator_key,
+            read_key: keys.recipient_key,
+        };
+        let header = PacketHeader {
+            nonce,
+            kind: PacketKind::Handshake {
+                src_id: local_id,
+                id_signature: Bytes::copy_from_slice(&id_signature),
+                ephemeral_pubkey: Bytes::copy_from_slice(ephemeral_pubkey.as_bytes()),
+                record,
+            },
+        };
+        let packet = session.seal(masking_iv, header, message);
+        Ok((session, packet))
+    }
+
+    /// Completes the handshake started by sending `whoareyou`, returning the new session and the
+    /// message carried by the handshake packet.
+    ///
+    /// `known_record` is the record of the remote node, if it is known. It is only used when the
+    /// handshake does not include a record.
+    pub fn accept(
+        static_key: &SigningKey,
+        whoareyou: &Packet,
+        handshake: &Packet,
+        known_record: Option<&Enr>,
+    ) -> Result<(Self, Discv5Message), Discv5Error> {
+        let (src_id, id_signature, ephemeral_pubkey, record) = match &handshake.header.kind {
+            PacketKind::Handshake {
+                src_id,
+                id_signature,
+                ephemeral_pubkey,
+                record,
+            } => (src_id, id_signature, ephemeral_pubkey, record),
+            _ => return Err(Discv5Error::UnexpectedPacket),
+        };
+
+        let remote = record
+            .as_ref()
+            .or(known_record)
+            .ok_or(Discv5Error::UnknownRecord)?;
+        if remote.node_id() != *src_id {
+            return Err(Discv5Error::UnknownRecord);
+        }
+
+        let local_id = crate::enr::node_id(static_key.verifying_key());
+        let challenge_data = whoareyou.authenticated_data();
+        id_verify(
+            &remote.public_key(),
+            id_signature,
+            &challenge_data,
+            ephemeral_pubkey,
+            &local_id,
+        )?;
+
+        let ephemeral_pubkey = VerifyingKey::from_sec1_bytes(ephemeral_pubkey)
+            .map_err(|_| Discv5Error::InvalidPublicKey)?;
+        let keys = derive_session_keys(
+            static_key,
+            &ephemeral_pubkey,
+            src_id,
+            &local_id,
+            &challenge_data,
+        );
+
+        let session = Self {
+            local_id,
+            remote_id: *src_id,
+            write_key: keys.recipient_key,
+            read_key: keys.initiator_key,
+        };
+        let message = session.decrypt(handshake)?;
+        Ok((session, message))
+    }
+
+    /// Returns the node id of the remote node.
+    pub const fn remote_id(&self) -> &NodeId {
+        &self.remote_id
+    }
+
+    /// Encrypts `message` into an ordinary message packet.
+    pub fn encrypt(
+        &self,
+        masking_iv: [u8; 16],
+        nonce: [u8; 12],
+        message: &Discv5Message,
+    ) -> Packet {
+        let header = PacketHeader {
+            nonce,
+            kind: PacketKind::Message {
+                src_id: self.local_id,
+            },
+        };
+        self.seal(masking_iv, header, message)
+    }
+
+    /// Decrypts the message of a message or handshake packet received from the remote node.
+    pub fn decrypt(&self, packet: &Packet) -> Result<Discv5Message, Discv5Error> {
+        match &packet.header.kind {
+            PacketKind::Message { src_id } | PacketKind::Handshake { src_id, .. }
+                if *src_id == self.remote_id => {}
+            _ => return Err(Discv5Error::UnexpectedPacket),
+        }
+
+        let plaintext = decrypt_message(
+            &self.read_key,
+            &packet.header.nonce,
+            &packet.message,
+            &packet.authenticated_data(),
+        )?;
+        Ok(Discv5Message::decode(&mut &plaintext[..])?)
+    }
+
+    fn seal(&self, masking_iv: [u8; 16], header: PacketHeader, message: &Discv5Message) -> Packet {
+        let mut packet = Packet {
+            masking_iv,
+            header,
+            message: Bytes::new(),
+        };
+
+        let mut plaintext = Vec::with_capacity(message.length());
+        message.encode(&mut plaintext);
+        let ciphertext = encrypt_message(
+            &self.write_key,
+            &packet.header.nonce,
+            &plaintext,
+            &packet.authenticated_data(),
+        );
+        packet.message = ciphertext.into();
+        packet
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        decrypt_message, derive_session_keys, encrypt_message, id_sign, id_verify, Session,
+    };
+    use crate::{
+        discv5::{Discv5Message, Packet, Ping},
+        enr::EnrBuilder,
+    };
+    use hex_literal::hex;
+    use k256::ecdsa::{SigningKey, VerifyingKey};
+    use std::net::Ipv4Addr;
+
+    const NODE_A_KEY: [u8; 32] =
+        hex!("eef77acb6c6a6eebc5b363a475ac583ec7eccdb42b6481424c60f59aa326547f");
+    const NODE_B_KEY: [u8; 32] =
+        hex!("66fb62bfbd66b9177a138c1e5cddbe4f7c30c343e94e68df8769459cb1cde628");
+    const NODE_A_ID: [u8; 32] =
+        hex!("aaaa8419e9f49d0083561b48287df592939a8d19947d8c0ef88f2a4856a69fbb");
+    const NODE_B_ID: [u8; 32] =
+        hex!("bbbb9d047f0488c0b5a93c1c3f2d8bafc7c8ff337024a55434a0d0555de64db9");
+    const CHALLENGE_DATA: [u8; 63] = hex!("000000000000000000000000000000006469736376350001010102030405060708090a0b0c00180102030405060708090a0b0c0d0e0f100000000000000000");
+
+    #[test]
+    fn node_ids() {
+        let a = SigningKey::from_slice(&NODE_A_KEY).unwrap();
+        let b = SigningKey::from_slice(&NODE_B_KEY).unwrap();
+        assert_eq!(crate::enr::node_id(a.verifying_key()), NODE_A_ID);
+        assert_eq!(crate::enr::node_id(b.verifying_key()), NODE_B_ID);
+    }
+
+    #[test]
+    // Test vector from the discv5 wire test vectors
+    fn ecdh_key_derivation() {
+        let ephemeral_key = SigningKey::from_slice(&hex!(
+            "fb757dc581730490a1d7a00deea65e9b1936924caaea8f44d476014856b68736"
+        ))
+        .unwrap();
+        let dest_pubkey = VerifyingKey::from_sec1_bytes(&hex!(
+            "0317931e6e0840220642f230037d285d122bc59063221ef3226b1f403ddc69ca91"
+        ))
+        .unwrap();
+
+        let keys = derive_session_keys(
+            &ephemeral_key,
+            &dest_pubkey,
+            &NODE_A_ID,
+            &NODE_B_ID,
+            &CHALLENGE_DATA,
+        );
+        assert_eq!(keys.initiator_key, hex!("dccc82d81bd610f4f76d3ebe97a40571"));
+        assert_eq!(keys.recipient_key, hex!("ac74bb8773749920b0d3a8881c173ec5"));
+    }
+
+    #[test]
+    // Test vector from the discv5 wire test vectors
+    fn id_nonce_signing() {
+        let static_key = SigningKey::from_slice(&hex!(
+            "fb757dc581730490a1d7a00deea65e9b1936924caaea8f44d476014856b68736"
+        ))
+        .unwrap();
+        let ephemeral_pubkey =
+            hex!("039961e4c2356d61bedb83052c115d311acb3a96f5777296dcf297351130266231");
+
+        let signature =
+            id_sign(&static_key, &CHALLENGE_DATA, &ephemeral_pubkey, &NODE_B_ID).unwrap();
+        assert_eq!(signature, hex!("94852a1e2318c4e5e9d422c98eaf19d1d90d876b29cd06ca7cb7546d0fff7b484fe86c09a064fe72bdbef73ba8e9c34df0cd2b53e9d65528c2c7f336d5dfc6e6"));
+        assert!(id_verify(
+            static_key.verifying_key(),
+            &signature,
+            &CHALLENGE_DATA,
+            &ephemeral_pubkey,
+            &NODE_B_ID
+        )
+        .is_ok());
+    }
+
+    #[test]
+    // Test vector from the discv5 wire test vectors
+    fn encryption() {
+        let key = hex!("9f2d77db7004bf8a1a85107ac686990b");
+        let nonce = hex!("27b5af763c446acd2749fe8e");
+        let plaintext = hex!("01c20101");
+        let authenticated_data =
+            hex!("93a7400fa0d6a694ebc24d5cf570f65d04215b6ac00757875e3f3a5f42107903");
+
+        let ciphertext = encrypt_message(&key, &nonce, &plaintext, &authenticated_data);
+        assert_eq!(ciphertext, hex!("a5d12a2d94b8ccb3ba55558229867dc13bfa3648"));
+        assert_eq!(
+            decrypt_message(&key, &nonce, &ciphertext, &authenticated_data).unwrap(),
+            plaintext
+        );
+    }
+
+    #[test]
+    fn handshake() {
+        let a_key = SigningKey::from_slice(&NODE_A_KEY).unwrap();
+        let b_key = SigningKey::from_slice(&NODE_B_KEY).unwrap();
+        let ephemeral_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let a_record = EnrBuilder::new(1)
+            .ip4(Ipv4Addr::LOCALHOST)
+            .udp4(30303)
+            .build(&a_key)
+            .unwrap();
+        let b_record = EnrBuilder::new(1)
+            .ip4(Ipv4Addr::LOCALHOST)
+            .udp4(30304)
+            .build(&b_key)
+            .unwrap();
+
+        // B does not know A, and challenges the first packet A sends it
+        let whoareyou = Packet::new_whoareyou([1; 16], [2; 12], [3; 16], 0);
+        let whoareyou = Packet::decode(&NODE_A_ID, &whoareyou.encode(&NODE_A_ID)).unwrap();
+
+        let ping = Discv5Message::Ping(Ping {
+            request_id: 1u64.into(),
+            enr_seq: 1,
+        });
+        let (a_session, handshake) = Session::initiate(
+            &a_key,
+            &ephemeral_key,
+            &a_record,
+            &b_record,
+            &whoareyou,
+            [4; 16],
+            [5; 12],
+            &ping,
+        )
+        .unwrap();
+        let handshake = Packet::decode(&NODE_B_ID, &handshake.encode(&NODE_B_ID)).unwrap();
+
+        let (b_session, message) = Session::accept(&b_key, &whoareyou, &handshake, None).unwrap();
+        assert_eq!(message, ping);
+        assert_eq!(b_session.remote_id(), &NODE_A_ID);
+
+        // both sides can now exchange ordinary messages
+        let packet = b_session.encrypt([6; 16], [7; 12], &ping);
+        let packet = Packet::decode(&NODE_A_ID, &packet.encode(&NODE_A_ID)).unwrap();
+        assert_eq!(a_session.decrypt(&packet).unwrap(), ping);
+    }
+}
@@ -0,0 +1,481 @@
+use crate::forkid::ForkId;
+use bytes::{BufMut, Bytes};
+use ethers_core::utils::keccak256;
+use k256::ecdsa::{
+    signature::hazmat::{PrehashSigner, PrehashVerifier},
+    Signature, SigningKey, VerifyingKey,
+};
+use open_fastrlp::{Decodable, DecodeError, Encodable, Header};
+use std::{
+    collections::BTreeMap,
+    net::{Ipv4Addr, Ipv6Addr},
+};
+use thiserror::Error;
+
+/// The maximum size of an RLP encoded node record, in bytes.
+pub const MAX_ENR_SIZE: usize = 300;
+
+/// The only identity scheme currently defined, see
+/// [EIP-778](https://eips.ethereum.org/EIPS/eip-778#v4-identity-scheme).
+const ID_SCHEME_V4: &[u8] = b"v4";
+
+/// A node identifier, the keccak256 hash of the node's uncompressed secp256k1 public key.
+pub type NodeId = [u8; 32];
+
+/// Returns the [`NodeId`] for the given public key.
+pub fn node_id(public_key: &VerifyingKey) -> NodeId {
+    let uncompressed = public_key.to_encoded_point(false);
+    keccak256(&uncompressed.as_bytes()[1..])
+}
+
+/// Reasons for rejecting a node record.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum EnrError {
+    /// The encoded record is larger than [`MAX_ENR_SIZE`].
+    #[error("node record exceeds {MAX_ENR_SIZE} bytes")]
+    TooLarge,
+    /// The record does not use the `v4` identity scheme.
+    #[error("unsupported node record identity scheme")]
+    UnsupportedScheme,
+    /// The `secp256k1` entry is missing or is not a valid compressed public key.
+    #[error("missing or invalid secp256k1 public key")]
+    InvalidPublicKey,
+    /// The record signature does not match its content.
+    #[error("invalid node record signature")]
+    InvalidSignature,
+}
+
+/// The `eth` entry of a node record, advertising the [`ForkId`] of the node's chain.
+///
+/// This is encoded as `[[fork_hash, fork_next], ...]`, where any trailing fields are ignored for
+/// forward compatibility.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnrForkIdEntry {
+    /// The fork identifier of the node's current chain.
+    pub fork_id: ForkId,
+}
+
+impl From<ForkId> for EnrForkIdEntry {
+    fn from(fork_id: ForkId) -> Self {
+        Self { fork_id }
+    }
+}
+
+impl Encodable for EnrForkIdEntry {
+    fn length(&self) -> usize {
+        let payload_length = self.fork_id.length();
+        payload_length + open_fastrlp::length_of_length(payload_length)
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header {
+            list: true,
+            payload_length: self.fork_id.length(),
+        }
+        .encode(out);
+        self.fork_id.encode(out);
+    }
+}
+
+impl Decodable for EnrForkIdEntry {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(DecodeError::UnexpectedString);
+        }
+        let payload = &mut &buf[..header.payload_length];
+        let fork_id = ForkId::decode(payload)?;
+        bytes::Buf::advance(buf, header.payload_length);
+        Ok(Self { fork_id })
+    }
+}
+
+/// An Ethereum Node Record, as defined by [EIP-778](https://eips.ethereum.org/EIPS/eip-778).
+///
+/// A record is a signed set of key/value pairs, sorted by key. Values are kept in their RLP
+/// encoded form, and can be read with [`Enr::get`] or the typed accessors for well-known keys.
+///
+/// Records can only be created with an [`EnrBuilder`], and decoding a record verifies its
+/// signature, so every `Enr` is correctly signed by the key in its `secp256k1` entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Enr {
+    seq: u64,
+    pairs: BTreeMap<Bytes, Bytes>,
+    signature: [u8; 64],
+}
+
+impl Enr {
+    /// Returns the sequence number of the record.
+    pub const fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Returns the record signature.
+    pub const fn signature(&self) -> &[u8; 64] {
+        &self.signature
+    }
+
+    /// Returns the RLP encoded value stored under `key`, if any.
+    pub fn get_raw(&self, key: &[u8]) -> Option<&[u8]> {
+        self.pairs.get(key).map(|value| &value[..])
+    }
+
+    /// Decodes the value stored under `key`, if any.
+    pub fn get<T: Decodable>(&self, key: &[u8]) -> Option<Result<T, DecodeError>> {
+        self.get_raw(key).map(|mut value| T::decode(&mut value))
+    }
+
+    /// Returns the public key of the node that signed the record.
+    pub fn public_key(&self) -> VerifyingKey {
+        // the key is checked when the record is built or decoded
+        let key = self
+            .get::<Bytes>(b"secp256k1")
+            .and_then(Result::ok)
+            .expect("records always contain a valid secp256k1 key; qed");
+        VerifyingKey::from_sec1_bytes(&key)
+            .expect("records always contain a valid secp256k1 key; qed")
+    }
+
+    /// Returns the [`NodeId`] of the node that signed the record.
+    pub fn node_id(&self) -> NodeId {
+        node_id(&self.public_key())
+    }
+
+    /// Returns the IPv4 address of the node, if any.
+    pub fn ip4(&self) -> Option<Ipv4Addr> {
+        self.get::<[u8; 4]>(b"ip")?.ok().map(Ipv4Addr::from)
+    }
+
+    /// Returns the IPv6 address of the node, if any.
+    pub fn ip6(&self) -> Option<Ipv6Addr> {
+        self.get::<[u8; 16]>(b"ip6")?.ok().map(Ipv6Addr::from)
+    }
+
+    /// Returns the IPv4 TCP port of the node, if any.
+    pub fn tcp4(&self) -> Option<u16> {
+        self.get(b"tcp")?.ok()
+    }
+
+    /// Returns the IPv4 UDP port of the node, if any.
+    pub fn udp4(&self) -> Option<u16> {
+        self.get(b"udp")?.ok()
+    }
+
+    /// Returns the IPv6 TCP port of the node, if any.
+    pub fn tcp6(&self) -> Option<u16> {
+        self.get(b"tcp6")?.ok()
+    }
+
+    /// Returns the IPv6 UDP port of the node, if any.
+    pub fn udp6(&self) -> Option<u16> {
+        self.get(b"udp6")?.ok()
+    }
+
+    /// Returns the [`ForkId`] advertised in the `eth` entry, if any.
+    pub fn eth(&self) -> Option<ForkId> {
+        self.get::<EnrForkIdEntry>(b"eth")?
+            .ok()
+            .map(|entry| entry.fork_id)
+    }
+
+    /// The payload length of the signed content, `[seq, k, v, ...]`.
+    fn content_payload_length(&self) -> usize {
+        self.seq.length() + pairs_length(&self.pairs)
+    }
+
+    /// The keccak256 hash of the signed content, `rlp([seq, k, v, ...])`.
+    fn signing_hash(&self) -> [u8; 32] {
+        let payload_length = self.content_payload_length();
+        let mut content = Vec::with_capacity(payload_length + 9);
+        Header {
+            list: true,
+            payload_length,
+        }
+        .encode(&mut content);
+        self.seq.encode(&mut content);
+        encode_pairs(&self.pairs, &mut content);
+        keccak256(content)
+    }
+
+    /// Checks the identity scheme, public key and signature of the record.
+    fn verify(&self) -> Result<(), EnrError> {
+        match self.get::<Bytes>(b"id") {
+            Some(Ok(id)) if id[..] == *ID_SCHEME_V4 => {}
+            _ => return Err(EnrError::UnsupportedScheme),
+        }
+
+        let key = match self.get::<Bytes>(b"secp256k1") {
+            Some(Ok(key)) if key.len() == 33 => key,
+            _ => return Err(EnrError::InvalidPublicKey),
+        };
+        let key = VerifyingKey::from_sec1_bytes(&key).map_err(|_| EnrError::InvalidPublicKey)?;
+        let signature =
+            Signature::from_slice(&self.signature).map_err(|_| EnrError::InvalidSignature)?;
+        key.verify_prehash(&self.signing_hash(), &signature)
+            .map_err(|_| EnrError::InvalidSignature)
+    }
+}
+
+fn pairs_length(pairs: &BTreeMap<Bytes, Bytes>) -> usize {
+    pairs
+        .iter()
+        .map(|(key, value)| key.length() + value.len())
+        .sum()
+}
+
+fn encode_pairs(pairs: &BTreeMap<Bytes, Bytes>, out: &mut dyn BufMut) {
+    for (key, value) in pairs {
+        key.encode(out);
+        out.put_slice(value);
+    }
+}
+
+/// Encodes a node record as `[signature, seq, k, v, ...]`.
+impl Encodable for Enr {
+    fn length(&self) -> usize {
+        let payload_length = self.signature.length() + self.content_payload_length();
+        payload_length + open_fastrlp::length_of_length(payload_length)
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header {
+            list: true,
+            payload_length: self.signature.length() + self.content_payload_length(),
+        }
+        .encode(out);
+        self.signature.encode(out);
+        self.seq.encode(out);
+        encode_pairs(&self.pairs, out);
+    }
+}
+
+/// Decodes a node record, rejecting records that are too large, have unsorted keys, or are not
+/// correctly signed.
+impl Decodable for Enr {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let original = *buf;
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(DecodeError::UnexpectedString);
+        }
+        if original.len() - buf.len() + header.payload_length > MAX_ENR_SIZE {
+            return Err(DecodeError::Custom("node record exceeds 300 bytes"));
+        }
+
+        let payload = &mut &buf[..header.payload_length];
+        let signature = <[u8; 64]>::decode(payload)?;
+        let seq = u64::decode(payload)?;
+
+        let mut pairs = BTreeMap::new();
+        let mut last_key: Option<Bytes> = None;
+        while !payload.is_empty() {
+            let key = Bytes::decode(payload)?;
+            if last_key.as_ref().is_some_and(|last| *last >= key) {
+                return Err(DecodeError::Custom(
+                    "node record keys are not sorted and unique",
+                ));
+            }
+
+            // keep the value in its encoded form
+            let value_start = *payload;
+            let value_header = Header::decode(payload)?;
+            bytes::Buf::advance(payload, value_header.payload_length);
+            let value_length = value_start.len() - payload.len();
+
+            last_key = Some(key.clone());
+            pairs.insert(key, Bytes::copy_from_slice(&value_start[..value_length]));
+        }
+        bytes::Buf::advance(buf, header.payload_length);
+
+        let enr = Self {
+            seq,
+            pairs,
+            signature,
+        };
+        enr.verify().map_err(|err| match err {
+            EnrError::UnsupportedScheme => {
+                DecodeError::Custom("unsupported node record identity scheme")
+            }
+            EnrError::InvalidPublicKey => DecodeError::Custom("invalid node record public key"),
+            _ => DecodeError::Custom("invalid node record signature"),
+        })?;
+        Ok(enr)
+    }
+}
+
+/// Builds and signs an [`Enr`].
+///
+/// # Example
+/// ```
+/// use ethp2p::EnrBuilder;
+/// use k256::ecdsa::SigningKey;
+/// use std::net::Ipv4Addr;
+///
+/// let key = SigningKey::from_slice(&[0x01; 32]).unwrap();
+/// let enr = EnrBuilder::new(1)
+///     .ip4(Ipv4Addr::LOCALHOST)
+///     .udp4(30303)
+///     .build(&key)
+///     .unwrap();
+/// assert_eq!(enr.udp4(), Some(30303));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnrBuilder {
+    seq: u64,
+    pairs: BTreeMap<Bytes, Bytes>,
+}
+
+impl EnrBuilder {
+    /// Creates a builder for a record with the given sequence number.
+    pub fn new(seq: u64) -> Self {
+        Self {
+            seq,
+            pairs: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the value stored under `key`, replacing any previous value.
+    pub fn add_value<T: Encodable>(mut self, key: &[u8], value: &T) -> Self {
+        let mut encoded = Vec::with_capacity(value.length());
+        value.encode(&mut encoded);
+        self.pairs
+            .insert(Bytes::copy_from_slice(key), Bytes::from(encoded));
+        self
+    }
+
+    /// Sets the IPv4 address.
+    pub fn ip4(self, ip: Ipv4Addr) -> Self {
+        self.add_value(b"ip", &ip.octets())
+    }
+
+    /// Sets the IPv6 address.
+    pub fn ip6(self, ip: Ipv6Addr) -> Self {
+        self.add_value(b"ip6", &ip.octets())
+    }
+
+    /// Sets the IPv4 TCP port.
+    pub fn tcp4(self, port: u16) -> Self {
+        self.add_value(b"tcp", &port)
+    }
+
+    /// Sets the IPv4 UDP port.
+    pub fn udp4(self, port: u16) -> Self {
+        self.add_value(b"udp", &port)
+    }
+
+    /// Sets the IPv6 TCP port.
+    pub fn tcp6(self, port: u16) -> Self {
+        self.add_value(b"tcp6", &port)
+    }
+
+    /// Sets the IPv6 UDP port.
+    pub fn udp6(self, port: u16) -> Self {
+        self.add_value(b"udp6", &port)
+    }
+
+    /// Sets the `eth` entry to the given [`ForkId`].
+    pub fn eth(self, fork_id: ForkId) -> Self {
+        self.add_value(b"eth", &EnrForkIdEntry::from(fork_id))
+    }
+
+    /// Signs the record with the `v4` identity scheme.
+    ///
+    /// # Errors
+    /// Returns [`EnrError::TooLarge`] if the signed record exceeds [`MAX_ENR_SIZE`].
+    pub fn build(self, key: &SigningKey) -> Result<Enr, EnrError> {
+        let public_key = key.verifying_key().to_encoded_point(true);
+        let builder = self
+            .add_value(b"id", &ID_SCHEME_V4)
+            .add_value(b"secp256k1", &public_key.as_bytes());
+
+        let mut enr = Enr {
+            seq: builder.seq,
+            pairs: builder.pairs,
+            signature: [0; 64],
+        };
+        let signature: Signature = key
+            .sign_prehash(&enr.signing_hash())
+            .map_err(|_| EnrError::InvalidSignature)?;
+        enr.signature.copy_from_slice(&signature.to_bytes());
+
+        if enr.length() > MAX_ENR_SIZE {
+            return Err(EnrError::TooLarge);
+        }
+        Ok(enr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Enr, EnrBuilder, EnrForkIdEntry};
+    use crate::forkid::{ForkHash, ForkId};
+    use hex_literal::hex;
+    use k256::ecdsa::SigningKey;
+    use open_fastrlp::{Decodable, Encodable};
+    use std::net::Ipv4Addr;
+
+    // Example record from EIP-778.
+    const EXAMPLE_ENR: [u8; 134] = hex!("f884b8407098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c01826964827634826970847f00000189736563703235366b31a103ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd31388375647082765f");
+
+    #[test]
+    fn decode_eip778_example() {
+        let enr = Enr::decode(&mut &EXAMPLE_ENR[..]).unwrap();
+        assert_eq!(enr.seq(), 1);
+        assert_eq!(enr.ip4(), Some(Ipv4Addr::new(127, 0, 0, 1)));
+        assert_eq!(enr.udp4(), Some(30303));
+        assert_eq!(enr.tcp4(), None);
+        assert_eq!(
+            enr.node_id(),
+            hex!("a448f24c6d18e575453db13171562b71999873db5b286df957af199ec94617f7")
+        );
+
+        let mut encoded = vec![];
+        enr.encode(&mut encoded);
+        assert_eq!(encoded, EXAMPLE_ENR);
+    }
+
+    #[test]
+    fn build_eip778_example() {
+        let key = SigningKey::from_slice(&hex!(
+            "b71c71a67e1177ad4e901695e1b4b9ee17ae16c6668d313eac2f96dbcda3f291"
+        ))
+        .unwrap();
+        let enr = EnrBuilder::new(1)
+            .ip4(Ipv4Addr::new(127, 0, 0, 1))
+            .udp4(30303)
+            .build(&key)
+            .unwrap();
+
+        let mut encoded = vec![];
+        enr.encode(&mut encoded);
+        assert_eq!(encoded, EXAMPLE_ENR);
+    }
+
+    #[test]
+    fn reject_modified_record() {
+        let mut data = EXAMPLE_ENR;
+        // change the udp port without re-signing the record
+        data[133] = 0x60;
+        assert!(Enr::decode(&mut &data[..]).is_err());
+    }
+
+    #[test]
+    fn eth_entry_roundtrip() {
+        let key = SigningKey::from_slice(&[0x01; 32]).unwrap();
+        let fork_id = ForkId {
+            hash: ForkHash(hex!("dce96c2d")),
+            next: 0,
+        };
+        let enr = EnrBuilder::new(7).eth(fork_id).build(&key).unwrap();
+
+        let mut encoded = vec![];
+        enr.encode(&mut encoded);
+        let decoded = Enr::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(decoded, enr);
+        assert_eq!(decoded.eth(), Some(fork_id));
+
+        let mut entry = vec![];
+        EnrForkIdEntry::from(fork_id).encode(&mut entry);
+        assert_eq!(entry, hex!("c7c684dce96c2d80"));
+    }
+}
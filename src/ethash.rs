@@ -0,0 +1,508 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use ethers_core::{
+    types::{H256, U256},
+    utils::keccak256,
+};
+use open_fastrlp::Encodable;
+use sha3::{Digest, Keccak512};
+use thiserror::Error;
+
+use crate::{primitives::encode_list_payload, BlockHeaders, Header, NewBlock};
+
+/// The number of blocks per ethash epoch, after which the cache and dataset change.
+pub const ETHASH_EPOCH_LENGTH: u64 = 30_000;
+
+/// The number of blocks per epoch once ECIP-1099 is active on Ethereum Classic.
+pub const ECIP1099_EPOCH_LENGTH: u64 = 60_000;
+
+/// The block at which ECIP-1099 activated on Ethereum Classic.
+pub const ETC_ECIP1099_BLOCK: u64 = 11_700_000;
+
+/// The highest epoch for which headers are verified, which bounds the size of the caches a peer
+/// can make us generate. Block numbers up to about 61 million are below it.
+pub const MAX_ETHASH_EPOCH: u64 = 2048;
+
+const WORD_BYTES: usize = 4;
+const DATASET_BYTES_INIT: u64 = 1 << 30;
+const DATASET_BYTES_GROWTH: u64 = 1 << 23;
+const CACHE_BYTES_INIT: u64 = 1 << 24;
+const CACHE_BYTES_GROWTH: u64 = 1 << 17;
+const MIX_BYTES: usize = 128;
+const HASH_BYTES: usize = 64;
+const HASH_WORDS: usize = HASH_BYTES / WORD_BYTES;
+const MIX_WORDS: usize = MIX_BYTES / WORD_BYTES;
+const DATASET_PARENTS: u32 = 256;
+const CACHE_ROUNDS: usize = 3;
+const ACCESSES: u32 = 64;
+
+/// Ways in which the proof of work of a header can be invalid.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum EthashError {
+    /// The block is in an epoch above [`MAX_ETHASH_EPOCH`].
+    #[error("block {0} is beyond the highest verified epoch")]
+    EpochTooHigh(U256),
+    /// The block has no difficulty.
+    #[error("zero difficulty")]
+    ZeroDifficulty,
+    /// The mix hash of the header is not the one computed from its nonce.
+    #[error("invalid mix hash: expected {expected:?}, got {got:?}")]
+    InvalidMixHash {
+        /// The mix hash computed from the header and nonce.
+        expected: H256,
+        /// The mix hash of the header.
+        got: H256,
+    },
+    /// The result of the proof of work does not meet the difficulty.
+    #[error("insufficient proof of work")]
+    InsufficientWork,
+}
+
+/// A header which was rejected by an [`Ethash`] verifier.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("invalid proof of work for header {index}: {error}")]
+pub struct InvalidEthashHeader {
+    /// The index of the header in the response.
+    pub index: usize,
+    /// Why the header was rejected.
+    #[source]
+    pub error: EthashError,
+}
+
+/// The cache of an ethash epoch, from which the items of the dataset are computed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EthashCache {
+    epoch: u64,
+    seed_epoch: u64,
+    full_size: u64,
+    items: Vec<[u32; HASH_WORDS]>,
+}
+
+impl EthashCache {
+    /// Generates the cache of an epoch, which takes a while: the cache is 16 MiB in the first
+    /// epoch, and grows by 128 KiB each epoch.
+    pub fn new(epoch: u64) -> Self {
+        Self::generate(epoch, epoch)
+    }
+
+    /// Generates the cache of an epoch with the seed of another, as the epochs of ECIP-1099 are
+    /// seeded by their first block as if epochs were still [`ETHASH_EPOCH_LENGTH`] long.
+    fn generate(epoch: u64, seed_epoch: u64) -> Self {
+        let mut seed = [0u8; 32];
+        for _ in 0..seed_epoch {
+            seed = keccak256(seed);
+        }
+
+        let n = (cache_size(epoch) / HASH_BYTES as u64) as usize;
+        let mut items = Vec::with_capacity(n);
+        items.push(keccak512(&seed));
+        for i in 1..n {
+            items.push(keccak512(&words_to_bytes(&items[i - 1])));
+        }
+
+        for _ in 0..CACHE_ROUNDS {
+            for i in 0..n {
+                let v = items[i][0] as usize % n;
+                let mut mixed = items[(i + n - 1) % n];
+                for (word, other) in mixed.iter_mut().zip(&items[v]) {
+                    *word ^= other;
+                }
+                items[i] = keccak512(&words_to_bytes(&mixed));
+            }
+        }
+
+        Self {
+            epoch,
+            seed_epoch,
+            full_size: full_size(epoch),
+            items,
+        }
+    }
+
+    /// Returns the epoch of the cache.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Computes an item of the dataset.
+    fn dataset_item(&self, index: u32) -> [u32; HASH_WORDS] {
+        let n = self.items.len();
+        let mut mix = self.items[index as usize % n];
+        mix[0] ^= index;
+        mix = keccak512(&words_to_bytes(&mix));
+        for j in 0..DATASET_PARENTS {
+            let parent = fnv(index ^ j, mix[j as usize % HASH_WORDS]) as usize % n;
+            for (word, other) in mix.iter_mut().zip(&self.items[parent]) {
+                *word = fnv(*word, *other);
+            }
+        }
+        keccak512(&words_to_bytes(&mix))
+    }
+
+    /// Computes the mix hash and the result of the proof of work for a header hash and nonce,
+    /// using the cache instead of the full dataset.
+    pub fn hashimoto_light(&self, header_hash: H256, nonce: u64) -> (H256, H256) {
+        let mut seed = [0u8; 40];
+        seed[..32].copy_from_slice(header_hash.as_bytes());
+        seed[32..].copy_from_slice(&nonce.to_le_bytes());
+        let s = keccak512(&seed);
+
+        let mut mix = [0u32; MIX_WORDS];
+        for (i, word) in mix.iter_mut().enumerate() {
+            *word = s[i % HASH_WORDS];
+        }
+
+        let rows = (self.full_size / MIX_BYTES as u64) as u32;
+        let mix_hashes = (MIX_BYTES / HASH_BYTES) as u32;
+        for i in 0..ACCESSES {
+            let p = fnv(i ^ s[0], mix[i as usize % MIX_WORDS]) % rows * mix_hashes;
+            for j in 0..mix_hashes {
+                let item = self.dataset_item(p + j);
+                let offset = j as usize * HASH_WORDS;
+                for (word, other) in mix[offset..offset + HASH_WORDS].iter_mut().zip(&item) {
+                    *word = fnv(*word, *other);
+                }
+            }
+        }
+
+        // compress the mix to 8 words
+        let mut mix_hash = [0u8; 32];
+        for (i, words) in mix.chunks_exact(4).enumerate() {
+            let word = fnv(fnv(fnv(words[0], words[1]), words[2]), words[3]);
+            mix_hash[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+        }
+
+        let mut result = words_to_bytes(&s).to_vec();
+        result.extend_from_slice(&mix_hash);
+        (H256(mix_hash), H256(keccak256(result)))
+    }
+}
+
+/// An ethash light verifier, which checks the proof of work of pre-merge headers using the cache
+/// of their epoch.
+///
+/// The most recently used caches are kept, so that verifying headers of the same few epochs does
+/// not regenerate them.
+#[derive(Clone, Debug)]
+pub struct Ethash {
+    capacity: usize,
+    /// The block from which epochs are [`ECIP1099_EPOCH_LENGTH`] long, if any.
+    ecip1099_block: Option<u64>,
+    /// The caches, most recently used first.
+    caches: VecDeque<Arc<EthashCache>>,
+}
+
+impl Default for Ethash {
+    /// A verifier keeping the caches of three epochs.
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl Ethash {
+    /// Creates a verifier which keeps the caches of up to `capacity` epochs.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ecip1099_block: None,
+            caches: VecDeque::new(),
+        }
+    }
+
+    /// Creates a verifier for Ethereum Classic, whose epochs are twice as long from
+    /// [`ETC_ECIP1099_BLOCK`].
+    pub fn classic(capacity: usize) -> Self {
+        Self {
+            ecip1099_block: Some(ETC_ECIP1099_BLOCK),
+            ..Self::new(capacity)
+        }
+    }
+
+    /// Returns the epoch of a block and the epoch whose seed its cache uses.
+    fn epochs(&self, number: u64) -> (u64, u64) {
+        match self.ecip1099_block {
+            Some(block) if number >= block => {
+                let epoch = number / ECIP1099_EPOCH_LENGTH;
+                (epoch, epoch * ECIP1099_EPOCH_LENGTH / ETHASH_EPOCH_LENGTH)
+            }
+            _ => (number / ETHASH_EPOCH_LENGTH, number / ETHASH_EPOCH_LENGTH),
+        }
+    }
+
+    /// Returns the cache of the block with the given number, generating it if it is not kept.
+    pub fn cache(&mut self, number: u64) -> Arc<EthashCache> {
+        let (epoch, seed_epoch) = self.epochs(number);
+        let position = self
+            .caches
+            .iter()
+            .position(|cache| cache.epoch == epoch && cache.seed_epoch == seed_epoch);
+        let cache = match position {
+            Some(index) => self.caches.remove(index).unwrap(),
+            None => Arc::new(EthashCache::generate(epoch, seed_epoch)),
+        };
+        self.caches.push_front(cache.clone());
+        self.caches.truncate(self.capacity);
+        cache
+    }
+
+    /// Returns the hash of the header without the mix hash and nonce, which is the input of the
+    /// proof of work.
+    pub fn seal_hash(header: &Header) -> H256 {
+        let mut payload = vec![];
+        header.parent_hash.encode(&mut payload);
+        header.ommers_hash.encode(&mut payload);
+        header.beneficiary.encode(&mut payload);
+        header.state_root.encode(&mut payload);
+        header.transactions_root.encode(&mut payload);
+        header.receipts_root.encode(&mut payload);
+        header.logs_bloom.encode(&mut payload);
+        header.difficulty.encode(&mut payload);
+        header.number.encode(&mut payload);
+        header.gas_limit.encode(&mut payload);
+        header.gas_used.encode(&mut payload);
+        header.timestamp.encode(&mut payload);
+        header.extra_data.encode(&mut payload);
+        if let Some(base_fee) = header.base_fee_per_gas {
+            base_fee.encode(&mut payload);
+        }
+
+        let mut encoded = vec![];
+        encode_list_payload(&payload, &mut encoded);
+        H256(keccak256(encoded))
+    }
+
+    /// Checks the mix hash and nonce of a header against its difficulty.
+    pub fn verify(&mut self, header: &Header) -> Result<(), EthashError> {
+        if header.number > U256::from(u64::MAX)
+            || self.epochs(header.number.as_u64()).0 > MAX_ETHASH_EPOCH
+        {
+            return Err(EthashError::EpochTooHigh(header.number));
+        }
+        if header.difficulty.is_zero() {
+            return Err(EthashError::ZeroDifficulty);
+        }
+
+        let cache = self.cache(header.number.as_u64());
+        let nonce = u64::from_be_bytes(header.nonce.0);
+        let (mix_hash, result) = cache.hashimoto_light(Self::seal_hash(header), nonce);
+        if mix_hash != header.mix_hash {
+            return Err(EthashError::InvalidMixHash {
+                expected: mix_hash,
+                got: header.mix_hash,
+            });
+        }
+        if U256::from_big_endian(result.as_bytes()) > target(header.difficulty) {
+            return Err(EthashError::InsufficientWork);
+        }
+        Ok(())
+    }
+
+    /// Checks the proof of work of each header of a [`BlockHeaders`] response.
+    ///
+    /// # Errors
+    /// Returns an [`InvalidEthashHeader`] with the index of the first invalid header.
+    pub fn verify_headers(&mut self, headers: &BlockHeaders) -> Result<(), InvalidEthashHeader> {
+        for (index, header) in headers.0.iter().enumerate() {
+            self.verify(header)
+                .map_err(|error| InvalidEthashHeader { index, error })?;
+        }
+        Ok(())
+    }
+
+    /// Checks the proof of work of the block of a [`NewBlock`] and of its ommers.
+    pub fn verify_new_block(&mut self, new_block: &NewBlock) -> Result<(), EthashError> {
+        self.verify(&new_block.block.header)?;
+        for ommer in &new_block.block.ommers {
+            self.verify(ommer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the largest result which meets the difficulty, `2^256 / difficulty`.
+fn target(difficulty: U256) -> U256 {
+    if difficulty == U256::one() {
+        return U256::MAX;
+    }
+    // 2^256 / d is (2^256 - 1) / d, plus one if d divides 2^256
+    let target = U256::MAX / difficulty;
+    if U256::MAX % difficulty == difficulty - 1 {
+        target + 1
+    } else {
+        target
+    }
+}
+
+fn cache_size(epoch: u64) -> u64 {
+    let mut size = CACHE_BYTES_INIT + CACHE_BYTES_GROWTH * epoch - HASH_BYTES as u64;
+    while !is_prime(size / HASH_BYTES as u64) {
+        size -= 2 * HASH_BYTES as u64;
+    }
+    size
+}
+
+fn full_size(epoch: u64) -> u64 {
+    let mut size = DATASET_BYTES_INIT + DATASET_BYTES_GROWTH * epoch - MIX_BYTES as u64;
+    while !is_prime(size / MIX_BYTES as u64) {
+        size -= 2 * MIX_BYTES as u64;
+    }
+    size
+}
+
+fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    (2..)
+        .take_while(|d| d * d <= n)
+        .all(|d| !n.is_multiple_of(d))
+}
+
+fn fnv(a: u32, b: u32) -> u32 {
+    a.wrapping_mul(0x0100_0193) ^ b
+}
+
+fn keccak512(data: &[u8]) -> [u32; HASH_WORDS] {
+    let hash = Keccak512::digest(data);
+    let mut words = [0u32; HASH_WORDS];
+    for (word, bytes) in words.iter_mut().zip(hash.chunks_exact(WORD_BYTES)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    words
+}
+
+fn words_to_bytes(words: &[u32; HASH_WORDS]) -> [u8; HASH_BYTES] {
+    let mut bytes = [0u8; HASH_BYTES];
+    for (chunk, word) in bytes.chunks_exact_mut(WORD_BYTES).zip(words) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use ethers_core::types::{Bloom, Bytes, H256, H64, U256};
+    use hex_literal::hex;
+
+    use super::{cache_size, full_size, target, Ethash, EthashError, InvalidEthashHeader};
+    use crate::{BlockHeaders, Header};
+
+    /// Block 1 of mainnet.
+    fn mainnet_block_one() -> Header {
+        Header {
+            parent_hash: H256(hex!(
+                "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+            )),
+            ommers_hash: H256(hex!(
+                "1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"
+            )),
+            beneficiary: hex!("05a56e2d52c817161883f50c441c3228cfe54d9f").into(),
+            state_root: H256(hex!(
+                "d67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3"
+            )),
+            transactions_root: H256(hex!(
+                "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+            )),
+            receipts_root: H256(hex!(
+                "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+            )),
+            logs_bloom: Bloom::zero(),
+            difficulty: 17_171_480_576u64.into(),
+            number: 1.into(),
+            gas_limit: 5000.into(),
+            gas_used: 0.into(),
+            timestamp: 1_438_269_988,
+            extra_data: Bytes::from(b"Geth/v1.0.0/linux/go1.4.2".to_vec()),
+            mix_hash: H256(hex!(
+                "969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f59"
+            )),
+            nonce: H64(hex!("539bd4979fef1ec4")),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(cache_size(0), 16_776_896);
+        assert_eq!(full_size(0), 1_073_739_904);
+        assert_eq!(cache_size(1), 16_907_456);
+        assert_eq!(full_size(1), 1_082_130_304);
+    }
+
+    #[test]
+    fn classic_epochs() {
+        let ethash = Ethash::classic(1);
+        assert_eq!(ethash.epochs(11_699_999), (389, 389));
+        // the first epoch of ECIP-1099 starts at block 11_700_000, seeded as the epoch of
+        // block 11_700_001 with the original length
+        assert_eq!(ethash.epochs(11_700_000), (195, 390));
+        assert_eq!(ethash.epochs(11_759_999), (195, 390));
+        assert_eq!(ethash.epochs(11_760_000), (196, 392));
+        assert_eq!(Ethash::default().epochs(11_760_000), (392, 392));
+    }
+
+    #[test]
+    fn targets() {
+        assert_eq!(target(U256::one()), U256::MAX);
+        assert_eq!(target(2.into()), U256::one() << 255);
+        assert_eq!(target(3.into()), U256::MAX / 3);
+    }
+
+    #[test]
+    fn verify_mainnet_block() {
+        let header = mainnet_block_one();
+        assert_eq!(
+            header.hash(),
+            H256(hex!(
+                "88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6"
+            ))
+        );
+
+        let mut ethash = Ethash::new(1);
+        assert_eq!(ethash.verify(&header), Ok(()));
+        let cache = ethash.cache(1);
+        // the cache is kept
+        assert!(Arc::ptr_eq(&cache, &ethash.cache(29_999)));
+
+        // a different nonce gives a different mix hash
+        let mut forged = header.clone();
+        forged.nonce = H64(hex!("539bd4979fef1ec5"));
+        assert!(matches!(
+            ethash.verify(&forged),
+            Err(EthashError::InvalidMixHash { .. })
+        ));
+
+        // as does any change to the header
+        let mut forged = header.clone();
+        forged.timestamp += 1;
+        let (expected, _) = cache.hashimoto_light(Ethash::seal_hash(&forged), 0x539bd4979fef1ec4);
+        assert_eq!(
+            ethash.verify_headers(&BlockHeaders(vec![header.clone(), forged])),
+            Err(InvalidEthashHeader {
+                index: 1,
+                error: EthashError::InvalidMixHash {
+                    expected,
+                    got: header.mix_hash,
+                },
+            })
+        );
+
+        // a number which does not fit in 64 bits is rejected too
+        let mut far = header.clone();
+        far.number = U256::from(u64::MAX) + 1;
+        assert_eq!(
+            ethash.verify(&far),
+            Err(EthashError::EpochTooHigh(far.number))
+        );
+
+        // headers beyond the verified epochs are rejected before generating their cache
+        let mut far = header;
+        far.number = U256::from(u64::MAX);
+        assert_eq!(
+            ethash.verify(&far),
+            Err(EthashError::EpochTooHigh(far.number))
+        );
+    }
+}
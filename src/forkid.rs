@@ -9,12 +9,21 @@ use primitive_types::H256;
 use std::{
     collections::{BTreeMap, BTreeSet},
     ops::{Add, AddAssign},
+    sync::mpsc::{channel, Receiver, Sender},
 };
 use thiserror::Error;
 
 /// Block number.
 pub type BlockNumber = u64;
 
+/// Unix timestamp, used to schedule forks after the merge.
+pub type Timestamp = u64;
+
+/// `next` values at or above this are timestamps rather than block numbers, see
+/// [EIP-6122](https://eips.ethereum.org/EIPS/eip-6122). This is the timestamp of the genesis of
+/// Ethereum mainnet, which geth uses for the same purpose.
+const TIMESTAMP_BEFORE_ETHEREUM_MAINNET: u64 = 1_438_269_973;
+
 /// `CRC32` hash of all previous forks starting from genesis block.
 #[derive(
     Clone,
@@ -35,29 +44,70 @@ impl From<H256> for ForkHash {
     }
 }
 
-impl AddAssign<BlockNumber> for ForkHash {
-    fn add_assign(&mut self, block: BlockNumber) {
-        let blob = block.to_be_bytes();
+impl AddAssign<u64> for ForkHash {
+    fn add_assign(&mut self, fork: u64) {
+        let blob = fork.to_be_bytes();
         self.0 = crc32::update(u32::from_be_bytes(self.0), &crc32::IEEE_TABLE, &blob).to_be_bytes();
     }
 }
 
-impl Add<BlockNumber> for ForkHash {
+impl Add<u64> for ForkHash {
     type Output = Self;
-    fn add(mut self, block: BlockNumber) -> Self {
-        self += block;
+    fn add(mut self, fork: u64) -> Self {
+        self += fork;
         self
     }
 }
 
-/// A fork identifier as defined by EIP-2124.
+/// The activation point of a fork: either a block number, or a timestamp for forks after the
+/// merge.
+///
+/// Block forks are ordered before time forks, which is the order they are added to the
+/// [`ForkHash`] in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ForkFilterKey {
+    /// A fork activated at a block number.
+    Block(BlockNumber),
+    /// A fork activated at a timestamp.
+    Time(Timestamp),
+}
+
+impl ForkFilterKey {
+    /// Whether the fork is active at the given head.
+    const fn is_active(self, head: Head) -> bool {
+        match self {
+            Self::Block(block) => head.number >= block,
+            Self::Time(time) => head.timestamp >= time,
+        }
+    }
+}
+
+impl From<ForkFilterKey> for u64 {
+    fn from(key: ForkFilterKey) -> Self {
+        match key {
+            ForkFilterKey::Block(block) => block,
+            ForkFilterKey::Time(time) => time,
+        }
+    }
+}
+
+/// The head of the local chain, used to determine which forks are active.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Head {
+    /// The number of the head block.
+    pub number: BlockNumber,
+    /// The timestamp of the head block.
+    pub timestamp: Timestamp,
+}
+
+/// A fork identifier as defined by EIP-2124, and extended to timestamp based forks by EIP-6122.
 /// Serves as the chain compatibility identifier.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, RlpEncodable, RlpDecodable, RlpMaxEncodedLen)]
 pub struct ForkId {
-    /// CRC32 checksum of the all fork blocks from genesis.
+    /// CRC32 checksum of the all fork blocks and timestamps from genesis.
     pub hash: ForkHash,
-    /// Next upcoming fork block number, 0 if not yet known.
-    pub next: BlockNumber,
+    /// Next upcoming fork block number or timestamp, 0 if not yet known.
+    pub next: u64,
 }
 
 /// Reason for rejecting provided `ForkId`.
@@ -71,41 +121,149 @@ pub enum ValidationError {
     LocalIncompatibleOrStale,
 }
 
+/// How a remote node relates to the local chain, according to its `ForkId`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PeerClassification {
+    /// The remote node is compatible with the local node.
+    Compatible,
+    /// The remote node is on the same chain, but does not know about a fork the local node has
+    /// already passed, and needs a software update.
+    StaleSameChain,
+    /// The remote node is on a different chain: it either followed forks the local node does not
+    /// know about, or did not follow a fork the local node has passed. This is also the case if
+    /// the local node is missing a fork and needs a software update.
+    DifferentChain,
+}
+
+/// A detailed report of the validation of a remote `ForkId`, see
+/// [`ForkFilter::validate_detailed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ForkValidationReport {
+    /// The fork id announced by the remote node.
+    pub remote: ForkId,
+    /// The local fork id at the time of validation.
+    pub local: ForkId,
+    /// The local head at the time of validation.
+    pub head: Head,
+    /// The local fork whose hash matches the remote fork hash, if any. This can be a past fork,
+    /// the current fork, or a fork the local node has not reached yet.
+    pub matched_fork: Option<ForkFilterKey>,
+    /// For stale remote nodes, the first local fork after [`Self::matched_fork`], which the
+    /// remote node does not know about.
+    pub missing_fork: Option<ForkFilterKey>,
+    /// The result of the `EIP-2124` validation rules.
+    pub result: Result<(), ValidationError>,
+    /// How the remote node relates to the local chain.
+    pub classification: PeerClassification,
+}
+
+impl ForkValidationReport {
+    /// Returns a human readable explanation of the validation result.
+    pub fn explanation(&self) -> String {
+        let fork = |key: ForkFilterKey| match key {
+            ForkFilterKey::Block(block) => format!("the fork at block {block}"),
+            ForkFilterKey::Time(time) => format!("the fork at timestamp {time}"),
+        };
+        let next = match self.remote.next {
+            0 => "no next fork".to_string(),
+            next => format!("next fork at {next}"),
+        };
+
+        match (self.result, self.matched_fork) {
+            (Ok(()), Some(key)) if key.is_active(self.head) => format!(
+                "remote is at {} with {next}, which is compatible with the local chain",
+                fork(key)
+            ),
+            (Ok(()), Some(key)) => format!(
+                "remote is already at {}, which the local node has not reached yet",
+                fork(key)
+            ),
+            (Ok(()), None) => "remote is compatible with the local chain".to_string(),
+            (Err(ValidationError::RemoteStale), Some(key)) => format!(
+                "remote is at {} but announces {next}, while the local chain continued with {}; \
+                 the remote node needs a software update",
+                fork(key),
+                self.missing_fork
+                    .map_or_else(|| "a later fork".to_string(), fork)
+            ),
+            (Err(_), Some(key)) => format!(
+                "remote is at {} and announces {next}, which the local chain passed at head \
+                 {}/{} without forking; the nodes are on different chains",
+                fork(key),
+                self.head.number,
+                self.head.timestamp
+            ),
+            (Err(_), None) => format!(
+                "remote fork hash 0x{} matches no known local fork; the remote node is on a \
+                 different chain, or the local node needs a software update",
+                hex::encode(self.remote.hash.0)
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for ForkValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.explanation())
+    }
+}
+
+/// A change of the local [`ForkId`], caused by a fork activating or by the head moving back
+/// before a fork.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ForkTransition {
+    /// The fork id before the head was updated.
+    pub previous: ForkId,
+    /// The fork id after the head was updated.
+    pub current: ForkId,
+}
+
 /// Filter that describes the state of blockchain and can be used to check incoming `ForkId`s for compatibility.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Cloning a filter keeps its subscribers, which are then notified of transitions of both filters.
+#[derive(Clone, Debug)]
 pub struct ForkFilter {
-    forks: BTreeMap<BlockNumber, ForkHash>,
+    forks: BTreeMap<ForkFilterKey, ForkHash>,
 
-    head: BlockNumber,
+    head: Head,
 
     cache: Cache,
+
+    listeners: Vec<Sender<ForkTransition>>,
+}
+
+// listeners are not part of the state of the filter
+impl PartialEq for ForkFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.forks == other.forks && self.head == other.head && self.cache == other.cache
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 struct Cache {
     // An epoch is a period between forks.
     // When we progress from one fork to the next one we move to the next epoch.
-    epoch_start: BlockNumber,
-    epoch_end: Option<BlockNumber>,
-    past: Vec<(BlockNumber, ForkHash)>,
+    epoch_start: ForkFilterKey,
+    epoch_end: Option<ForkFilterKey>,
+    past: Vec<(ForkFilterKey, ForkHash)>,
     future: Vec<ForkHash>,
     fork_id: ForkId,
 }
 
 impl Cache {
-    fn compute_cache(forks: &BTreeMap<BlockNumber, ForkHash>, head: BlockNumber) -> Self {
+    fn compute_cache(forks: &BTreeMap<ForkFilterKey, ForkHash>, head: Head) -> Self {
         let mut past = Vec::with_capacity(forks.len());
         let mut future = Vec::with_capacity(forks.len());
 
-        let mut epoch_start = 0;
+        let mut epoch_start = ForkFilterKey::Block(0);
         let mut epoch_end = None;
-        for (block, hash) in forks {
-            if *block <= head {
-                epoch_start = *block;
-                past.push((*block, *hash));
+        for (key, hash) in forks {
+            if key.is_active(head) {
+                epoch_start = *key;
+                past.push((*key, *hash));
             } else {
                 if epoch_end.is_none() {
-                    epoch_end = Some(*block);
+                    epoch_end = Some(*key);
                 }
                 future.push(*hash);
             }
@@ -116,7 +274,7 @@ impl Cache {
                 .last()
                 .expect("there is always at least one - genesis - fork hash; qed")
                 .1,
-            next: epoch_end.unwrap_or(0),
+            next: epoch_end.map_or(0, u64::from),
         };
 
         Self {
@@ -130,21 +288,43 @@ impl Cache {
 }
 
 impl ForkFilter {
-    /// Create the filter from provided head, genesis block hash, past forks and expected future forks.
-    pub fn new<F>(head: BlockNumber, genesis: H256, forks: F) -> Self
+    /// Create the filter from provided head, genesis block hash and timestamp, and the past and
+    /// expected future forks, activated by block number or by timestamp.
+    ///
+    /// Forks that are already active at genesis are skipped, as required by EIP-2124 and EIP-6122.
+    pub fn new<B, T>(
+        head: Head,
+        genesis: H256,
+        genesis_timestamp: Timestamp,
+        block_forks: B,
+        time_forks: T,
+    ) -> Self
     where
-        F: IntoIterator<Item = BlockNumber>,
+        B: IntoIterator<Item = BlockNumber>,
+        T: IntoIterator<Item = Timestamp>,
     {
         let genesis_fork_hash = ForkHash::from(genesis);
-        let mut forks = forks.into_iter().collect::<BTreeSet<_>>();
-        forks.remove(&0);
+        let forks = block_forks
+            .into_iter()
+            .filter(|block| *block > 0)
+            .map(ForkFilterKey::Block)
+            .chain(
+                time_forks
+                    .into_iter()
+                    .filter(|time| *time > genesis_timestamp)
+                    .map(ForkFilterKey::Time),
+            )
+            .collect::<BTreeSet<_>>();
         let forks = forks
             .into_iter()
             .fold(
-                (btreemap! { 0 => genesis_fork_hash }, genesis_fork_hash),
-                |(mut acc, base_hash), block| {
-                    let fork_hash = base_hash + block;
-                    acc.insert(block, fork_hash);
+                (
+                    btreemap! { ForkFilterKey::Block(0) => genesis_fork_hash },
+                    genesis_fork_hash,
+                ),
+                |(mut acc, base_hash), key| {
+                    let fork_hash = base_hash + u64::from(key);
+                    acc.insert(key, fork_hash);
                     (acc, fork_hash)
                 },
             )
@@ -152,16 +332,21 @@ impl ForkFilter {
 
         let cache = Cache::compute_cache(&forks, head);
 
-        Self { forks, head, cache }
+        Self {
+            forks,
+            head,
+            cache,
+            listeners: Vec::new(),
+        }
     }
 
-    fn set_head_priv(&mut self, head: BlockNumber) -> bool {
+    fn set_head_priv(&mut self, head: Head) -> bool {
         #[allow(clippy::option_if_let_else)]
         let recompute_cache = {
-            if head < self.cache.epoch_start {
+            if !self.cache.epoch_start.is_active(head) {
                 true
             } else if let Some(epoch_end) = self.cache.epoch_end {
-                head >= epoch_end
+                epoch_end.is_active(head)
             } else {
                 false
             }
@@ -175,9 +360,40 @@ impl ForkFilter {
         recompute_cache
     }
 
-    /// Set the current head
-    pub fn set_head(&mut self, head: BlockNumber) {
-        self.set_head_priv(head);
+    /// Set the current head.
+    ///
+    /// Returns the [`ForkTransition`] if this changed the current [`ForkId`], after sending it to
+    /// all subscribers.
+    pub fn set_head(&mut self, head: Head) -> Option<ForkTransition> {
+        let previous = self.current();
+        if !self.set_head_priv(head) || previous == self.current() {
+            return None;
+        }
+
+        let transition = ForkTransition {
+            previous,
+            current: self.current(),
+        };
+        // drop the subscribers whose receivers are gone
+        self.listeners
+            .retain(|listener| listener.send(transition).is_ok());
+        Some(transition)
+    }
+
+    /// Subscribe to changes of the current [`ForkId`], for example to re-advertise the local
+    /// `Status` and node record, and to re-check the fork ids of connected peers.
+    ///
+    /// A [`ForkTransition`] is sent whenever [`ForkFilter::set_head`] changes the fork id.
+    pub fn subscribe(&mut self) -> Receiver<ForkTransition> {
+        let (sender, receiver) = channel();
+        self.listeners.push(sender);
+        receiver
+    }
+
+    /// Return the current head.
+    #[must_use]
+    pub const fn head(&self) -> Head {
+        self.head
     }
 
     /// Return current fork id
@@ -198,8 +414,13 @@ impl ForkFilter {
                 return Ok(());
             }
 
-            //... compare local head to FORK_NEXT.
-            if self.head >= fork_id.next {
+            //... compare local head to FORK_NEXT, which is a block number or a timestamp.
+            let head = if fork_id.next >= TIMESTAMP_BEFORE_ETHEREUM_MAINNET {
+                self.head.timestamp
+            } else {
+                self.head.number
+            };
+            if head >= fork_id.next {
                 // 1a) A remotely announced but remotely not passed block is already passed locally, disconnect,
                 // since the chains are incompatible.
                 return Err(ValidationError::LocalIncompatibleOrStale);
@@ -214,8 +435,8 @@ impl ForkFilter {
         while let Some((_, hash)) = it.next() {
             if *hash == fork_id.hash {
                 // ...and the remote FORK_NEXT matches with the locally following fork block number, connect.
-                if let Some((actual_fork_block, _)) = it.next() {
-                    if *actual_fork_block == fork_id.next {
+                if let Some((actual_fork, _)) = it.next() {
+                    if u64::from(*actual_fork) == fork_id.next {
                         return Ok(());
                     } else {
                         return Err(ValidationError::RemoteStale);
@@ -236,6 +457,38 @@ impl ForkFilter {
         // 4) Reject in all other cases.
         Err(ValidationError::LocalIncompatibleOrStale)
     }
+
+    /// Validate the provided `ForkId` like [`ForkFilter::validate`], and report the details of
+    /// the decision.
+    pub fn validate_detailed(&self, fork_id: ForkId) -> ForkValidationReport {
+        let result = self.validate(fork_id);
+
+        let mut forks = self.forks.iter();
+        let matched_fork = forks
+            .by_ref()
+            .find(|(_, hash)| **hash == fork_id.hash)
+            .map(|(key, _)| *key);
+        let missing_fork = match result {
+            Err(ValidationError::RemoteStale) => forks.next().map(|(key, _)| *key),
+            _ => None,
+        };
+
+        let classification = match result {
+            Ok(()) => PeerClassification::Compatible,
+            Err(ValidationError::RemoteStale) => PeerClassification::StaleSameChain,
+            Err(ValidationError::LocalIncompatibleOrStale) => PeerClassification::DifferentChain,
+        };
+
+        ForkValidationReport {
+            remote: fork_id,
+            local: self.current(),
+            head: self.head,
+            matched_fork,
+            missing_fork,
+            result,
+            classification,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -247,6 +500,13 @@ mod tests {
         "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
     ));
 
+    const fn block(number: BlockNumber) -> Head {
+        Head {
+            number,
+            timestamp: 0,
+        }
+    }
+
     // EIP test vectors.
 
     #[test]
@@ -264,15 +524,17 @@ mod tests {
     #[test]
     fn compatibility_check() {
         let mut filter = ForkFilter::new(
-            0,
+            block(0),
             GENESIS_HASH,
+            0,
             vec![
                 1_150_000, 1_920_000, 2_463_000, 2_675_000, 4_370_000, 7_280_000,
             ],
+            vec![],
         );
 
         // Local is mainnet Petersburg, remote announces the same. No future fork is announced.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("668db0af")),
@@ -283,11 +545,11 @@ mod tests {
 
         // Local is mainnet Petersburg, remote announces the same. Remote also announces a next fork
         // at block 0xffffffff, but that is uncertain.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("668db0af")),
-                next: BlockNumber::max_value()
+                next: BlockNumber::MAX
             }),
             Ok(())
         );
@@ -295,7 +557,7 @@ mod tests {
         // Local is mainnet currently in Byzantium only (so it's aware of Petersburg),remote announces
         // also Byzantium, but it's not yet aware of Petersburg (e.g. non updated node before the fork).
         // In this case we don't know if Petersburg passed yet or not.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("a00bc324")),
@@ -307,7 +569,7 @@ mod tests {
         // Local is mainnet currently in Byzantium only (so it's aware of Petersburg), remote announces
         // also Byzantium, and it's also aware of Petersburg (e.g. updated node before the fork). We
         // don't know if Petersburg passed yet (will pass) or not.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("a00bc324")),
@@ -319,17 +581,17 @@ mod tests {
         // Local is mainnet currently in Byzantium only (so it's aware of Petersburg), remote announces
         // also Byzantium, and it's also aware of some random fork (e.g. misconfigured Petersburg). As
         // neither forks passed at neither nodes, they may mismatch, but we still connect for now.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("a00bc324")),
-                next: BlockNumber::max_value()
+                next: BlockNumber::MAX
             }),
             Ok(())
         );
 
         // Local is mainnet Petersburg, remote announces Byzantium + knowledge about Petersburg. Remote is simply out of sync, accept.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("a00bc324")),
@@ -340,7 +602,7 @@ mod tests {
 
         // Local is mainnet Petersburg, remote announces Spurious + knowledge about Byzantium. Remote
         // is definitely out of sync. It may or may not need the Petersburg update, we don't know yet.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("3edd5b10")),
@@ -350,7 +612,7 @@ mod tests {
         );
 
         // Local is mainnet Byzantium, remote announces Petersburg. Local is out of sync, accept.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("668db0af")),
@@ -361,7 +623,7 @@ mod tests {
 
         // Local is mainnet Spurious, remote announces Byzantium, but is not aware of Petersburg. Local
         // out of sync. Local also knows about a future fork, but that is uncertain yet.
-        filter.set_head(4_369_999);
+        filter.set_head(block(4_369_999));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("a00bc324")),
@@ -372,7 +634,7 @@ mod tests {
 
         // Local is mainnet Petersburg. remote announces Byzantium but is not aware of further forks.
         // Remote needs software update.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("a00bc324")),
@@ -383,7 +645,7 @@ mod tests {
 
         // Local is mainnet Petersburg, and isn't aware of more forks. Remote announces Petersburg +
         // 0xffffffff. Local needs software update, reject.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("5cddc0e1")),
@@ -394,7 +656,7 @@ mod tests {
 
         // Local is mainnet Byzantium, and is aware of Petersburg. Remote announces Petersburg +
         // 0xffffffff. Local needs software update, reject.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("5cddc0e1")),
@@ -404,7 +666,7 @@ mod tests {
         );
 
         // Local is mainnet Petersburg, remote is Rinkeby Petersburg.
-        filter.set_head(7_987_396);
+        filter.set_head(block(7_987_396));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("afec6b27")),
@@ -417,7 +679,7 @@ mod tests {
         // at some future block 88888888, for itself, but past block for local. Local is incompatible.
         //
         // This case detects non-upgraded nodes with majority hash power (typical Ropsten mess).
-        filter.set_head(88_888_888);
+        filter.set_head(block(88_888_888));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("668db0af")),
@@ -428,7 +690,7 @@ mod tests {
 
         // Local is mainnet Byzantium. Remote is also in Byzantium, but announces Gopherium (non existing
         // fork) at block 7279999, before Petersburg. Local is incompatible.
-        filter.set_head(7_279_999);
+        filter.set_head(block(7_279_999));
         assert_eq!(
             filter.validate(ForkId {
                 hash: ForkHash(hex!("a00bc324")),
@@ -457,7 +719,7 @@ mod tests {
         assert_eq!(
             &*open_fastrlp::encode_fixed_size(&ForkId {
                 hash: ForkHash(hex!("ffffffff")),
-                next: u64::max_value()
+                next: u64::MAX
             }),
             hex!("ce84ffffffff88ffffffffffffffff")
         );
@@ -480,7 +742,7 @@ mod tests {
             ForkId::decode(&mut (&hex!("ce84ffffffff88ffffffffffffffff") as &[u8])).unwrap(),
             ForkId {
                 hash: ForkHash(hex!("ffffffff")),
-                next: u64::max_value()
+                next: u64::MAX
             }
         );
     }
@@ -503,30 +765,328 @@ mod tests {
             next: 0,
         };
 
-        let mut fork_filter = ForkFilter::new(0, GENESIS_HASH, vec![b1, b2]);
+        let mut fork_filter = ForkFilter::new(block(0), GENESIS_HASH, 0, vec![b1, b2], vec![]);
 
-        assert!(!fork_filter.set_head_priv(0));
+        assert!(!fork_filter.set_head_priv(block(0)));
         assert_eq!(fork_filter.current(), h0);
 
-        assert!(!fork_filter.set_head_priv(1));
+        assert!(!fork_filter.set_head_priv(block(1)));
         assert_eq!(fork_filter.current(), h0);
 
-        assert!(fork_filter.set_head_priv(b1 + 1));
+        assert!(fork_filter.set_head_priv(block(b1 + 1)));
         assert_eq!(fork_filter.current(), h1);
 
-        assert!(!fork_filter.set_head_priv(b1));
+        assert!(!fork_filter.set_head_priv(block(b1)));
         assert_eq!(fork_filter.current(), h1);
 
-        assert!(fork_filter.set_head_priv(b1 - 1));
+        assert!(fork_filter.set_head_priv(block(b1 - 1)));
         assert_eq!(fork_filter.current(), h0);
 
-        assert!(fork_filter.set_head_priv(b1));
+        assert!(fork_filter.set_head_priv(block(b1)));
         assert_eq!(fork_filter.current(), h1);
 
-        assert!(!fork_filter.set_head_priv(b2 - 1));
+        assert!(!fork_filter.set_head_priv(block(b2 - 1)));
         assert_eq!(fork_filter.current(), h1);
 
-        assert!(fork_filter.set_head_priv(b2));
+        assert!(fork_filter.set_head_priv(block(b2)));
         assert_eq!(fork_filter.current(), h2);
     }
+
+    const MAINNET_BLOCK_FORKS: [BlockNumber; 12] = [
+        1_150_000, 1_920_000, 2_463_000, 2_675_000, 4_370_000, 7_280_000, 9_069_000, 9_200_000,
+        12_244_000, 12_965_000, 13_773_000, 15_050_000,
+    ];
+    const MAINNET_TIME_FORKS: [Timestamp; 6] = [
+        1_681_338_455,
+        1_710_338_135,
+        1_746_612_311,
+        1_764_798_551,
+        1_765_290_071,
+        1_767_747_671,
+    ];
+
+    #[test]
+    fn timestamp_forks() {
+        let mut filter = ForkFilter::new(
+            block(0),
+            GENESIS_HASH,
+            0,
+            MAINNET_BLOCK_FORKS,
+            MAINNET_TIME_FORKS,
+        );
+
+        let cases = [
+            (15_050_000, 0, hex!("f0afd0e3"), 1_681_338_455), // Gray Glacier
+            (20_000_000, 1_681_338_455, hex!("dce96c2d"), 1_710_338_135), // Shanghai
+            (30_000_000, 1_710_338_135, hex!("9f3d2254"), 1_746_612_311), // Cancun
+            (30_000_000, 1_746_612_311, hex!("c376cf8b"), 1_764_798_551), // Prague
+            (30_000_000, 1_764_798_551, hex!("5167e2a6"), 1_765_290_071), // Osaka
+            (30_000_000, 1_765_290_071, hex!("cba2a1c0"), 1_767_747_671), // BPO1
+            (30_000_000, 1_767_747_671, hex!("07c9462e"), 0), // BPO2
+        ];
+        for (number, timestamp, hash, next) in cases {
+            filter.set_head(Head { number, timestamp });
+            assert_eq!(
+                filter.current(),
+                ForkId {
+                    hash: ForkHash(hash),
+                    next
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn timestamp_compatibility_check() {
+        let mut filter = ForkFilter::new(
+            block(0),
+            GENESIS_HASH,
+            0,
+            MAINNET_BLOCK_FORKS,
+            MAINNET_TIME_FORKS,
+        );
+
+        // Local is mainnet Gray Glacier, remote announces the same and is aware of Shanghai.
+        filter.set_head(Head {
+            number: 15_050_000,
+            timestamp: 1_681_338_454,
+        });
+        assert_eq!(
+            filter.validate(ForkId {
+                hash: ForkHash(hex!("f0afd0e3")),
+                next: 1_681_338_455
+            }),
+            Ok(())
+        );
+
+        // Local is mainnet Shanghai, remote announces Gray Glacier and is aware of Shanghai. Remote
+        // is simply out of sync, accept.
+        filter.set_head(Head {
+            number: 20_000_000,
+            timestamp: 1_681_338_455,
+        });
+        assert_eq!(
+            filter.validate(ForkId {
+                hash: ForkHash(hex!("f0afd0e3")),
+                next: 1_681_338_455
+            }),
+            Ok(())
+        );
+
+        // Local is mainnet Shanghai, remote announces Gray Glacier but is not aware of Shanghai.
+        // Remote needs software update.
+        assert_eq!(
+            filter.validate(ForkId {
+                hash: ForkHash(hex!("f0afd0e3")),
+                next: 0
+            }),
+            Err(ValidationError::RemoteStale)
+        );
+
+        // Local is mainnet Shanghai, remote announces Shanghai with a future fork at a timestamp
+        // that local has already passed. Local is incompatible.
+        filter.set_head(Head {
+            number: 20_000_000,
+            timestamp: 1_700_000_000,
+        });
+        assert_eq!(
+            filter.validate(ForkId {
+                hash: ForkHash(hex!("dce96c2d")),
+                next: 1_690_000_000
+            }),
+            Err(ValidationError::LocalIncompatibleOrStale)
+        );
+
+        // The same fork at a timestamp that local has not passed yet is fine, even though the head
+        // block number is far above it.
+        assert_eq!(
+            filter.validate(ForkId {
+                hash: ForkHash(hex!("dce96c2d")),
+                next: 1_710_000_000
+            }),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn timestamp_threshold() {
+        let head = Head {
+            number: 1_000,
+            timestamp: 1_500_000_000,
+        };
+        let filter = ForkFilter::new(head, GENESIS_HASH, 0, [], []);
+        let hash = filter.current().hash;
+
+        // just below the mainnet genesis timestamp, next is a block number the head has not passed
+        assert_eq!(
+            filter.validate(ForkId {
+                hash,
+                next: TIMESTAMP_BEFORE_ETHEREUM_MAINNET - 1
+            }),
+            Ok(())
+        );
+        // from the mainnet genesis timestamp, next is a timestamp the head has passed
+        assert_eq!(
+            filter.validate(ForkId {
+                hash,
+                next: TIMESTAMP_BEFORE_ETHEREUM_MAINNET
+            }),
+            Err(ValidationError::LocalIncompatibleOrStale)
+        );
+    }
+
+    #[test]
+    fn skip_forks_at_genesis() {
+        // Sepolia activates every block fork at genesis, except for the merge netsplit block.
+        let genesis = H256(hex!(
+            "25a5cc106eea7138acab33231d7160d69cb777ee0c2c553fcddf5138993e6dd9"
+        ));
+        let filter = ForkFilter::new(
+            block(0),
+            genesis,
+            1_633_267_481,
+            vec![0, 0, 1_735_371],
+            vec![1_633_267_481, 1_677_557_088],
+        );
+        assert_eq!(
+            filter.current(),
+            ForkId {
+                hash: ForkHash(hex!("fe3366e7")),
+                next: 1_735_371
+            }
+        );
+    }
+
+    #[test]
+    fn fork_transitions() {
+        let mut filter = ForkFilter::new(
+            block(0),
+            GENESIS_HASH,
+            0,
+            MAINNET_BLOCK_FORKS,
+            MAINNET_TIME_FORKS,
+        );
+        let transitions = filter.subscribe();
+
+        let gray_glacier = Head {
+            number: 15_050_000,
+            timestamp: 1_681_338_454,
+        };
+        let shanghai = Head {
+            number: 15_050_001,
+            timestamp: 1_681_338_455,
+        };
+
+        let to_gray_glacier = filter.set_head(gray_glacier).unwrap();
+        assert_eq!(to_gray_glacier.previous.hash, ForkHash(hex!("fc64ec04")));
+        assert_eq!(to_gray_glacier.current.hash, ForkHash(hex!("f0afd0e3")));
+
+        // moving within the same fork is not a transition
+        assert_eq!(
+            filter.set_head(Head {
+                number: 15_050_001,
+                timestamp: 1_681_338_454,
+            }),
+            None
+        );
+
+        let to_shanghai = filter.set_head(shanghai).unwrap();
+        assert_eq!(
+            to_shanghai,
+            ForkTransition {
+                previous: ForkId {
+                    hash: ForkHash(hex!("f0afd0e3")),
+                    next: 1_681_338_455
+                },
+                current: ForkId {
+                    hash: ForkHash(hex!("dce96c2d")),
+                    next: 1_710_338_135
+                },
+            }
+        );
+
+        // a reorg back before the fork is a transition as well
+        let reorg = filter.set_head(gray_glacier).unwrap();
+        assert_eq!(reorg.previous, to_shanghai.current);
+        assert_eq!(reorg.current, to_shanghai.previous);
+
+        assert_eq!(
+            transitions.try_iter().collect::<Vec<_>>(),
+            vec![to_gray_glacier, to_shanghai, reorg]
+        );
+
+        // dropped subscribers are removed on the next transition
+        drop(transitions);
+        assert!(filter.set_head(shanghai).is_some());
+        assert!(filter.listeners.is_empty());
+    }
+
+    #[test]
+    fn detailed_validation() {
+        let mut filter = ForkFilter::new(
+            block(0),
+            GENESIS_HASH,
+            0,
+            vec![
+                1_150_000, 1_920_000, 2_463_000, 2_675_000, 4_370_000, 7_280_000,
+            ],
+            vec![],
+        );
+        filter.set_head(block(7_987_396));
+
+        // Remote announces the same fork.
+        let report = filter.validate_detailed(ForkId {
+            hash: ForkHash(hex!("668db0af")),
+            next: 0,
+        });
+        assert_eq!(report.classification, PeerClassification::Compatible);
+        assert_eq!(report.matched_fork, Some(ForkFilterKey::Block(7_280_000)));
+        assert_eq!(report.local, filter.current());
+        assert_eq!(report.head, block(7_987_396));
+        assert_eq!(
+            report.to_string(),
+            "remote is at the fork at block 7280000 with no next fork, which is compatible with the local chain"
+        );
+
+        // Remote announces Byzantium but is not aware of Petersburg.
+        let report = filter.validate_detailed(ForkId {
+            hash: ForkHash(hex!("a00bc324")),
+            next: 0,
+        });
+        assert_eq!(report.result, Err(ValidationError::RemoteStale));
+        assert_eq!(report.classification, PeerClassification::StaleSameChain);
+        assert_eq!(report.matched_fork, Some(ForkFilterKey::Block(4_370_000)));
+        assert_eq!(report.missing_fork, Some(ForkFilterKey::Block(7_280_000)));
+        assert_eq!(
+            report.to_string(),
+            "remote is at the fork at block 4370000 but announces no next fork, while the local \
+             chain continued with the fork at block 7280000; the remote node needs a software update"
+        );
+
+        // Remote announces Gopherium at a block the local chain already passed.
+        let report = filter.validate_detailed(ForkId {
+            hash: ForkHash(hex!("668db0af")),
+            next: 7_900_000,
+        });
+        assert_eq!(
+            report.result,
+            Err(ValidationError::LocalIncompatibleOrStale)
+        );
+        assert_eq!(report.classification, PeerClassification::DifferentChain);
+        assert_eq!(report.matched_fork, Some(ForkFilterKey::Block(7_280_000)));
+        assert_eq!(report.missing_fork, None);
+
+        // Remote is Rinkeby Petersburg.
+        let report = filter.validate_detailed(ForkId {
+            hash: ForkHash(hex!("afec6b27")),
+            next: 0,
+        });
+        assert_eq!(report.classification, PeerClassification::DifferentChain);
+        assert_eq!(report.matched_fork, None);
+        assert_eq!(
+            report.to_string(),
+            "remote fork hash 0xafec6b27 matches no known local fork; the remote node is on a \
+             different chain, or the local node needs a software update"
+        );
+    }
 }
@@ -0,0 +1,467 @@
+//! Types for geth style `genesis.json` files.
+use crate::{
+    forkid::{BlockNumber, ForkFilter, Head, Timestamp},
+    primitives::encode_list_payload,
+    trie::{sec_trie_root, EMPTY_ROOT},
+    Chain, EthVersion, Header, Status,
+};
+use ethers_core::{
+    types::{Address, Bloom, Bytes, H256, H64, U256},
+    utils::keccak256,
+};
+use hex_literal::hex;
+use open_fastrlp::Encodable;
+use serde::{de::Error as _, Deserialize, Deserializer};
+use std::collections::BTreeMap;
+
+/// The hash of an empty list of ommers, `keccak256(rlp([]))`.
+const EMPTY_OMMERS_HASH: H256 = H256(hex!(
+    "1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"
+));
+
+/// The requests hash of a block without requests, `sha256("")`.
+const EMPTY_REQUESTS_HASH: H256 = H256(hex!(
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+));
+
+/// The gas limit of the genesis block if none is given.
+const DEFAULT_GAS_LIMIT: u64 = 4_712_388;
+
+/// The difficulty of the genesis block if neither difficulty nor mix hash are given.
+const DEFAULT_DIFFICULTY: u64 = 131_072;
+
+/// The base fee of the genesis block if London is active at genesis, and no base fee is given.
+const DEFAULT_BASE_FEE: u64 = 1_000_000_000;
+
+/// The fork schedule and chain id of a `genesis.json`.
+///
+/// Block and time forks that are not known by name, such as those of BNB Smart Chain, are kept
+/// in [`ChainConfig::other`] and are included in the fork id.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainConfig {
+    /// The chain id, as introduced in
+    /// [EIP155](https://eips.ethereum.org/EIPS/eip-155#list-of-chain-ids).
+    pub chain_id: u64,
+    /// The Homestead fork block.
+    pub homestead_block: Option<BlockNumber>,
+    /// The DAO fork block.
+    pub dao_fork_block: Option<BlockNumber>,
+    /// The Tangerine Whistle fork block.
+    pub eip150_block: Option<BlockNumber>,
+    /// The Spurious Dragon fork block, for replay protection.
+    pub eip155_block: Option<BlockNumber>,
+    /// The Spurious Dragon fork block, for state clearing.
+    pub eip158_block: Option<BlockNumber>,
+    /// The Byzantium fork block.
+    pub byzantium_block: Option<BlockNumber>,
+    /// The Constantinople fork block.
+    pub constantinople_block: Option<BlockNumber>,
+    /// The Petersburg fork block.
+    pub petersburg_block: Option<BlockNumber>,
+    /// The Istanbul fork block.
+    pub istanbul_block: Option<BlockNumber>,
+    /// The Muir Glacier fork block.
+    pub muir_glacier_block: Option<BlockNumber>,
+    /// The Berlin fork block.
+    pub berlin_block: Option<BlockNumber>,
+    /// The London fork block.
+    pub london_block: Option<BlockNumber>,
+    /// The Arrow Glacier fork block.
+    pub arrow_glacier_block: Option<BlockNumber>,
+    /// The Gray Glacier fork block.
+    pub gray_glacier_block: Option<BlockNumber>,
+    /// The block used to split the network after the merge.
+    pub merge_netsplit_block: Option<BlockNumber>,
+    /// The Shanghai fork timestamp.
+    pub shanghai_time: Option<Timestamp>,
+    /// The Cancun fork timestamp.
+    pub cancun_time: Option<Timestamp>,
+    /// The Prague fork timestamp.
+    pub prague_time: Option<Timestamp>,
+    /// The Osaka fork timestamp.
+    pub osaka_time: Option<Timestamp>,
+    /// Any other fields of the config.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+impl ChainConfig {
+    /// Returns the numbers of all fork blocks, including the ones in [`ChainConfig::other`].
+    pub fn block_forks(&self) -> Vec<BlockNumber> {
+        [
+            self.homestead_block,
+            self.dao_fork_block,
+            self.eip150_block,
+            self.eip155_block,
+            self.eip158_block,
+            self.byzantium_block,
+            self.constantinople_block,
+            self.petersburg_block,
+            self.istanbul_block,
+            self.muir_glacier_block,
+            self.berlin_block,
+            self.london_block,
+            self.arrow_glacier_block,
+            self.gray_glacier_block,
+            self.merge_netsplit_block,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.other_forks("Block"))
+        .collect()
+    }
+
+    /// Returns the timestamps of all time forks, including the ones in [`ChainConfig::other`].
+    pub fn time_forks(&self) -> Vec<Timestamp> {
+        [
+            self.shanghai_time,
+            self.cancun_time,
+            self.prague_time,
+            self.osaka_time,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.other_forks("Time"))
+        .collect()
+    }
+
+    fn other_forks<'a>(&'a self, suffix: &'a str) -> impl Iterator<Item = u64> + 'a {
+        self.other
+            .iter()
+            .filter(move |(name, _)| name.ends_with(suffix))
+            .filter_map(|(_, value)| value.as_u64())
+    }
+}
+
+/// An account in the `alloc` of a `genesis.json`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct GenesisAccount {
+    /// The balance of the account.
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub balance: U256,
+    /// The nonce of the account.
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub nonce: u64,
+    /// The code of the account.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub code: Bytes,
+    /// The storage of the account.
+    #[serde(default)]
+    pub storage: BTreeMap<H256, H256>,
+}
+
+impl GenesisAccount {
+    /// Returns the root of the storage trie of the account.
+    pub fn storage_root(&self) -> H256 {
+        sec_trie_root(
+            self.storage
+                .iter()
+                .map(|(slot, value)| (slot, U256::from_big_endian(value.as_bytes())))
+                .filter(|(_, value)| !value.is_zero())
+                .map(|(slot, value)| {
+                    let mut encoded = vec![];
+                    value.encode(&mut encoded);
+                    (slot, encoded)
+                }),
+        )
+    }
+
+    /// Returns the RLP encoding of the account, as stored in the state trie.
+    fn encode_state(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        self.nonce.encode(&mut payload);
+        self.balance.encode(&mut payload);
+        self.storage_root().encode(&mut payload);
+        H256(keccak256(&self.code)).encode(&mut payload);
+        let mut out = vec![];
+        encode_list_payload(&payload, &mut out);
+        out
+    }
+}
+
+/// A geth style `genesis.json`, describing the genesis block and the fork schedule of a chain.
+///
+/// # Example
+/// ```
+/// use ethp2p::Genesis;
+///
+/// let genesis = Genesis::from_json(
+///     r#"{
+///         "config": { "chainId": 1337, "homesteadBlock": 0, "londonBlock": 0 },
+///         "gasLimit": "0x1c9c380",
+///         "difficulty": "0x1",
+///         "alloc": { "0x0000000000000000000000000000000000000001": { "balance": "1" } }
+///     }"#,
+/// )
+/// .unwrap();
+/// assert_eq!(genesis.config.chain_id, 1337);
+/// assert_eq!(genesis.alloc.len(), 1);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Genesis {
+    /// The chain id and fork schedule.
+    #[serde(default)]
+    pub config: ChainConfig,
+    /// The nonce of the genesis block.
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub nonce: u64,
+    /// The timestamp of the genesis block.
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub timestamp: Timestamp,
+    /// The extra data of the genesis block.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub extra_data: Bytes,
+    /// The gas limit of the genesis block.
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub gas_limit: u64,
+    /// The difficulty of the genesis block. An explicit zero, as in post-merge devnets, is kept.
+    #[serde(default, deserialize_with = "deserialize_option_u256")]
+    pub difficulty: Option<U256>,
+    /// The mix hash of the genesis block.
+    #[serde(default)]
+    pub mix_hash: H256,
+    /// The beneficiary of the genesis block.
+    #[serde(default)]
+    pub coinbase: Address,
+    /// The accounts of the genesis state.
+    #[serde(default)]
+    pub alloc: BTreeMap<Address, GenesisAccount>,
+    /// The number of the genesis block, which is usually zero.
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub number: BlockNumber,
+    /// The gas used by the genesis block.
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub gas_used: u64,
+    /// The parent hash of the genesis block.
+    #[serde(default)]
+    pub parent_hash: H256,
+    /// The base fee of the genesis block, if London is active at genesis.
+    #[serde(default, deserialize_with = "deserialize_option_u256")]
+    pub base_fee_per_gas: Option<U256>,
+    /// The excess blob gas of the genesis block, if Cancun is active at genesis.
+    #[serde(default, deserialize_with = "deserialize_option_u64")]
+    pub excess_blob_gas: Option<u64>,
+    /// The blob gas used by the genesis block, if Cancun is active at genesis.
+    #[serde(default, deserialize_with = "deserialize_option_u64")]
+    pub blob_gas_used: Option<u64>,
+}
+
+impl Genesis {
+    /// Parses a `genesis.json`.
+    ///
+    /// # Errors
+    /// Returns an error if the json is not a valid genesis.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Returns the root of the genesis state.
+    pub fn state_root(&self) -> H256 {
+        sec_trie_root(
+            self.alloc
+                .iter()
+                .map(|(address, account)| (address, account.encode_state())),
+        )
+    }
+
+    /// Returns the genesis block header.
+    ///
+    /// The header contains the fields of every fork active at genesis, like geth does.
+    pub fn header(&self) -> Header {
+        let block_active = |fork: Option<BlockNumber>| fork.is_some_and(|b| b <= self.number);
+        let time_active = |fork: Option<Timestamp>| fork.is_some_and(|t| t <= self.timestamp);
+
+        let gas_limit = if self.gas_limit == 0 {
+            DEFAULT_GAS_LIMIT
+        } else {
+            self.gas_limit
+        };
+        let difficulty = match self.difficulty {
+            Some(difficulty) => difficulty,
+            None if self.mix_hash.is_zero() => U256::from(DEFAULT_DIFFICULTY),
+            None => U256::zero(),
+        };
+        let cancun = time_active(self.config.cancun_time);
+
+        Header {
+            parent_hash: self.parent_hash,
+            ommers_hash: EMPTY_OMMERS_HASH,
+            beneficiary: self.coinbase,
+            state_root: self.state_root(),
+            transactions_root: EMPTY_ROOT,
+            receipts_root: EMPTY_ROOT,
+            logs_bloom: Bloom::zero(),
+            difficulty,
+            number: self.number.into(),
+            gas_limit: gas_limit.into(),
+            gas_used: self.gas_used.into(),
+            timestamp: self.timestamp,
+            extra_data: self.extra_data.clone(),
+            mix_hash: self.mix_hash,
+            nonce: H64(self.nonce.to_be_bytes()),
+            base_fee_per_gas: block_active(self.config.london_block).then(|| {
+                self.base_fee_per_gas
+                    .unwrap_or(U256::from(DEFAULT_BASE_FEE))
+            }),
+            withdrawals_root: time_active(self.config.shanghai_time).then_some(EMPTY_ROOT),
+            blob_gas_used: cancun.then(|| self.blob_gas_used.unwrap_or_default()),
+            excess_blob_gas: cancun.then(|| self.excess_blob_gas.unwrap_or_default()),
+            parent_beacon_block_root: cancun.then(H256::zero),
+            requests_hash: time_active(self.config.prague_time).then_some(EMPTY_REQUESTS_HASH),
+        }
+    }
+
+    /// Returns the hash of the genesis block, as used in [`Status::genesis`].
+    pub fn hash(&self) -> H256 {
+        self.header().hash()
+    }
+
+    /// Creates a [`ForkFilter`] for the chain at the given head.
+    pub fn fork_filter(&self, head: Head) -> ForkFilter {
+        ForkFilter::new(
+            head,
+            self.hash(),
+            self.timestamp,
+            self.config.block_forks(),
+            self.config.time_forks(),
+        )
+    }
+
+    /// Creates the [`Status`] message announcing the given head on this chain.
+    pub fn status(
+        &self,
+        version: EthVersion,
+        head: Head,
+        blockhash: [u8; 32],
+        total_difficulty: U256,
+    ) -> Status {
+        Status {
+            version: version as u8,
+            chain: Chain::Id(self.config.chain_id),
+            total_difficulty,
+            blockhash,
+            genesis: self.hash().0,
+            forkid: self.fork_filter(head).current(),
+        }
+    }
+}
+
+/// A number in a `genesis.json`, which is either a json number, a hex string or a decimal string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Quantity {
+    Number(u64),
+    String(String),
+}
+
+impl Quantity {
+    fn into_u256<E: serde::de::Error>(self) -> Result<U256, E> {
+        match self {
+            Self::Number(number) => Ok(number.into()),
+            Self::String(string) => match string.strip_prefix("0x") {
+                Some(hex) => U256::from_str_radix(hex, 16).map_err(E::custom),
+                None => U256::from_dec_str(&string).map_err(E::custom),
+            },
+        }
+    }
+}
+
+fn deserialize_u256<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+    Quantity::deserialize(deserializer)?.into_u256()
+}
+
+fn deserialize_option_u256<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<U256>, D::Error> {
+    Option::<Quantity>::deserialize(deserializer)?
+        .map(Quantity::into_u256)
+        .transpose()
+}
+
+fn deserialize_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let value = deserialize_u256(deserializer)?;
+    if value > U256::from(u64::MAX) {
+        return Err(D::Error::custom("number does not fit into u64"));
+    }
+    Ok(value.as_u64())
+}
+
+fn deserialize_option_u64<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u64>, D::Error> {
+    match deserialize_option_u256(deserializer)? {
+        Some(value) if value > U256::from(u64::MAX) => {
+            Err(D::Error::custom("number does not fit into u64"))
+        }
+        value => Ok(value.map(|value| value.as_u64())),
+    }
+}
+
+fn deserialize_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Bytes, D::Error> {
+    let string = String::deserialize(deserializer)?;
+    hex::decode(string.strip_prefix("0x").unwrap_or(&string))
+        .map(Into::into)
+        .map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Genesis, GenesisAccount};
+    use crate::trie::EMPTY_ROOT;
+    use ethers_core::types::{H256, U256};
+    use hex_literal::hex;
+    use maplit::btreemap;
+
+    #[test]
+    fn parse_quantities() {
+        let genesis = Genesis::from_json(
+            r#"{
+                "config": { "chainId": 5, "istanbulBlock": 1561651, "shanghaiTime": 1678832736, "customBlock": 7 },
+                "nonce": "0x42",
+                "timestamp": "1548854791",
+                "gasLimit": 10485760,
+                "difficulty": "0x1",
+                "extraData": "0x1234",
+                "alloc": {
+                    "0000000000000000000000000000000000000001": { "balance": "0x10" },
+                    "0x0000000000000000000000000000000000000002": { "balance": "16", "nonce": "0x1" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(genesis.config.chain_id, 5);
+        assert_eq!(genesis.config.block_forks(), vec![1_561_651, 7]);
+        assert_eq!(genesis.config.time_forks(), vec![1_678_832_736]);
+        assert_eq!(genesis.nonce, 0x42);
+        assert_eq!(genesis.timestamp, 1_548_854_791);
+        assert_eq!(genesis.gas_limit, 10_485_760);
+        assert_eq!(genesis.extra_data.as_ref(), &[0x12, 0x34]);
+        let balances = genesis
+            .alloc
+            .values()
+            .map(|account| account.balance)
+            .collect::<Vec<_>>();
+        assert_eq!(balances, vec![U256::from(16), U256::from(16)]);
+    }
+
+    #[test]
+    fn storage_root() {
+        let account = GenesisAccount {
+            storage: btreemap! {
+                H256::zero() => H256::from_low_u64_be(1),
+                H256::from_low_u64_be(1) => H256::zero(),
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            account.storage_root(),
+            H256(hex!(
+                "821e2556a290c86405f8160a2d662042a431ba456b9db265c79bb837c04be5f0"
+            ))
+        );
+        assert_eq!(GenesisAccount::default().storage_root(), EMPTY_ROOT);
+    }
+}
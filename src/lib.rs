@@ -1,11 +1,27 @@
+mod primitives;
+pub use primitives::{
+    BlobTransactionSidecar, Block, Chain, EIP1559Transaction, EIP2930Transaction,
+    EIP4844Transaction, EIP658Receipt, EIP7702Transaction, EthereumPrimitives, Header,
+    LegacyTransaction, Log, NetworkPrimitives, PooledTransaction, Primitive, SignedAuthorization,
+    TransactionKind, TypedReceipt, TypedTransaction, Withdrawal, BYTES_PER_BLOB,
+    CELLS_PER_EXT_BLOB,
+};
+
+/// Chains with a known name, see [`Chain::Named`].
+pub use ethers_core::types::Chain as NamedChain;
+
 mod broadcast;
 pub use broadcast::{
-    BlockHashNumber, NewBlock, NewBlockHashes, NewPooledTransactionHashes, Transactions,
+    BlockHashNumber, NewBlock, NewBlockError, NewBlockHashes, NewPooledTransactionHashes,
+    TotalDifficultyLookup, Transactions, MAX_TOTAL_DIFFICULTY,
 };
 
 mod message;
 pub use message::{EthMessage, EthMessageID, ProtocolMessage, RequestPair};
 
+mod merge;
+pub use merge::{BlockBroadcastAfterMerge, MergePolicy, MessageVerdict};
+
 mod request;
 pub use request::Request;
 
@@ -13,30 +29,92 @@ mod response;
 pub use response::Response;
 
 mod status;
-pub use status::Status;
+pub use status::{Status, UpgradeStatus, UpgradeStatusExtension};
 
 mod blocks;
-pub use blocks::{BlockBodies, BlockHashOrNumber, BlockHeaders, GetBlockBodies, GetBlockHeaders};
+pub use blocks::{
+    BlockBodies, BlockBodiesError, BlockBody, BlockBodyError, BlockHashOrNumber, BlockHeaders,
+    BlockHeadersError, BlockHeadersVerdict, GetBlockBodies, GetBlockHeaders,
+};
 
 mod transactions;
-pub use transactions::{GetPooledTransactions, PooledTransactions};
+pub use transactions::{
+    GetPooledTransactions, MatchedTransactions, PooledTransactions, PooledTransactionsViolation,
+};
+
+mod senders;
+#[cfg(feature = "rayon")]
+pub use senders::par_recover_senders;
+pub use senders::{recover_senders, InvalidSignature};
+
+mod blobs;
+pub use blobs::{
+    kzg_to_versioned_hash, BlobSidecarError, InvalidBlobTransaction, VERSIONED_HASH_VERSION_KZG,
+};
 
 mod receipts;
-pub use receipts::{GetReceipts, Receipts};
+pub use receipts::{GetReceipts, Receipts, ReceiptsError};
 
 mod state;
-pub use state::{GetNodeData, NodeData};
+pub use state::{GetNodeData, MatchedNodeData, NodeData, NodeDataViolation};
 
 mod version;
 pub use version::EthVersion;
 
 mod forkid;
-pub use forkid::{ForkFilter, ForkHash, ForkId};
+pub use forkid::{
+    ForkFilter, ForkFilterKey, ForkHash, ForkId, ForkTransition, ForkValidationReport, Head,
+    PeerClassification, ValidationError,
+};
+
+mod enr;
+pub use enr::{node_id, Enr, EnrBuilder, EnrError, EnrForkIdEntry, NodeId, MAX_ENR_SIZE};
+
+pub mod discv5;
+
+mod spec;
+pub use spec::NetworkSpec;
+
+mod parlia;
+pub use parlia::{
+    Parlia, ParliaError, ParliaExtraData, ParliaValidator, VoteAttestation, VoteData,
+    BLS_PUBLIC_KEY_LENGTH, PARLIA_EXTRA_SEAL, PARLIA_EXTRA_VANITY,
+};
+
+mod clique;
+pub use clique::{
+    Clique, CliqueError, CliqueSnapshot, InvalidCliqueHeader, CLIQUE_EXTRA_SEAL,
+    CLIQUE_EXTRA_VANITY, DIFF_IN_TURN, DIFF_NO_TURN, NONCE_AUTH_VOTE, NONCE_DROP_VOTE,
+};
+
+mod ethash;
+pub use ethash::{
+    Ethash, EthashCache, EthashError, InvalidEthashHeader, ECIP1099_EPOCH_LENGTH,
+    ETC_ECIP1099_BLOCK, ETHASH_EPOCH_LENGTH, MAX_ETHASH_EPOCH,
+};
+
+mod bsc;
+pub use bsc::{
+    BscCap, BscMessage, BscMessageID, BscVersionMismatch, VoteEnvelope, Votes,
+    BLS_SIGNATURE_LENGTH, BSC_MESSAGE_COUNT, BSC_PROTOCOL_NAME, BSC_PROTOCOL_VERSION,
+};
+
+mod snap;
+pub use snap::{
+    AccountData, AccountRange, ByteCodes, GetAccountRange, GetByteCodes, GetStorageRanges,
+    GetTrieNodes, SnapMessage, SnapMessageID, StorageData, StorageRanges, TrieAccount, TrieNodes,
+    EMPTY_CODE_HASH, SNAP_MESSAGE_COUNT, SNAP_PROTOCOL_NAME, SNAP_PROTOCOL_VERSION,
+};
+
+pub mod trie;
+
+mod genesis;
+pub use genesis::{ChainConfig, Genesis, GenesisAccount};
 
 // impl from for each variant of EthMessage
 macro_rules! message_from_impl {
     ($t:ty, $variant:ident) => {
-        impl From<$t> for EthMessage {
+        impl<N: NetworkPrimitives> From<$t> for EthMessage<N> {
             fn from(t: $t) -> Self {
                 EthMessage::$variant(t)
             }
@@ -45,27 +123,28 @@ macro_rules! message_from_impl {
 }
 
 message_from_impl!(Status, Status);
+message_from_impl!(UpgradeStatus, UpgradeStatus);
 message_from_impl!(NewBlockHashes, NewBlockHashes);
-message_from_impl!(Box<NewBlock>, NewBlock);
-message_from_impl!(Transactions, Transactions);
+message_from_impl!(Box<NewBlock<N>>, NewBlock);
+message_from_impl!(Transactions<N>, Transactions);
 message_from_impl!(NewPooledTransactionHashes, NewPooledTransactionHashes);
 message_from_impl!(RequestPair<GetBlockHeaders>, GetBlockHeaders);
-message_from_impl!(RequestPair<BlockHeaders>, BlockHeaders);
+message_from_impl!(RequestPair<BlockHeaders<N>>, BlockHeaders);
 message_from_impl!(RequestPair<GetBlockBodies>, GetBlockBodies);
-message_from_impl!(RequestPair<BlockBodies>, BlockBodies);
+message_from_impl!(RequestPair<BlockBodies<N>>, BlockBodies);
 message_from_impl!(RequestPair<GetPooledTransactions>, GetPooledTransactions);
-message_from_impl!(RequestPair<PooledTransactions>, PooledTransactions);
+message_from_impl!(RequestPair<PooledTransactions<N>>, PooledTransactions);
 message_from_impl!(RequestPair<GetNodeData>, GetNodeData);
 message_from_impl!(RequestPair<NodeData>, NodeData);
 message_from_impl!(RequestPair<GetReceipts>, GetReceipts);
-message_from_impl!(RequestPair<Receipts>, Receipts);
+message_from_impl!(RequestPair<Receipts<N>>, Receipts);
 
 // do the same for each variant of Request and Response
 
 // impl from for each variant of Request
 macro_rules! request_from_impl {
     ($t:ty, $variant:ident) => {
-        impl From<$t> for Request {
+        impl<N: NetworkPrimitives> From<$t> for Request<N> {
             fn from(t: $t) -> Self {
                 Request::$variant(t)
             }
@@ -75,8 +154,8 @@ macro_rules! request_from_impl {
 
 request_from_impl!(Status, Status);
 request_from_impl!(NewBlockHashes, NewBlockHashes);
-request_from_impl!(Box<NewBlock>, NewBlock);
-request_from_impl!(Transactions, Transactions);
+request_from_impl!(Box<NewBlock<N>>, NewBlock);
+request_from_impl!(Transactions<N>, Transactions);
 request_from_impl!(NewPooledTransactionHashes, NewPooledTransactionHashes);
 request_from_impl!(RequestPair<GetBlockHeaders>, GetBlockHeaders);
 request_from_impl!(RequestPair<GetBlockBodies>, GetBlockBodies);
@@ -87,7 +166,7 @@ request_from_impl!(RequestPair<GetReceipts>, GetReceipts);
 // impl from for each variant of Response
 macro_rules! response_from_impl {
     ($t:ty, $variant:ident) => {
-        impl From<$t> for Response {
+        impl<N: NetworkPrimitives> From<$t> for Response<N> {
             fn from(t: $t) -> Self {
                 Response::$variant(t)
             }
@@ -96,8 +175,28 @@ macro_rules! response_from_impl {
 }
 
 response_from_impl!(Status, Status);
-response_from_impl!(RequestPair<BlockHeaders>, BlockHeaders);
-response_from_impl!(RequestPair<BlockBodies>, BlockBodies);
-response_from_impl!(RequestPair<PooledTransactions>, PooledTransactions);
+response_from_impl!(RequestPair<BlockHeaders<N>>, BlockHeaders);
+response_from_impl!(RequestPair<BlockBodies<N>>, BlockBodies);
+response_from_impl!(RequestPair<PooledTransactions<N>>, PooledTransactions);
 response_from_impl!(RequestPair<NodeData>, NodeData);
-response_from_impl!(RequestPair<Receipts>, Receipts);
+response_from_impl!(RequestPair<Receipts<N>>, Receipts);
+
+// impl from for each variant of SnapMessage
+macro_rules! snap_message_from_impl {
+    ($t:ty, $variant:ident) => {
+        impl From<$t> for SnapMessage {
+            fn from(t: $t) -> Self {
+                SnapMessage::$variant(t)
+            }
+        }
+    };
+}
+
+snap_message_from_impl!(RequestPair<GetAccountRange>, GetAccountRange);
+snap_message_from_impl!(RequestPair<AccountRange>, AccountRange);
+snap_message_from_impl!(RequestPair<GetStorageRanges>, GetStorageRanges);
+snap_message_from_impl!(RequestPair<StorageRanges>, StorageRanges);
+snap_message_from_impl!(RequestPair<GetByteCodes>, GetByteCodes);
+snap_message_from_impl!(RequestPair<ByteCodes>, ByteCodes);
+snap_message_from_impl!(RequestPair<GetTrieNodes>, GetTrieNodes);
+snap_message_from_impl!(RequestPair<TrieNodes>, TrieNodes);
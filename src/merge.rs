@@ -0,0 +1,126 @@
+use ethers_core::types::U256;
+use thiserror::Error;
+
+use crate::{EthMessage, NetworkPrimitives};
+
+/// Whether the chain has transitioned to proof of stake, which determines how block broadcasts
+/// are handled, as specified in [EIP-3675](https://eips.ethereum.org/EIPS/eip-3675).
+///
+/// Once the chain reaches its terminal total difficulty, blocks are no longer propagated with
+/// [`NewBlock`](crate::NewBlock) and [`NewBlockHashes`](crate::NewBlockHashes), and these
+/// messages are ignored. Once the first proof of stake block is finalized, peers which still send
+/// them are disconnected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// The chain transitions once the total difficulty of its head reaches the given terminal
+    /// total difficulty.
+    TerminalTotalDifficulty(U256),
+    /// The chain has transitioned, and the first proof of stake block has been finalized.
+    Merged,
+}
+
+/// How an incoming message should be handled, see [`MergePolicy::classify`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MessageVerdict {
+    /// The message should be handled.
+    Allowed,
+    /// The message should be dropped, without penalizing the peer.
+    Ignore,
+    /// The message is invalid, and the peer should be disconnected.
+    Disconnect,
+}
+
+/// A block broadcast was about to be sent after the transition to proof of stake.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[error("blocks are not broadcast after the merge")]
+pub struct BlockBroadcastAfterMerge;
+
+impl MergePolicy {
+    /// Returns whether blocks are still propagated by broadcasts, given the total difficulty of
+    /// the head of the chain.
+    pub fn allows_block_broadcasts(&self, total_difficulty: U256) -> bool {
+        match self {
+            MergePolicy::TerminalTotalDifficulty(terminal) => total_difficulty < *terminal,
+            MergePolicy::Merged => false,
+        }
+    }
+
+    /// Classifies an incoming message, given the total difficulty of the head of the chain.
+    ///
+    /// Only block broadcasts are affected by the merge, all other messages are allowed.
+    pub fn classify<N: NetworkPrimitives>(
+        &self,
+        message: &EthMessage<N>,
+        total_difficulty: U256,
+    ) -> MessageVerdict {
+        if !is_block_broadcast(message) || self.allows_block_broadcasts(total_difficulty) {
+            return MessageVerdict::Allowed;
+        }
+        match self {
+            MergePolicy::TerminalTotalDifficulty(_) => MessageVerdict::Ignore,
+            MergePolicy::Merged => MessageVerdict::Disconnect,
+        }
+    }
+
+    /// Checks that an outgoing message may be sent, given the total difficulty of the head of the
+    /// chain.
+    pub fn check_outbound<N: NetworkPrimitives>(
+        &self,
+        message: &EthMessage<N>,
+        total_difficulty: U256,
+    ) -> Result<(), BlockBroadcastAfterMerge> {
+        if is_block_broadcast(message) && !self.allows_block_broadcasts(total_difficulty) {
+            return Err(BlockBroadcastAfterMerge);
+        }
+        Ok(())
+    }
+}
+
+fn is_block_broadcast<N: NetworkPrimitives>(message: &EthMessage<N>) -> bool {
+    matches!(
+        message,
+        EthMessage::NewBlock(_) | EthMessage::NewBlockHashes(_)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use ethers_core::types::U256;
+
+    use super::{BlockBroadcastAfterMerge, MergePolicy, MessageVerdict};
+    use crate::{EthMessage, NewBlockHashes, Transactions};
+
+    #[test]
+    fn classify_block_broadcasts() {
+        let hashes: EthMessage = EthMessage::NewBlockHashes(NewBlockHashes(vec![]));
+        let transactions: EthMessage = EthMessage::Transactions(Transactions(vec![]));
+        let terminal = MergePolicy::TerminalTotalDifficulty(1000.into());
+
+        // before the terminal total difficulty, everything is allowed
+        let td = U256::from(999);
+        assert_eq!(terminal.classify(&hashes, td), MessageVerdict::Allowed);
+        assert_eq!(terminal.check_outbound(&hashes, td), Ok(()));
+
+        // after the transition, block broadcasts are ignored
+        let td = U256::from(1000);
+        assert_eq!(terminal.classify(&hashes, td), MessageVerdict::Ignore);
+        assert_eq!(
+            terminal.classify(&transactions, td),
+            MessageVerdict::Allowed
+        );
+        assert_eq!(
+            terminal.check_outbound(&hashes, td),
+            Err(BlockBroadcastAfterMerge)
+        );
+
+        // after finalization, peers sending block broadcasts are disconnected
+        let merged = MergePolicy::Merged;
+        assert_eq!(merged.classify(&hashes, td), MessageVerdict::Disconnect);
+        assert_eq!(merged.classify(&transactions, td), MessageVerdict::Allowed);
+        assert_eq!(
+            merged.check_outbound(&hashes, U256::zero()),
+            Err(BlockBroadcastAfterMerge)
+        );
+        assert_eq!(merged.check_outbound(&transactions, td), Ok(()));
+    }
+}
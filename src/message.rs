@@ -5,18 +5,19 @@ use open_fastrlp::{length_of_length, Decodable, Encodable, Header};
 use crate::{
     blocks::{BlockBodies, BlockHeaders, GetBlockBodies},
     broadcast::{NewBlock, NewBlockHashes, NewPooledTransactionHashes, Transactions},
-    GetBlockHeaders, GetNodeData, GetPooledTransactions, GetReceipts, NodeData, PooledTransactions,
-    Receipts, Status,
+    primitives::{decode_list, ensure_consumed},
+    EthereumPrimitives, GetBlockHeaders, GetNodeData, GetPooledTransactions, GetReceipts,
+    NetworkPrimitives, NodeData, PooledTransactions, Receipts, Status, UpgradeStatus,
 };
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 /// An `eth` protocol message, containing a message ID and payload.
-pub struct ProtocolMessage {
+pub struct ProtocolMessage<N: NetworkPrimitives = EthereumPrimitives> {
     pub message_type: EthMessageID,
-    pub message: EthMessage,
+    pub message: EthMessage<N>,
 }
 
-impl ProtocolMessage {
+impl<N: NetworkPrimitives> ProtocolMessage<N> {
     /// Create a new ProtocolMessage from a message type and message rlp bytes.
     pub fn decode_message(
         message_type: EthMessageID,
@@ -24,6 +25,7 @@ impl ProtocolMessage {
     ) -> Result<Self, open_fastrlp::DecodeError> {
         let message = match message_type {
             EthMessageID::Status => EthMessage::Status(Status::decode(buf)?),
+            EthMessageID::UpgradeStatus => EthMessage::UpgradeStatus(UpgradeStatus::decode(buf)?),
             EthMessageID::NewBlockHashes => {
                 EthMessage::NewBlockHashes(NewBlockHashes::decode(buf)?)
             }
@@ -37,7 +39,7 @@ impl ProtocolMessage {
                 EthMessage::GetBlockHeaders(request_pair)
             }
             EthMessageID::BlockHeaders => {
-                let request_pair = RequestPair::<BlockHeaders>::decode(buf)?;
+                let request_pair = RequestPair::<BlockHeaders<N>>::decode(buf)?;
                 EthMessage::BlockHeaders(request_pair)
             }
             EthMessageID::GetBlockBodies => {
@@ -45,7 +47,7 @@ impl ProtocolMessage {
                 EthMessage::GetBlockBodies(request_pair)
             }
             EthMessageID::BlockBodies => {
-                let request_pair = RequestPair::<BlockBodies>::decode(buf)?;
+                let request_pair = RequestPair::<BlockBodies<N>>::decode(buf)?;
                 EthMessage::BlockBodies(request_pair)
             }
             EthMessageID::GetPooledTransactions => {
@@ -53,7 +55,7 @@ impl ProtocolMessage {
                 EthMessage::GetPooledTransactions(request_pair)
             }
             EthMessageID::PooledTransactions => {
-                let request_pair = RequestPair::<PooledTransactions>::decode(buf)?;
+                let request_pair = RequestPair::<PooledTransactions<N>>::decode(buf)?;
                 EthMessage::PooledTransactions(request_pair)
             }
             EthMessageID::GetNodeData => {
@@ -69,7 +71,7 @@ impl ProtocolMessage {
                 EthMessage::GetReceipts(request_pair)
             }
             EthMessageID::Receipts => {
-                let request_pair = RequestPair::<Receipts>::decode(buf)?;
+                let request_pair = RequestPair::<Receipts<N>>::decode(buf)?;
                 EthMessage::Receipts(request_pair)
             }
         };
@@ -82,7 +84,7 @@ impl ProtocolMessage {
 
 /// Encodes the protocol message into bytes.
 /// The message type is encoded as a single byte and prepended to the message.
-impl Encodable for ProtocolMessage {
+impl<N: NetworkPrimitives> Encodable for ProtocolMessage<N> {
     fn length(&self) -> usize {
         self.message_type.length() + self.message.length()
     }
@@ -94,15 +96,15 @@ impl Encodable for ProtocolMessage {
 
 /// Decodes a protocol message from bytes, using the first byte to determine the message type.
 /// This decodes `eth/66` request ids for each message type.
-impl Decodable for ProtocolMessage {
+impl<N: NetworkPrimitives> Decodable for ProtocolMessage<N> {
     fn decode(buf: &mut &[u8]) -> Result<Self, open_fastrlp::DecodeError> {
         let message_type = EthMessageID::decode(buf)?;
         Self::decode_message(message_type, buf)
     }
 }
 
-impl From<EthMessage> for ProtocolMessage {
-    fn from(message: EthMessage) -> Self {
+impl<N: NetworkPrimitives> From<EthMessage<N>> for ProtocolMessage<N> {
+    fn from(message: EthMessage<N>) -> Self {
         ProtocolMessage {
             message_type: message.message_id(),
             message,
@@ -117,40 +119,46 @@ impl From<EthMessage> for ProtocolMessage {
 /// The ethereum wire protocol is a set of messages that are broadcasted to the network in two
 /// styles:
 ///  * A request message sent by a peer (such as [`GetPooledTransactions`]), and an associated
-///  response message (such as [`PooledTransactions`]).
+///    response message (such as [`PooledTransactions`]).
 ///  * A message that is broadcast to the network, without a corresponding request.
 ///
 ///  The newer `eth/66` is an efficiency upgrade on top of `eth/65`, introducing a request id to
 ///  correlate request-response message pairs. This allows for request multiplexing.
+///
+/// The blocks, transactions and receipts in the messages are those of the network primitives
+/// `N`, see [`NetworkPrimitives`].
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub enum EthMessage {
+pub enum EthMessage<N: NetworkPrimitives = EthereumPrimitives> {
     // Status is required for the protocol handshake
     Status(Status),
+    // Sent after the status by BNB Smart Chain peers
+    UpgradeStatus(UpgradeStatus),
 
     // The following messages are broadcast to the network
     NewBlockHashes(NewBlockHashes),
-    NewBlock(Box<NewBlock>),
-    Transactions(Transactions),
+    NewBlock(Box<NewBlock<N>>),
+    Transactions(Transactions<N>),
     NewPooledTransactionHashes(NewPooledTransactionHashes),
 
     // The following messages are request-response message pairs
     GetBlockHeaders(RequestPair<GetBlockHeaders>),
-    BlockHeaders(RequestPair<BlockHeaders>),
+    BlockHeaders(RequestPair<BlockHeaders<N>>),
     GetBlockBodies(RequestPair<GetBlockBodies>),
-    BlockBodies(RequestPair<BlockBodies>),
+    BlockBodies(RequestPair<BlockBodies<N>>),
     GetPooledTransactions(RequestPair<GetPooledTransactions>),
-    PooledTransactions(RequestPair<PooledTransactions>),
+    PooledTransactions(RequestPair<PooledTransactions<N>>),
     GetNodeData(RequestPair<GetNodeData>),
     NodeData(RequestPair<NodeData>),
     GetReceipts(RequestPair<GetReceipts>),
-    Receipts(RequestPair<Receipts>),
+    Receipts(RequestPair<Receipts<N>>),
 }
 
-impl EthMessage {
+impl<N: NetworkPrimitives> EthMessage<N> {
     /// Returns the message's ID.
     pub fn message_id(&self) -> EthMessageID {
         match self {
             EthMessage::Status(_) => EthMessageID::Status,
+            EthMessage::UpgradeStatus(_) => EthMessageID::UpgradeStatus,
             EthMessage::NewBlockHashes(_) => EthMessageID::NewBlockHashes,
             EthMessage::NewBlock(_) => EthMessageID::NewBlock,
             EthMessage::Transactions(_) => EthMessageID::Transactions,
@@ -169,10 +177,11 @@ impl EthMessage {
     }
 }
 
-impl Encodable for EthMessage {
+impl<N: NetworkPrimitives> Encodable for EthMessage<N> {
     fn length(&self) -> usize {
         match self {
             EthMessage::Status(status) => status.length(),
+            EthMessage::UpgradeStatus(status) => status.length(),
             EthMessage::NewBlockHashes(new_block_hashes) => new_block_hashes.length(),
             EthMessage::NewBlock(new_block) => new_block.length(),
             EthMessage::Transactions(transactions) => transactions.length(),
@@ -192,6 +201,7 @@ impl Encodable for EthMessage {
     fn encode(&self, out: &mut dyn bytes::BufMut) {
         match self {
             EthMessage::Status(status) => status.encode(out),
+            EthMessage::UpgradeStatus(status) => status.encode(out),
             EthMessage::NewBlockHashes(new_block_hashes) => new_block_hashes.encode(out),
             EthMessage::NewBlock(new_block) => new_block.encode(out),
             EthMessage::Transactions(transactions) => transactions.encode(out),
@@ -225,6 +235,8 @@ pub enum EthMessageID {
     NewPooledTransactionHashes = 0x08,
     GetPooledTransactions = 0x09,
     PooledTransactions = 0x0a,
+    /// The [`UpgradeStatus`] of BNB Smart Chain peers.
+    UpgradeStatus = 0x0b,
     GetNodeData = 0x0d,
     NodeData = 0x0e,
     GetReceipts = 0x0f,
@@ -257,6 +269,7 @@ impl Decodable for EthMessageID {
             0x08 => EthMessageID::NewPooledTransactionHashes,
             0x09 => EthMessageID::GetPooledTransactions,
             0x0a => EthMessageID::PooledTransactions,
+            0x0b => EthMessageID::UpgradeStatus,
             0x0d => EthMessageID::GetNodeData,
             0x0e => EthMessageID::NodeData,
             0x0f => EthMessageID::GetReceipts,
@@ -282,6 +295,7 @@ impl TryFrom<usize> for EthMessageID {
             0x08 => Ok(EthMessageID::NewPooledTransactionHashes),
             0x09 => Ok(EthMessageID::GetPooledTransactions),
             0x0a => Ok(EthMessageID::PooledTransactions),
+            0x0b => Ok(EthMessageID::UpgradeStatus),
             0x0d => Ok(EthMessageID::GetNodeData),
             0x0e => Ok(EthMessageID::NodeData),
             0x0f => Ok(EthMessageID::GetReceipts),
@@ -334,11 +348,13 @@ where
     T: Decodable,
 {
     fn decode(buf: &mut &[u8]) -> Result<Self, open_fastrlp::DecodeError> {
-        let _header = Header::decode(buf)?;
-        Ok(Self {
-            request_id: u64::decode(buf)?,
-            message: T::decode(buf)?,
-        })
+        let payload = &mut decode_list(buf)?;
+        let pair = Self {
+            request_id: u64::decode(payload)?,
+            message: T::decode(payload)?,
+        };
+        ensure_consumed(payload)?;
+        Ok(pair)
     }
 }
 
@@ -0,0 +1,470 @@
+use ethers_core::{
+    types::{Address, Bytes, Signature, H256, U256},
+    utils::keccak256,
+};
+use open_fastrlp::{Decodable, DecodeError, Encodable, RlpDecodable, RlpEncodable};
+use thiserror::Error;
+
+use crate::{primitives::encode_list_payload, Header};
+
+/// The length of the vanity prefix of the extra data.
+pub const PARLIA_EXTRA_VANITY: usize = 32;
+
+/// The length of the validator seal at the end of the extra data.
+pub const PARLIA_EXTRA_SEAL: usize = 65;
+
+/// The length of the BLS public key of a validator, which it uses to sign votes.
+pub const BLS_PUBLIC_KEY_LENGTH: usize = 48;
+
+/// The parameters of a chain using Parlia, the proof of staked authority consensus of the BNB
+/// Smart Chain.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Parlia {
+    /// The chain id, which is included in the seal hash.
+    pub chain_id: u64,
+    /// The block of the Luban hard fork, from which validators have BLS keys and blocks may
+    /// carry a vote attestation.
+    pub luban_block: Option<u64>,
+    /// The timestamp of the Bohr hard fork, from which epoch blocks carry the turn length.
+    pub bohr_time: Option<u64>,
+}
+
+/// A validator of a Parlia chain, as listed in the extra data of epoch blocks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParliaValidator {
+    pub address: Address,
+    /// The BLS public key used to sign votes, from Luban onwards.
+    pub vote_address: Option<[u8; BLS_PUBLIC_KEY_LENGTH]>,
+}
+
+/// The source and target of a fast finality vote.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct VoteData {
+    /// The number of the latest justified block.
+    pub source_number: u64,
+    /// The hash of the latest justified block.
+    pub source_hash: H256,
+    /// The number of the block being voted for.
+    pub target_number: u64,
+    /// The hash of the block being voted for.
+    pub target_hash: H256,
+}
+
+impl VoteData {
+    /// Returns the hash of the vote data, which is what validators sign.
+    pub fn hash(&self) -> H256 {
+        let mut encoded = vec![];
+        self.encode(&mut encoded);
+        H256(keccak256(encoded))
+    }
+}
+
+/// The aggregated votes of the validators for the parent of a block, which a block may carry in
+/// its extra data from Luban onwards.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct VoteAttestation {
+    /// A bit set of the validators which voted, indexed by their position in the validator set.
+    pub vote_address_set: u64,
+    /// The aggregated BLS signature of the votes.
+    pub agg_signature: [u8; 96],
+    pub data: VoteData,
+    /// Reserved for future use.
+    pub extra: Bytes,
+}
+
+/// The fields of the extra data of a Parlia block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParliaExtraData {
+    pub vanity: [u8; PARLIA_EXTRA_VANITY],
+    /// The validator set for the next epoch, which is only present in epoch blocks.
+    pub validators: Vec<ParliaValidator>,
+    /// The number of consecutive blocks each validator produces, which is present in epoch
+    /// blocks from Bohr onwards.
+    pub turn_length: Option<u8>,
+    pub vote_attestation: Option<VoteAttestation>,
+    /// The signature of the validator which sealed the block.
+    pub seal: [u8; PARLIA_EXTRA_SEAL],
+}
+
+/// Ways in which the extra data or the seal of a Parlia block can be invalid.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum ParliaError {
+    /// The extra data is too short for the vanity and the seal.
+    #[error("extra data of {0} bytes is too short")]
+    ExtraTooShort(usize),
+    /// The validator set of an epoch block has an invalid length.
+    #[error("invalid validator set of {0} bytes")]
+    InvalidValidators(usize),
+    /// A non-epoch block before Luban has data between its vanity and seal.
+    #[error("unexpected data in the extra data of a non-epoch block")]
+    UnexpectedData,
+    /// The vote attestation could not be decoded.
+    #[error("invalid vote attestation: {0}")]
+    VoteAttestation(#[source] DecodeError),
+    /// No signer could be recovered from the seal.
+    #[error("invalid seal")]
+    InvalidSeal,
+    /// The block was sealed by another account than its coinbase.
+    #[error("block sealed by {signer:?}, but its coinbase is {coinbase:?}")]
+    CoinbaseMismatch {
+        /// The account which sealed the block.
+        signer: Address,
+        /// The coinbase of the block.
+        coinbase: Address,
+    },
+    /// The block was sealed by an account which is not a validator.
+    #[error("block sealed by {0:?}, which is not a validator")]
+    UnauthorizedSigner(Address),
+}
+
+impl Parlia {
+    /// The BNB Smart Chain mainnet.
+    pub fn bsc() -> Self {
+        Self {
+            chain_id: 56,
+            luban_block: Some(29_020_050),
+            bohr_time: Some(1_727_317_200),
+        }
+    }
+
+    /// The BNB Smart Chain testnet, also known as Chapel.
+    pub fn bsc_testnet() -> Self {
+        Self {
+            chain_id: 97,
+            luban_block: Some(29_295_050),
+            bohr_time: Some(1_724_116_996),
+        }
+    }
+
+    fn is_luban(&self, header: &Header) -> bool {
+        self.luban_block
+            .is_some_and(|block| header.number >= U256::from(block))
+    }
+
+    fn is_bohr(&self, header: &Header) -> bool {
+        self.bohr_time.is_some_and(|time| header.timestamp >= time)
+    }
+
+    /// Parses the extra data of a block.
+    ///
+    /// Whether the block is the first of an epoch, and so lists the validators, depends on the
+    /// epoch length, which the BNB Smart Chain has changed in hard forks. It is up to the caller
+    /// to track it.
+    pub fn parse_extra_data(
+        &self,
+        header: &Header,
+        is_epoch: bool,
+    ) -> Result<ParliaExtraData, ParliaError> {
+        let extra = &header.extra_data[..];
+        if extra.len() < PARLIA_EXTRA_VANITY + PARLIA_EXTRA_SEAL {
+            return Err(ParliaError::ExtraTooShort(extra.len()));
+        }
+        let (vanity, rest) = extra.split_at(PARLIA_EXTRA_VANITY);
+        let (mut data, seal) = rest.split_at(rest.len() - PARLIA_EXTRA_SEAL);
+
+        let mut parsed = ParliaExtraData {
+            vanity: vanity.try_into().unwrap(),
+            validators: vec![],
+            turn_length: None,
+            vote_attestation: None,
+            seal: seal.try_into().unwrap(),
+        };
+
+        if !self.is_luban(header) {
+            // before Luban, epoch blocks list the validator addresses and nothing else
+            if !is_epoch && !data.is_empty() {
+                return Err(ParliaError::UnexpectedData);
+            }
+            if data.len() % Address::len_bytes() != 0 {
+                return Err(ParliaError::InvalidValidators(data.len()));
+            }
+            parsed.validators = data
+                .chunks_exact(Address::len_bytes())
+                .map(|address| ParliaValidator {
+                    address: Address::from_slice(address),
+                    vote_address: None,
+                })
+                .collect();
+            return Ok(parsed);
+        }
+
+        if is_epoch {
+            const VALIDATOR_LENGTH: usize = 20 + BLS_PUBLIC_KEY_LENGTH;
+            let (count, validators) = data
+                .split_first()
+                .ok_or(ParliaError::InvalidValidators(0))?;
+            let length = *count as usize * VALIDATOR_LENGTH;
+            if validators.len() < length {
+                return Err(ParliaError::InvalidValidators(data.len()));
+            }
+            let (validators, rest) = validators.split_at(length);
+            parsed.validators = validators
+                .chunks_exact(VALIDATOR_LENGTH)
+                .map(|validator| {
+                    let (address, vote_address) = validator.split_at(20);
+                    ParliaValidator {
+                        address: Address::from_slice(address),
+                        vote_address: Some(vote_address.try_into().unwrap()),
+                    }
+                })
+                .collect();
+            data = rest;
+
+            if self.is_bohr(header) {
+                let (turn_length, rest) = data
+                    .split_first()
+                    .ok_or(ParliaError::InvalidValidators(length + 1))?;
+                parsed.turn_length = Some(*turn_length);
+                data = rest;
+            }
+        }
+
+        if !data.is_empty() {
+            let attestation =
+                VoteAttestation::decode(&mut data).map_err(ParliaError::VoteAttestation)?;
+            if !data.is_empty() {
+                return Err(ParliaError::UnexpectedData);
+            }
+            parsed.vote_attestation = Some(attestation);
+        }
+        Ok(parsed)
+    }
+
+    /// Returns the hash which the validator signs to seal the block.
+    ///
+    /// This is the hash of the header without the seal, with the chain id prepended. Headers
+    /// from Cancun onwards, which have a zero parent beacon block root, include the fields added
+    /// since London as well.
+    pub fn seal_hash(&self, header: &Header) -> Result<H256, ParliaError> {
+        let extra = &header.extra_data[..];
+        if extra.len() < PARLIA_EXTRA_SEAL {
+            return Err(ParliaError::ExtraTooShort(extra.len()));
+        }
+
+        let mut payload = vec![];
+        self.chain_id.encode(&mut payload);
+        header.parent_hash.encode(&mut payload);
+        header.ommers_hash.encode(&mut payload);
+        header.beneficiary.encode(&mut payload);
+        header.state_root.encode(&mut payload);
+        header.transactions_root.encode(&mut payload);
+        header.receipts_root.encode(&mut payload);
+        header.logs_bloom.encode(&mut payload);
+        header.difficulty.encode(&mut payload);
+        header.number.encode(&mut payload);
+        header.gas_limit.encode(&mut payload);
+        header.gas_used.encode(&mut payload);
+        header.timestamp.encode(&mut payload);
+        let unsealed = &extra[..extra.len() - PARLIA_EXTRA_SEAL];
+        unsealed.encode(&mut payload);
+        header.mix_hash.encode(&mut payload);
+        header.nonce.encode(&mut payload);
+
+        if header.parent_beacon_block_root == Some(H256::zero()) {
+            // unset fields are encoded as empty strings
+            fn encode_optional<T: Encodable>(field: &Option<T>, out: &mut Vec<u8>) {
+                match field {
+                    Some(field) => field.encode(out),
+                    None => out.push(open_fastrlp::EMPTY_STRING_CODE),
+                }
+            }
+            encode_optional(&header.base_fee_per_gas, &mut payload);
+            encode_optional(&header.withdrawals_root, &mut payload);
+            encode_optional(&header.blob_gas_used, &mut payload);
+            encode_optional(&header.excess_blob_gas, &mut payload);
+            encode_optional(&header.parent_beacon_block_root, &mut payload);
+            if let Some(requests_hash) = &header.requests_hash {
+                requests_hash.encode(&mut payload);
+            }
+        }
+
+        let mut encoded = vec![];
+        encode_list_payload(&payload, &mut encoded);
+        Ok(H256(keccak256(encoded)))
+    }
+
+    /// Recovers the validator which sealed the block from the seal in its extra data.
+    pub fn recover_signer(&self, header: &Header) -> Result<Address, ParliaError> {
+        let seal_hash = self.seal_hash(header)?;
+        let extra = &header.extra_data[..];
+        let seal = &extra[extra.len() - PARLIA_EXTRA_SEAL..];
+        let signature = Signature {
+            r: U256::from_big_endian(&seal[..32]),
+            s: U256::from_big_endian(&seal[32..64]),
+            v: seal[64].into(),
+        };
+        if signature.v > 1 {
+            return Err(ParliaError::InvalidSeal);
+        }
+        signature
+            .recover(seal_hash)
+            .map_err(|_| ParliaError::InvalidSeal)
+    }
+
+    /// Checks that the block was sealed by its coinbase, and that the coinbase is one of the
+    /// given validators, returning the signer.
+    ///
+    /// This does not check whether the validator was in turn, or has signed too recently.
+    pub fn verify_seal(
+        &self,
+        header: &Header,
+        validators: &[Address],
+    ) -> Result<Address, ParliaError> {
+        let signer = self.recover_signer(header)?;
+        if signer != header.beneficiary {
+            return Err(ParliaError::CoinbaseMismatch {
+                signer,
+                coinbase: header.beneficiary,
+            });
+        }
+        if !validators.contains(&signer) {
+            return Err(ParliaError::UnauthorizedSigner(signer));
+        }
+        Ok(signer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::PathBuf};
+
+    use ethers_core::types::{Address, Bytes, H256};
+    use hex_literal::hex;
+    use open_fastrlp::{Decodable, Encodable};
+
+    use super::{
+        Parlia, ParliaError, ParliaValidator, VoteAttestation, VoteData, BLS_PUBLIC_KEY_LENGTH,
+    };
+    use crate::{Header, NewBlock};
+
+    fn bsc_block(name: &str) -> Header {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata")
+            .join(name);
+        let data = fs::read_to_string(path).unwrap();
+        let encoded = hex::decode(data.trim()).unwrap();
+        <NewBlock>::decode(&mut &encoded[..]).unwrap().block.header
+    }
+
+    #[test]
+    fn recover_bsc_signer() {
+        let parlia = Parlia::bsc();
+        for name in ["bsc_new_block_network_one", "bsc_new_block_network_two"] {
+            let header = bsc_block(name);
+            let signer = parlia.recover_signer(&header).unwrap();
+            assert_eq!(signer, header.beneficiary);
+            assert_eq!(
+                parlia.verify_seal(&header, &[header.beneficiary]),
+                Ok(signer)
+            );
+            assert_eq!(
+                parlia.verify_seal(&header, &[]),
+                Err(ParliaError::UnauthorizedSigner(signer))
+            );
+
+            let extra = parlia.parse_extra_data(&header, false).unwrap();
+            assert!(extra.validators.is_empty());
+            assert_eq!(extra.vote_attestation, None);
+
+            // the seal hash covers the chain id
+            let testnet = Parlia::bsc_testnet();
+            assert_ne!(testnet.recover_signer(&header).ok(), Some(signer));
+        }
+    }
+
+    #[test]
+    fn tampered_bsc_block() {
+        let parlia = Parlia::bsc();
+        let mut header = bsc_block("bsc_new_block_network_one");
+        let coinbase = header.beneficiary;
+        header.gas_used += 1.into();
+        assert!(matches!(
+            parlia.verify_seal(&header, &[coinbase]),
+            Err(ParliaError::CoinbaseMismatch { .. }) | Err(ParliaError::InvalidSeal)
+        ));
+
+        header.extra_data = Bytes::from(vec![0; 64]);
+        assert_eq!(
+            parlia.verify_seal(&header, &[coinbase]),
+            Err(ParliaError::ExtraTooShort(64))
+        );
+    }
+
+    #[test]
+    fn parse_epoch_extra_data() {
+        let parlia = Parlia::bsc();
+        let validators = [
+            ParliaValidator {
+                address: Address::repeat_byte(0x11),
+                vote_address: Some([0xaa; BLS_PUBLIC_KEY_LENGTH]),
+            },
+            ParliaValidator {
+                address: Address::repeat_byte(0x22),
+                vote_address: Some([0xbb; BLS_PUBLIC_KEY_LENGTH]),
+            },
+        ];
+        let attestation = VoteAttestation {
+            vote_address_set: 0b11,
+            agg_signature: [0xcc; 96],
+            data: VoteData {
+                source_number: 39_999_998,
+                source_hash: H256::repeat_byte(0x01),
+                target_number: 39_999_999,
+                target_hash: H256::repeat_byte(0x02),
+            },
+            extra: Bytes::default(),
+        };
+
+        let mut extra = vec![0x42; 32];
+        extra.push(validators.len() as u8);
+        for validator in &validators {
+            extra.extend_from_slice(validator.address.as_bytes());
+            extra.extend_from_slice(&validator.vote_address.unwrap());
+        }
+        // the turn length, from Bohr onwards
+        extra.push(4);
+        attestation.encode(&mut extra);
+        extra.extend_from_slice(&[0x99; 65]);
+
+        let header = Header {
+            number: 40_000_000.into(),
+            timestamp: 1_730_000_000,
+            extra_data: extra.into(),
+            ..Default::default()
+        };
+        let parsed = parlia.parse_extra_data(&header, true).unwrap();
+        assert_eq!(parsed.vanity, [0x42; 32]);
+        assert_eq!(parsed.validators, validators);
+        assert_eq!(parsed.turn_length, Some(4));
+        assert_eq!(parsed.vote_attestation, Some(attestation));
+        assert_eq!(parsed.seal, [0x99; 65]);
+
+        // without the validators, the count is read as the start of the attestation
+        assert!(matches!(
+            parlia.parse_extra_data(&header, false),
+            Err(ParliaError::VoteAttestation(_))
+        ));
+
+        // before Luban, validators are listed by address only
+        let mut extra = vec![0; 32];
+        extra.extend_from_slice(&hex!("1111111111111111111111111111111111111111"));
+        extra.extend_from_slice(&[0; 65]);
+        let header = Header {
+            number: 200.into(),
+            extra_data: extra.into(),
+            ..Default::default()
+        };
+        let parsed = parlia.parse_extra_data(&header, true).unwrap();
+        assert_eq!(
+            parsed.validators,
+            vec![ParliaValidator {
+                address: Address::repeat_byte(0x11),
+                vote_address: None,
+            }]
+        );
+        assert_eq!(
+            parlia.parse_extra_data(&header, false),
+            Err(ParliaError::UnexpectedData)
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use open_fastrlp::{BufMut, Decodable, DecodeError, Encodable};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    decode_list, encode_list_payload, ensure_consumed, list_length, EthereumPrimitives,
+    NetworkPrimitives, Withdrawal,
+};
+
+/// A block, consisting of a header and a body.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "N::Header: Serialize, N::Transaction: Serialize",
+    deserialize = "N::Header: Deserialize<'de>, N::Transaction: Deserialize<'de>"
+))]
+pub struct Block<N: NetworkPrimitives = EthereumPrimitives> {
+    pub header: N::Header,
+    pub transactions: Vec<N::Transaction>,
+    pub ommers: Vec<N::Header>,
+    /// The withdrawals of the block, from Shanghai onwards.
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+impl<N: NetworkPrimitives> Block<N> {
+    fn encode_payload(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        self.header.encode(&mut payload);
+        self.transactions.encode(&mut payload);
+        self.ommers.encode(&mut payload);
+        if let Some(withdrawals) = &self.withdrawals {
+            withdrawals.encode(&mut payload);
+        }
+        payload
+    }
+}
+
+impl<N: NetworkPrimitives> Encodable for Block<N> {
+    fn length(&self) -> usize {
+        list_length(self.encode_payload().len())
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_list_payload(&self.encode_payload(), out)
+    }
+}
+
+impl<N: NetworkPrimitives> Decodable for Block<N> {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let payload = &mut decode_list(buf)?;
+        let block = Block {
+            header: Decodable::decode(payload)?,
+            transactions: Decodable::decode(payload)?,
+            ommers: Decodable::decode(payload)?,
+            withdrawals: if payload.is_empty() {
+                None
+            } else {
+                Some(Decodable::decode(payload)?)
+            },
+        };
+        ensure_consumed(payload)?;
+        Ok(block)
+    }
+}
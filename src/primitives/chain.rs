@@ -0,0 +1,64 @@
+use std::fmt;
+
+use ethers_core::types::Chain as NamedChain;
+use open_fastrlp::{BufMut, Decodable, DecodeError, Encodable};
+use serde::{Deserialize, Serialize};
+
+/// A chain, identified by its [EIP-155](https://eips.ethereum.org/EIPS/eip-155) chain id.
+///
+/// Chains known to [`NamedChain`] are decoded as [`Chain::Named`], all others as [`Chain::Id`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Chain {
+    Named(NamedChain),
+    Id(u64),
+}
+
+impl Chain {
+    /// Returns the chain id.
+    pub fn id(&self) -> u64 {
+        match self {
+            Chain::Named(chain) => *chain as u64,
+            Chain::Id(id) => *id,
+        }
+    }
+}
+
+impl From<NamedChain> for Chain {
+    fn from(chain: NamedChain) -> Self {
+        Chain::Named(chain)
+    }
+}
+
+impl From<u64> for Chain {
+    fn from(id: u64) -> Self {
+        NamedChain::try_from(id)
+            .map(Chain::Named)
+            .unwrap_or(Chain::Id(id))
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Chain::Named(chain) => write!(f, "{chain}"),
+            Chain::Id(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+impl Encodable for Chain {
+    fn length(&self) -> usize {
+        self.id().length()
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        self.id().encode(out)
+    }
+}
+
+impl Decodable for Chain {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(u64::decode(buf)?.into())
+    }
+}
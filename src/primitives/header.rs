@@ -0,0 +1,250 @@
+use ethers_core::{
+    types::{Address, Bloom, Bytes, H256, H64, U256},
+    utils::keccak256,
+};
+use open_fastrlp::{BufMut, Decodable, DecodeError, Encodable};
+use serde::{Deserialize, Serialize};
+
+use super::{decode_list, encode_list_payload, ensure_consumed, list_length};
+
+/// A block header.
+///
+/// Fields added by later forks are optional, and are only encoded if they are set. They are
+/// positional, so a field can only be encoded if all fields before it are: unset fields before a
+/// set one are encoded as zero.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Header {
+    pub parent_hash: H256,
+    pub ommers_hash: H256,
+    pub beneficiary: Address,
+    pub state_root: H256,
+    pub transactions_root: H256,
+    pub receipts_root: H256,
+    pub logs_bloom: Bloom,
+    pub difficulty: U256,
+    pub number: U256,
+    pub gas_limit: U256,
+    pub gas_used: U256,
+    pub timestamp: u64,
+    pub extra_data: Bytes,
+    pub mix_hash: H256,
+    pub nonce: H64,
+    /// The base fee of the block, added in London (EIP-1559).
+    pub base_fee_per_gas: Option<U256>,
+    /// The root of the withdrawals trie of the block, added in Shanghai (EIP-4895).
+    pub withdrawals_root: Option<H256>,
+    /// The total blob gas used by the transactions of the block, added in Cancun (EIP-4844).
+    pub blob_gas_used: Option<u64>,
+    /// The blob gas in excess of the target, which determines the blob base fee, added in Cancun
+    /// (EIP-4844).
+    pub excess_blob_gas: Option<u64>,
+    /// The root of the parent beacon block, added in Cancun (EIP-4788).
+    pub parent_beacon_block_root: Option<H256>,
+    /// The hash of the execution layer requests of the block, added in Prague (EIP-7685).
+    pub requests_hash: Option<H256>,
+}
+
+impl Header {
+    /// Returns the hash of the header, which is the hash of the block.
+    pub fn hash(&self) -> H256 {
+        let mut out = vec![];
+        self.encode(&mut out);
+        H256(keccak256(out))
+    }
+
+    fn encode_payload(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        self.parent_hash.encode(&mut payload);
+        self.ommers_hash.encode(&mut payload);
+        self.beneficiary.encode(&mut payload);
+        self.state_root.encode(&mut payload);
+        self.transactions_root.encode(&mut payload);
+        self.receipts_root.encode(&mut payload);
+        self.logs_bloom.encode(&mut payload);
+        self.difficulty.encode(&mut payload);
+        self.number.encode(&mut payload);
+        self.gas_limit.encode(&mut payload);
+        self.gas_used.encode(&mut payload);
+        self.timestamp.encode(&mut payload);
+        self.extra_data.encode(&mut payload);
+        self.mix_hash.encode(&mut payload);
+        self.nonce.encode(&mut payload);
+
+        // the number of optional fields to encode, which is up to the last one that is set
+        let optional = [
+            self.base_fee_per_gas.is_some(),
+            self.withdrawals_root.is_some(),
+            self.blob_gas_used.is_some(),
+            self.excess_blob_gas.is_some(),
+            self.parent_beacon_block_root.is_some(),
+            self.requests_hash.is_some(),
+        ]
+        .iter()
+        .rposition(|set| *set)
+        .map_or(0, |last| last + 1);
+        if optional > 0 {
+            self.base_fee_per_gas
+                .unwrap_or_default()
+                .encode(&mut payload);
+        }
+        if optional > 1 {
+            self.withdrawals_root
+                .unwrap_or_default()
+                .encode(&mut payload);
+        }
+        if optional > 2 {
+            self.blob_gas_used.unwrap_or_default().encode(&mut payload);
+        }
+        if optional > 3 {
+            self.excess_blob_gas
+                .unwrap_or_default()
+                .encode(&mut payload);
+        }
+        if optional > 4 {
+            self.parent_beacon_block_root
+                .unwrap_or_default()
+                .encode(&mut payload);
+        }
+        if optional > 5 {
+            self.requests_hash.unwrap_or_default().encode(&mut payload);
+        }
+        payload
+    }
+}
+
+impl Encodable for Header {
+    fn length(&self) -> usize {
+        list_length(self.encode_payload().len())
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_list_payload(&self.encode_payload(), out)
+    }
+}
+
+impl Decodable for Header {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let payload = &mut decode_list(buf)?;
+        let mut header = Header {
+            parent_hash: Decodable::decode(payload)?,
+            ommers_hash: Decodable::decode(payload)?,
+            beneficiary: Decodable::decode(payload)?,
+            state_root: Decodable::decode(payload)?,
+            transactions_root: Decodable::decode(payload)?,
+            receipts_root: Decodable::decode(payload)?,
+            logs_bloom: Decodable::decode(payload)?,
+            difficulty: Decodable::decode(payload)?,
+            number: Decodable::decode(payload)?,
+            gas_limit: Decodable::decode(payload)?,
+            gas_used: Decodable::decode(payload)?,
+            timestamp: Decodable::decode(payload)?,
+            extra_data: Decodable::decode(payload)?,
+            mix_hash: Decodable::decode(payload)?,
+            nonce: Decodable::decode(payload)?,
+            base_fee_per_gas: None,
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            requests_hash: None,
+        };
+
+        if !payload.is_empty() {
+            header.base_fee_per_gas = Some(Decodable::decode(payload)?);
+        }
+        if !payload.is_empty() {
+            header.withdrawals_root = Some(Decodable::decode(payload)?);
+        }
+        if !payload.is_empty() {
+            header.blob_gas_used = Some(Decodable::decode(payload)?);
+        }
+        if !payload.is_empty() {
+            header.excess_blob_gas = Some(Decodable::decode(payload)?);
+        }
+        if !payload.is_empty() {
+            header.parent_beacon_block_root = Some(Decodable::decode(payload)?);
+        }
+        if !payload.is_empty() {
+            header.requests_hash = Some(Decodable::decode(payload)?);
+        }
+        ensure_consumed(payload)?;
+        Ok(header)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ethers_core::types::{H256, U256};
+    use hex_literal::hex;
+    use open_fastrlp::{Decodable, Encodable};
+
+    use super::Header;
+
+    #[test]
+    // Test vector from: https://eips.ethereum.org/EIPS/eip-2481
+    fn header_roundtrip() {
+        let data = hex!("f901f9a00000000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000000000000000000000000000000000000940000000000000000000000000000000000000000a00000000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000000000000000000000000000000000000b90100000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000008208ae820d0582115c8215b3821a0a827788a00000000000000000000000000000000000000000000000000000000000000000880000000000000000");
+        let header = Header::decode(&mut &data[..]).unwrap();
+        assert_eq!(
+            header,
+            Header {
+                difficulty: 0x8aeu64.into(),
+                number: 0xd05u64.into(),
+                gas_limit: 0x115cu64.into(),
+                gas_used: 0x15b3u64.into(),
+                timestamp: 0x1a0a,
+                extra_data: hex!("7788").into(),
+                ..Default::default()
+            }
+        );
+        let mut encoded = vec![];
+        header.encode(&mut encoded);
+        assert_eq!(encoded, data);
+        assert_eq!(header.length(), data.len());
+    }
+
+    #[test]
+    fn optional_fields() {
+        let london = Header {
+            number: 1u64.into(),
+            base_fee_per_gas: Some(7u64.into()),
+            ..Default::default()
+        };
+        let shanghai = Header {
+            withdrawals_root: Some(H256::repeat_byte(0x56)),
+            ..london.clone()
+        };
+        let cancun = Header {
+            blob_gas_used: Some(0x20000),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(H256::repeat_byte(0xbe)),
+            ..shanghai.clone()
+        };
+        let prague = Header {
+            requests_hash: Some(H256::repeat_byte(0x77)),
+            ..cancun.clone()
+        };
+        for header in [&london, &shanghai, &cancun, &prague] {
+            let mut encoded = vec![];
+            header.encode(&mut encoded);
+            assert_eq!(encoded.len(), header.length());
+            assert_eq!(Header::decode(&mut &encoded[..]).unwrap(), *header);
+        }
+
+        // a base fee of zero is encoded if a later field is set
+        let gap = Header {
+            base_fee_per_gas: None,
+            ..shanghai.clone()
+        };
+        let mut encoded = vec![];
+        gap.encode(&mut encoded);
+        assert_eq!(
+            Header::decode(&mut &encoded[..]).unwrap(),
+            Header {
+                base_fee_per_gas: Some(U256::zero()),
+                ..shanghai
+            }
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Block, transaction and receipt types used in `eth` messages.
+use open_fastrlp::{DecodeError, Header as RlpHeader};
+
+mod block;
+pub use block::Block;
+
+mod chain;
+pub use chain::Chain;
+
+mod header;
+pub use header::Header;
+
+mod network;
+pub use network::{EthereumPrimitives, NetworkPrimitives, Primitive};
+
+mod transaction;
+pub use transaction::{
+    EIP1559Transaction, EIP2930Transaction, EIP4844Transaction, EIP7702Transaction,
+    LegacyTransaction, SignedAuthorization, TransactionKind, TypedTransaction,
+};
+
+mod pooled;
+pub use pooled::{BlobTransactionSidecar, PooledTransaction, BYTES_PER_BLOB, CELLS_PER_EXT_BLOB};
+
+mod receipt;
+pub use receipt::{EIP658Receipt, Log, TypedReceipt};
+
+mod withdrawal;
+pub use withdrawal::Withdrawal;
+
+/// Decodes the header of an RLP list, returning its payload and advancing `buf` past the list.
+pub(crate) fn decode_list<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+    let header = RlpHeader::decode(buf)?;
+    if !header.list {
+        return Err(DecodeError::UnexpectedString);
+    }
+    if buf.len() < header.payload_length {
+        return Err(DecodeError::InputTooShort);
+    }
+    let (payload, rest) = buf.split_at(header.payload_length);
+    *buf = rest;
+    Ok(payload)
+}
+
+/// Decodes the header of an RLP string, returning its payload and advancing `buf` past the
+/// string.
+pub(crate) fn decode_string<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+    let header = RlpHeader::decode(buf)?;
+    if header.list {
+        return Err(DecodeError::UnexpectedList);
+    }
+    if buf.len() < header.payload_length {
+        return Err(DecodeError::InputTooShort);
+    }
+    let (payload, rest) = buf.split_at(header.payload_length);
+    *buf = rest;
+    Ok(payload)
+}
+
+/// Returns an error if the payload of a list has not been fully decoded.
+pub(crate) fn ensure_consumed(payload: &[u8]) -> Result<(), DecodeError> {
+    if payload.is_empty() {
+        Ok(())
+    } else {
+        Err(DecodeError::UnexpectedLength)
+    }
+}
+
+/// Encodes a list with the given, already encoded, payload.
+pub(crate) fn encode_list_payload(payload: &[u8], out: &mut dyn open_fastrlp::BufMut) {
+    RlpHeader {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(out);
+    out.put_slice(payload);
+}
+
+/// Returns the length of a list with a payload of the given length.
+pub(crate) fn list_length(payload_length: usize) -> usize {
+    payload_length + open_fastrlp::length_of_length(payload_length)
+}
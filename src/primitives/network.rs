@@ -0,0 +1,87 @@
+use std::fmt::Debug;
+
+use open_fastrlp::{Decodable, Encodable};
+
+use super::{Header, PooledTransaction, TypedReceipt, TypedTransaction};
+
+/// The types of the blocks, transactions and receipts of a network, which the `eth` messages are
+/// generic over.
+///
+/// Defaults to [`EthereumPrimitives`] everywhere. Chains with other header or transaction
+/// formats, such as the deposit transactions of OP stack chains, can plug in their own types. The
+/// checks against headers, such as [`BlockBodies::validate`](crate::BlockBodies::validate), are
+/// only provided for Ethereum.
+pub trait NetworkPrimitives: Copy + Clone + Debug + PartialEq + Eq + Send + Sync + 'static {
+    /// The block header.
+    type Header: Primitive;
+    /// A transaction, as included in blocks and sent in
+    /// [`Transactions`](crate::Transactions).
+    type Transaction: Primitive;
+    /// A transaction, as sent in [`PooledTransactions`](crate::PooledTransactions).
+    type PooledTransaction: Primitive;
+    /// A transaction receipt.
+    type Receipt: Primitive;
+}
+
+/// The bounds on each of the [`NetworkPrimitives`] types.
+pub trait Primitive: Encodable + Decodable + Clone + Debug + PartialEq + Eq + Send + Sync {}
+
+impl<T> Primitive for T where T: Encodable + Decodable + Clone + Debug + PartialEq + Eq + Send + Sync
+{}
+
+/// The primitives of Ethereum mainnet and its testnets.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EthereumPrimitives;
+
+impl NetworkPrimitives for EthereumPrimitives {
+    type Header = Header;
+    type Transaction = TypedTransaction;
+    type PooledTransaction = PooledTransaction;
+    type Receipt = TypedReceipt;
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::Bytes;
+    use open_fastrlp::Encodable;
+
+    use super::NetworkPrimitives;
+    use crate::{EthMessage, EthMessageID, Header, ProtocolMessage, Transactions, TypedReceipt};
+
+    /// A network with transaction types unknown to this crate, which keeps transactions as their
+    /// opaque envelopes.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    struct OpaquePrimitives;
+
+    impl NetworkPrimitives for OpaquePrimitives {
+        type Header = Header;
+        type Transaction = Bytes;
+        type PooledTransaction = Bytes;
+        type Receipt = TypedReceipt;
+    }
+
+    #[test]
+    fn custom_transaction_type() {
+        // a deposit transaction of an OP stack chain, with type 0x7e
+        let mut deposit = vec![0x7e];
+        let fields = [Bytes::from(vec![0x11; 32]), Bytes::from(vec![0x22; 20])];
+        open_fastrlp::encode_list::<Bytes, _>(&fields, &mut deposit);
+        let txs = Transactions::<OpaquePrimitives>(vec![Bytes::from(deposit)]);
+
+        let mut encoded = vec![];
+        txs.encode(&mut encoded);
+
+        let message = ProtocolMessage::<OpaquePrimitives>::decode_message(
+            EthMessageID::Transactions,
+            &mut &encoded[..],
+        )
+        .unwrap();
+        assert_eq!(message.message, EthMessage::Transactions(txs));
+
+        // the default primitives reject the unknown transaction type
+        assert!(
+            <ProtocolMessage>::decode_message(EthMessageID::Transactions, &mut &encoded[..])
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use ethers_core::types::{Address, Bytes, SignatureError, H256};
+use open_fastrlp::{BufMut, Decodable, DecodeError, Encodable, Header as RlpHeader};
+
+use super::{
+    decode_list, decode_string, encode_list_payload, ensure_consumed, EIP4844Transaction,
+    TypedTransaction,
+};
+
+/// The size of a blob, in bytes.
+pub const BYTES_PER_BLOB: usize = 131_072;
+
+/// The number of cells of an extended blob, each of which has a proof in a version 1 sidecar.
+pub const CELLS_PER_EXT_BLOB: usize = 128;
+
+/// The blobs of an [`EIP4844Transaction`], along with their KZG commitments and proofs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlobTransactionSidecar {
+    /// The version of the sidecar: 0 with a proof per blob, or 1 since
+    /// [EIP-7594](https://eips.ethereum.org/EIPS/eip-7594) with [`CELLS_PER_EXT_BLOB`] cell
+    /// proofs per blob.
+    pub version: u8,
+    pub blobs: Vec<Bytes>,
+    pub commitments: Vec<[u8; 48]>,
+    pub proofs: Vec<[u8; 48]>,
+}
+
+/// A transaction as sent in [`PooledTransactions`](crate::PooledTransactions).
+///
+/// Blob transactions are sent in their network form here, which wraps the transaction with its
+/// blobs: `0x03 || rlp([tx_payload_body, blobs, commitments, proofs])`, or
+/// `0x03 || rlp([tx_payload_body, 1, blobs, commitments, cell_proofs])` for version 1 sidecars.
+/// Other sidecar versions are rejected when decoding. All other transactions are sent the same
+/// way as in blocks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PooledTransaction {
+    /// The transaction, as it is included in blocks.
+    pub transaction: TypedTransaction,
+    /// The blobs of a blob transaction.
+    pub sidecar: Option<BlobTransactionSidecar>,
+}
+
+impl PooledTransaction {
+    /// Returns the hash of the transaction, which does not cover the sidecar.
+    pub fn hash(&self) -> H256 {
+        self.transaction.hash()
+    }
+
+    /// Recovers the address of the sender from the signature.
+    pub fn recover(&self) -> Result<Address, SignatureError> {
+        self.transaction.recover()
+    }
+}
+
+impl From<TypedTransaction> for PooledTransaction {
+    fn from(transaction: TypedTransaction) -> Self {
+        PooledTransaction {
+            transaction,
+            sidecar: None,
+        }
+    }
+}
+
+impl Encodable for PooledTransaction {
+    fn length(&self) -> usize {
+        let mut out = vec![];
+        self.encode(&mut out);
+        out.len()
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        let (tx, sidecar) = match (&self.transaction, &self.sidecar) {
+            (TypedTransaction::EIP4844(tx), Some(sidecar)) => (tx, sidecar),
+            (transaction, _) => return transaction.encode(out),
+        };
+
+        let mut payload = vec![];
+        tx.encode(&mut payload);
+        if sidecar.version != 0 {
+            sidecar.version.encode(&mut payload);
+        }
+        sidecar.blobs.encode(&mut payload);
+        sidecar.commitments.encode(&mut payload);
+        sidecar.proofs.encode(&mut payload);
+        let mut envelope = vec![EIP4844Transaction::TYPE];
+        encode_list_payload(&payload, &mut envelope);
+
+        RlpHeader {
+            list: false,
+            payload_length: envelope.len(),
+        }
+        .encode(out);
+        out.put_slice(&envelope);
+    }
+}
+
+impl Decodable for PooledTransaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        if buf.first().is_some_and(|byte| *byte >= 0xc0) {
+            return Ok(TypedTransaction::decode(buf)?.into());
+        }
+
+        let mut envelope = decode_string(buf)?;
+        if envelope.first() != Some(&EIP4844Transaction::TYPE) {
+            let transaction = TypedTransaction::decode_2718(&mut envelope)?;
+            if matches!(transaction, TypedTransaction::Legacy(_)) {
+                return Err(DecodeError::UnexpectedList);
+            }
+            ensure_consumed(envelope)?;
+            return Ok(transaction.into());
+        }
+
+        // blob transactions must be in their network form
+        envelope = &envelope[1..];
+        let payload = &mut decode_list(&mut envelope)?;
+        ensure_consumed(envelope)?;
+        let transaction = TypedTransaction::EIP4844(Decodable::decode(payload)?);
+        // version 0 sidecars have no version, so the blobs follow the transaction directly
+        let version = if RlpHeader::decode(&mut &payload[..])?.list {
+            0
+        } else {
+            match u8::decode(payload)? {
+                1 => 1,
+                _ => return Err(DecodeError::Custom("unsupported blob sidecar version")),
+            }
+        };
+        let sidecar = BlobTransactionSidecar {
+            version,
+            blobs: Decodable::decode(payload)?,
+            commitments: Decodable::decode(payload)?,
+            proofs: Decodable::decode(payload)?,
+        };
+        ensure_consumed(payload)?;
+        Ok(PooledTransaction {
+            transaction,
+            sidecar: Some(sidecar),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ethers_core::types::{Bytes, H256, U256};
+    use open_fastrlp::{Decodable, DecodeError, Encodable};
+
+    use super::{BlobTransactionSidecar, PooledTransaction};
+    use crate::{EIP4844Transaction, PooledTransactions, Transactions, TypedTransaction};
+
+    fn blob_transaction() -> TypedTransaction {
+        TypedTransaction::EIP4844(EIP4844Transaction {
+            chain_id: 1,
+            nonce: 7u64.into(),
+            max_priority_fee_per_gas: 1_000_000_000u64.into(),
+            max_fee_per_gas: 30_000_000_000u64.into(),
+            gas_limit: 21_000u64.into(),
+            to: [0x11; 20].into(),
+            value: U256::zero(),
+            input: Bytes::default(),
+            access_list: Default::default(),
+            max_fee_per_blob_gas: 1u64.into(),
+            blob_versioned_hashes: vec![H256::repeat_byte(0x01)],
+            odd_y_parity: true,
+            r: H256::repeat_byte(0x22),
+            s: H256::repeat_byte(0x33),
+        })
+    }
+
+    fn encode<T: Encodable>(value: &T) -> Vec<u8> {
+        let mut out = vec![];
+        value.encode(&mut out);
+        assert_eq!(out.len(), value.length());
+        out
+    }
+
+    #[test]
+    fn blob_transaction_forms() {
+        let transaction = blob_transaction();
+        let pooled = PooledTransaction {
+            transaction: transaction.clone(),
+            sidecar: Some(BlobTransactionSidecar {
+                version: 0,
+                blobs: vec![vec![0xab; super::BYTES_PER_BLOB].into()],
+                commitments: vec![[0xcd; 48]],
+                proofs: vec![[0xef; 48]],
+            }),
+        };
+
+        // the bare form is used in blocks and broadcasts
+        let txs: Transactions = Transactions(vec![transaction.clone()]);
+        let bare = encode(&txs);
+        assert_eq!(Transactions::decode(&mut &bare[..]).unwrap(), txs);
+
+        // the network form wraps the bare transaction with the sidecar
+        let response: PooledTransactions = PooledTransactions(vec![pooled.clone()]);
+        let network = encode(&response);
+        assert!(network.len() > super::BYTES_PER_BLOB);
+        assert_eq!(
+            PooledTransactions::decode(&mut &network[..]).unwrap(),
+            response
+        );
+
+        // the hash of the transaction does not cover the sidecar
+        assert_eq!(pooled.hash(), transaction.hash());
+
+        // blob transactions must be sent with their sidecar in pooled transactions
+        assert!(<PooledTransactions>::decode(&mut &bare[..]).is_err());
+        assert!(<Transactions>::decode(&mut &network[..]).is_err());
+    }
+
+    #[test]
+    fn versioned_sidecar() {
+        let transaction = blob_transaction();
+        let pooled = PooledTransaction {
+            transaction: transaction.clone(),
+            sidecar: Some(BlobTransactionSidecar {
+                version: 1,
+                blobs: vec![vec![0xab; super::BYTES_PER_BLOB].into()],
+                commitments: vec![[0xcd; 48]],
+                proofs: vec![[0xef; 48]; super::CELLS_PER_EXT_BLOB],
+            }),
+        };
+        let encoded = encode(&pooled);
+        assert_eq!(
+            PooledTransaction::decode(&mut &encoded[..]).unwrap(),
+            pooled
+        );
+
+        // the version follows the transaction in the network form
+        let mut bare = vec![];
+        match &transaction {
+            TypedTransaction::EIP4844(tx) => tx.encode(&mut bare),
+            _ => unreachable!(),
+        }
+        let version = encoded
+            .windows(bare.len())
+            .position(|window| window == bare)
+            .unwrap()
+            + bare.len();
+        assert_eq!(encoded[version], 0x01);
+
+        // other versions are rejected
+        for unsupported in [0x80, 0x02] {
+            let mut encoded = encoded.clone();
+            encoded[version] = unsupported;
+            assert_eq!(
+                PooledTransaction::decode(&mut &encoded[..]),
+                Err(DecodeError::Custom("unsupported blob sidecar version"))
+            );
+        }
+    }
+}
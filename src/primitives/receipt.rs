@@ -0,0 +1,132 @@
+use ethers_core::types::{Address, Bloom, Bytes, H256, U256};
+use open_fastrlp::{
+    BufMut, Decodable, DecodeError, Encodable, Header as RlpHeader, RlpDecodable, RlpEncodable,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    decode_string, ensure_consumed, EIP1559Transaction, EIP2930Transaction, EIP4844Transaction,
+    EIP7702Transaction,
+};
+
+/// A log emitted by a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, RlpEncodable, RlpDecodable)]
+pub struct Log {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    pub data: Bytes,
+}
+
+/// The fields of a receipt, with the status code introduced in
+/// [EIP-658](https://eips.ethereum.org/EIPS/eip-658).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, RlpEncodable, RlpDecodable)]
+pub struct EIP658Receipt {
+    /// Whether the transaction succeeded, `1` for success and `0` for failure.
+    pub status_code: u8,
+    /// The gas used in the block up to and including this transaction.
+    pub gas_used: U256,
+    pub logs_bloom: Bloom,
+    pub logs: Vec<Log>,
+}
+
+/// A receipt of a transaction of any type.
+///
+/// Like transactions, receipts of typed transactions are encoded as an RLP string containing
+/// their [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718) envelope.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TypedReceipt {
+    Legacy(EIP658Receipt),
+    EIP2930(EIP658Receipt),
+    EIP1559(EIP658Receipt),
+    EIP4844(EIP658Receipt),
+    EIP7702(EIP658Receipt),
+}
+
+impl TypedReceipt {
+    /// Returns the fields of the receipt, which are the same for all transaction types.
+    pub fn receipt(&self) -> &EIP658Receipt {
+        match self {
+            TypedReceipt::Legacy(receipt)
+            | TypedReceipt::EIP2930(receipt)
+            | TypedReceipt::EIP1559(receipt)
+            | TypedReceipt::EIP4844(receipt)
+            | TypedReceipt::EIP7702(receipt) => receipt,
+        }
+    }
+
+    /// Returns the EIP-2718 type of the transaction, or `None` for legacy transactions.
+    pub fn tx_type(&self) -> Option<u8> {
+        match self {
+            TypedReceipt::Legacy(_) => None,
+            TypedReceipt::EIP2930(_) => Some(EIP2930Transaction::TYPE),
+            TypedReceipt::EIP1559(_) => Some(EIP1559Transaction::TYPE),
+            TypedReceipt::EIP4844(_) => Some(EIP4844Transaction::TYPE),
+            TypedReceipt::EIP7702(_) => Some(EIP7702Transaction::TYPE),
+        }
+    }
+
+    /// Returns the EIP-2718 envelope of the receipt, as used in the receipts trie.
+    pub fn encoded_2718(&self) -> Vec<u8> {
+        let mut out = vec![];
+        out.extend(self.tx_type());
+        self.receipt().encode(&mut out);
+        out
+    }
+
+    /// Decodes a receipt from its EIP-2718 envelope.
+    pub fn decode_2718(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let tx_type = *buf.first().ok_or(DecodeError::InputTooShort)?;
+        if tx_type >= 0xc0 {
+            return Ok(TypedReceipt::Legacy(Decodable::decode(buf)?));
+        }
+
+        *buf = &buf[1..];
+        let receipt = Decodable::decode(buf)?;
+        match tx_type {
+            EIP2930Transaction::TYPE => Ok(TypedReceipt::EIP2930(receipt)),
+            EIP1559Transaction::TYPE => Ok(TypedReceipt::EIP1559(receipt)),
+            EIP4844Transaction::TYPE => Ok(TypedReceipt::EIP4844(receipt)),
+            EIP7702Transaction::TYPE => Ok(TypedReceipt::EIP7702(receipt)),
+            _ => Err(DecodeError::Custom("unknown receipt type")),
+        }
+    }
+}
+
+impl Encodable for TypedReceipt {
+    fn length(&self) -> usize {
+        let length = self.receipt().length();
+        match self {
+            TypedReceipt::Legacy(_) => length,
+            // the type byte and the string header
+            _ => 1 + length + open_fastrlp::length_of_length(1 + length),
+        }
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        let envelope = self.encoded_2718();
+        if !matches!(self, TypedReceipt::Legacy(_)) {
+            RlpHeader {
+                list: false,
+                payload_length: envelope.len(),
+            }
+            .encode(out);
+        }
+        out.put_slice(&envelope);
+    }
+}
+
+impl Decodable for TypedReceipt {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        if buf.first().is_some_and(|byte| *byte >= 0xc0) {
+            return Ok(TypedReceipt::Legacy(Decodable::decode(buf)?));
+        }
+
+        let envelope = &mut decode_string(buf)?;
+        let receipt = TypedReceipt::decode_2718(envelope)?;
+        if matches!(receipt, TypedReceipt::Legacy(_)) {
+            return Err(DecodeError::UnexpectedList);
+        }
+        ensure_consumed(envelope)?;
+        Ok(receipt)
+    }
+}
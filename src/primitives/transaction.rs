@@ -0,0 +1,817 @@
+use ethers_core::{
+    types::{
+        transaction::eip2930::AccessList, Address, Bytes, Signature, SignatureError, H256, U256,
+    },
+    utils::keccak256,
+};
+use open_fastrlp::{
+    BufMut, Decodable, DecodeError, Encodable, Header as RlpHeader, EMPTY_STRING_CODE,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{decode_list, decode_string, encode_list_payload, ensure_consumed, list_length};
+
+/// The recipient of a transaction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TransactionKind {
+    /// A call to the given address.
+    Call(Address),
+    /// A contract creation.
+    Create,
+}
+
+impl Encodable for TransactionKind {
+    fn length(&self) -> usize {
+        match self {
+            TransactionKind::Call(address) => address.length(),
+            TransactionKind::Create => 1,
+        }
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        match self {
+            TransactionKind::Call(address) => address.encode(out),
+            TransactionKind::Create => out.put_u8(EMPTY_STRING_CODE),
+        }
+    }
+}
+
+impl Decodable for TransactionKind {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        if buf.first() == Some(&EMPTY_STRING_CODE) {
+            *buf = &buf[1..];
+            Ok(TransactionKind::Create)
+        } else {
+            Ok(TransactionKind::Call(Address::decode(buf)?))
+        }
+    }
+}
+
+/// A legacy transaction, optionally replay protected by
+/// [EIP-155](https://eips.ethereum.org/EIPS/eip-155).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LegacyTransaction {
+    pub nonce: U256,
+    pub gas_price: U256,
+    pub gas_limit: U256,
+    pub kind: TransactionKind,
+    pub value: U256,
+    pub input: Bytes,
+    pub signature: Signature,
+}
+
+impl LegacyTransaction {
+    /// Returns the chain id of an EIP-155 transaction.
+    pub fn chain_id(&self) -> Option<u64> {
+        (self.signature.v >= 35).then(|| (self.signature.v - 35) / 2)
+    }
+
+    /// Returns the hash that is signed by the sender.
+    pub fn sighash(&self) -> H256 {
+        let mut payload = self.encode_fields();
+        if let Some(chain_id) = self.chain_id() {
+            chain_id.encode(&mut payload);
+            0u8.encode(&mut payload);
+            0u8.encode(&mut payload);
+        }
+        list_hash(None, &payload)
+    }
+
+    fn encode_fields(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        self.nonce.encode(&mut payload);
+        self.gas_price.encode(&mut payload);
+        self.gas_limit.encode(&mut payload);
+        self.kind.encode(&mut payload);
+        self.value.encode(&mut payload);
+        self.input.encode(&mut payload);
+        payload
+    }
+
+    fn encode_payload(&self) -> Vec<u8> {
+        let mut payload = self.encode_fields();
+        self.signature.encode(&mut payload);
+        payload
+    }
+}
+
+impl Encodable for LegacyTransaction {
+    fn length(&self) -> usize {
+        list_length(self.encode_payload().len())
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_list_payload(&self.encode_payload(), out)
+    }
+}
+
+impl Decodable for LegacyTransaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let payload = &mut decode_list(buf)?;
+        let tx = LegacyTransaction {
+            nonce: Decodable::decode(payload)?,
+            gas_price: Decodable::decode(payload)?,
+            gas_limit: Decodable::decode(payload)?,
+            kind: Decodable::decode(payload)?,
+            value: Decodable::decode(payload)?,
+            input: Decodable::decode(payload)?,
+            signature: Decodable::decode(payload)?,
+        };
+        ensure_consumed(payload)?;
+        Ok(tx)
+    }
+}
+
+/// A transaction with an access list, added in Berlin
+/// ([EIP-2930](https://eips.ethereum.org/EIPS/eip-2930)).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EIP2930Transaction {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub gas_price: U256,
+    pub gas_limit: U256,
+    pub kind: TransactionKind,
+    pub value: U256,
+    pub input: Bytes,
+    pub access_list: AccessList,
+    pub odd_y_parity: bool,
+    pub r: H256,
+    pub s: H256,
+}
+
+impl EIP2930Transaction {
+    /// The EIP-2718 type of the transaction.
+    pub const TYPE: u8 = 0x01;
+
+    /// Returns the hash that is signed by the sender.
+    pub fn sighash(&self) -> H256 {
+        list_hash(Some(Self::TYPE), &self.encode_fields())
+    }
+
+    fn encode_fields(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        self.chain_id.encode(&mut payload);
+        self.nonce.encode(&mut payload);
+        self.gas_price.encode(&mut payload);
+        self.gas_limit.encode(&mut payload);
+        self.kind.encode(&mut payload);
+        self.value.encode(&mut payload);
+        self.input.encode(&mut payload);
+        self.access_list.encode(&mut payload);
+        payload
+    }
+
+    fn encode_payload(&self) -> Vec<u8> {
+        let mut payload = self.encode_fields();
+        encode_signature(self.odd_y_parity, self.r, self.s, &mut payload);
+        payload
+    }
+}
+
+impl Encodable for EIP2930Transaction {
+    fn length(&self) -> usize {
+        list_length(self.encode_payload().len())
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_list_payload(&self.encode_payload(), out)
+    }
+}
+
+impl Decodable for EIP2930Transaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let payload = &mut decode_list(buf)?;
+        let tx = EIP2930Transaction {
+            chain_id: Decodable::decode(payload)?,
+            nonce: Decodable::decode(payload)?,
+            gas_price: Decodable::decode(payload)?,
+            gas_limit: Decodable::decode(payload)?,
+            kind: Decodable::decode(payload)?,
+            value: Decodable::decode(payload)?,
+            input: Decodable::decode(payload)?,
+            access_list: Decodable::decode(payload)?,
+            odd_y_parity: Decodable::decode(payload)?,
+            r: decode_h256(payload)?,
+            s: decode_h256(payload)?,
+        };
+        ensure_consumed(payload)?;
+        Ok(tx)
+    }
+}
+
+/// A transaction with a priority fee, added in London
+/// ([EIP-1559](https://eips.ethereum.org/EIPS/eip-1559)).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EIP1559Transaction {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+    pub kind: TransactionKind,
+    pub value: U256,
+    pub input: Bytes,
+    pub access_list: AccessList,
+    pub odd_y_parity: bool,
+    pub r: H256,
+    pub s: H256,
+}
+
+impl EIP1559Transaction {
+    /// The EIP-2718 type of the transaction.
+    pub const TYPE: u8 = 0x02;
+
+    /// Returns the hash that is signed by the sender.
+    pub fn sighash(&self) -> H256 {
+        list_hash(Some(Self::TYPE), &self.encode_fields())
+    }
+
+    fn encode_fields(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        self.chain_id.encode(&mut payload);
+        self.nonce.encode(&mut payload);
+        self.max_priority_fee_per_gas.encode(&mut payload);
+        self.max_fee_per_gas.encode(&mut payload);
+        self.gas_limit.encode(&mut payload);
+        self.kind.encode(&mut payload);
+        self.value.encode(&mut payload);
+        self.input.encode(&mut payload);
+        self.access_list.encode(&mut payload);
+        payload
+    }
+
+    fn encode_payload(&self) -> Vec<u8> {
+        let mut payload = self.encode_fields();
+        encode_signature(self.odd_y_parity, self.r, self.s, &mut payload);
+        payload
+    }
+}
+
+impl Encodable for EIP1559Transaction {
+    fn length(&self) -> usize {
+        list_length(self.encode_payload().len())
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_list_payload(&self.encode_payload(), out)
+    }
+}
+
+impl Decodable for EIP1559Transaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let payload = &mut decode_list(buf)?;
+        let tx = EIP1559Transaction {
+            chain_id: Decodable::decode(payload)?,
+            nonce: Decodable::decode(payload)?,
+            max_priority_fee_per_gas: Decodable::decode(payload)?,
+            max_fee_per_gas: Decodable::decode(payload)?,
+            gas_limit: Decodable::decode(payload)?,
+            kind: Decodable::decode(payload)?,
+            value: Decodable::decode(payload)?,
+            input: Decodable::decode(payload)?,
+            access_list: Decodable::decode(payload)?,
+            odd_y_parity: Decodable::decode(payload)?,
+            r: decode_h256(payload)?,
+            s: decode_h256(payload)?,
+        };
+        ensure_consumed(payload)?;
+        Ok(tx)
+    }
+}
+
+/// A transaction carrying blobs, added in Cancun
+/// ([EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)).
+///
+/// This is the form used in blocks and [`Transactions`](crate::Transactions), which only
+/// contains the versioned hashes of the blobs. The blobs themselves are only sent along with the
+/// transaction in [`PooledTransactions`](crate::PooledTransactions), see
+/// [`PooledTransaction`](crate::PooledTransaction).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EIP4844Transaction {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+    /// The recipient of the transaction. Blob transactions cannot create contracts.
+    pub to: Address,
+    pub value: U256,
+    pub input: Bytes,
+    pub access_list: AccessList,
+    pub max_fee_per_blob_gas: U256,
+    pub blob_versioned_hashes: Vec<H256>,
+    pub odd_y_parity: bool,
+    pub r: H256,
+    pub s: H256,
+}
+
+impl EIP4844Transaction {
+    /// The EIP-2718 type of the transaction.
+    pub const TYPE: u8 = 0x03;
+
+    /// Returns the hash that is signed by the sender.
+    pub fn sighash(&self) -> H256 {
+        list_hash(Some(Self::TYPE), &self.encode_fields())
+    }
+
+    fn encode_fields(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        self.chain_id.encode(&mut payload);
+        self.nonce.encode(&mut payload);
+        self.max_priority_fee_per_gas.encode(&mut payload);
+        self.max_fee_per_gas.encode(&mut payload);
+        self.gas_limit.encode(&mut payload);
+        self.to.encode(&mut payload);
+        self.value.encode(&mut payload);
+        self.input.encode(&mut payload);
+        self.access_list.encode(&mut payload);
+        self.max_fee_per_blob_gas.encode(&mut payload);
+        self.blob_versioned_hashes.encode(&mut payload);
+        payload
+    }
+
+    fn encode_payload(&self) -> Vec<u8> {
+        let mut payload = self.encode_fields();
+        encode_signature(self.odd_y_parity, self.r, self.s, &mut payload);
+        payload
+    }
+}
+
+impl Encodable for EIP4844Transaction {
+    fn length(&self) -> usize {
+        list_length(self.encode_payload().len())
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_list_payload(&self.encode_payload(), out)
+    }
+}
+
+impl Decodable for EIP4844Transaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let payload = &mut decode_list(buf)?;
+        let tx = EIP4844Transaction {
+            chain_id: Decodable::decode(payload)?,
+            nonce: Decodable::decode(payload)?,
+            max_priority_fee_per_gas: Decodable::decode(payload)?,
+            max_fee_per_gas: Decodable::decode(payload)?,
+            gas_limit: Decodable::decode(payload)?,
+            to: Decodable::decode(payload)?,
+            value: Decodable::decode(payload)?,
+            input: Decodable::decode(payload)?,
+            access_list: Decodable::decode(payload)?,
+            max_fee_per_blob_gas: Decodable::decode(payload)?,
+            blob_versioned_hashes: Decodable::decode(payload)?,
+            odd_y_parity: Decodable::decode(payload)?,
+            r: decode_h256(payload)?,
+            s: decode_h256(payload)?,
+        };
+        ensure_consumed(payload)?;
+        Ok(tx)
+    }
+}
+
+/// A transaction which sets the code of accounts, added in Prague
+/// ([EIP-7702](https://eips.ethereum.org/EIPS/eip-7702)).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EIP7702Transaction {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+    /// The recipient of the transaction. Set code transactions cannot create contracts.
+    pub to: Address,
+    pub value: U256,
+    pub input: Bytes,
+    pub access_list: AccessList,
+    pub authorization_list: Vec<SignedAuthorization>,
+    pub odd_y_parity: bool,
+    pub r: H256,
+    pub s: H256,
+}
+
+impl EIP7702Transaction {
+    /// The EIP-2718 type of the transaction.
+    pub const TYPE: u8 = 0x04;
+
+    /// Returns the hash that is signed by the sender.
+    pub fn sighash(&self) -> H256 {
+        list_hash(Some(Self::TYPE), &self.encode_fields())
+    }
+
+    fn encode_fields(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        self.chain_id.encode(&mut payload);
+        self.nonce.encode(&mut payload);
+        self.max_priority_fee_per_gas.encode(&mut payload);
+        self.max_fee_per_gas.encode(&mut payload);
+        self.gas_limit.encode(&mut payload);
+        self.to.encode(&mut payload);
+        self.value.encode(&mut payload);
+        self.input.encode(&mut payload);
+        self.access_list.encode(&mut payload);
+        self.authorization_list.encode(&mut payload);
+        payload
+    }
+
+    fn encode_payload(&self) -> Vec<u8> {
+        let mut payload = self.encode_fields();
+        encode_signature(self.odd_y_parity, self.r, self.s, &mut payload);
+        payload
+    }
+}
+
+impl Encodable for EIP7702Transaction {
+    fn length(&self) -> usize {
+        list_length(self.encode_payload().len())
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_list_payload(&self.encode_payload(), out)
+    }
+}
+
+impl Decodable for EIP7702Transaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let payload = &mut decode_list(buf)?;
+        let tx = EIP7702Transaction {
+            chain_id: Decodable::decode(payload)?,
+            nonce: Decodable::decode(payload)?,
+            max_priority_fee_per_gas: Decodable::decode(payload)?,
+            max_fee_per_gas: Decodable::decode(payload)?,
+            gas_limit: Decodable::decode(payload)?,
+            to: Decodable::decode(payload)?,
+            value: Decodable::decode(payload)?,
+            input: Decodable::decode(payload)?,
+            access_list: Decodable::decode(payload)?,
+            authorization_list: Decodable::decode(payload)?,
+            odd_y_parity: Decodable::decode(payload)?,
+            r: decode_h256(payload)?,
+            s: decode_h256(payload)?,
+        };
+        ensure_consumed(payload)?;
+        Ok(tx)
+    }
+}
+
+/// Half of the order of the secp256k1 curve, which is the largest valid `s` value of a signature
+/// since [EIP-2](https://eips.ethereum.org/EIPS/eip-2).
+const SECP256K1N_HALF: U256 = U256([
+    0xdfe92f46681b20a0,
+    0x5d576e7357a4501d,
+    0xffffffffffffffff,
+    0x7fffffffffffffff,
+]);
+
+/// Checks the `r` and `s` values of a signature as geth does before recovering: both must be
+/// non-zero, and `s` must be in the lower half of the curve order.
+fn check_signature_values(r: U256, s: U256) -> Result<(), SignatureError> {
+    if r.is_zero() || s.is_zero() || s > SECP256K1N_HALF {
+        return Err(SignatureError::RecoveryError);
+    }
+    Ok(())
+}
+
+/// An authorization for an account to delegate its code to `address`, signed by the account
+/// itself, the authority.
+///
+/// The signature is not checked when decoding, since invalid authorizations are skipped rather
+/// than invalidating the transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SignedAuthorization {
+    /// The chain the authorization is valid on, or zero for any chain.
+    pub chain_id: U256,
+    /// The address whose code is delegated to.
+    pub address: Address,
+    /// The nonce of the authority.
+    pub nonce: u64,
+    pub y_parity: u8,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl SignedAuthorization {
+    /// The prefix of the message signed by the authority.
+    pub const MAGIC: u8 = 0x05;
+
+    /// Returns the hash that is signed by the authority.
+    pub fn sighash(&self) -> H256 {
+        let mut payload = vec![];
+        self.chain_id.encode(&mut payload);
+        self.address.encode(&mut payload);
+        self.nonce.encode(&mut payload);
+        list_hash(Some(Self::MAGIC), &payload)
+    }
+
+    /// Recovers the address of the authority from the signature.
+    pub fn recover_authority(&self) -> Result<Address, SignatureError> {
+        if self.y_parity > 1 {
+            return Err(SignatureError::RecoveryError);
+        }
+        check_signature_values(self.r, self.s)?;
+        let signature = Signature {
+            r: self.r,
+            s: self.s,
+            v: self.y_parity.into(),
+        };
+        signature.recover(self.sighash())
+    }
+
+    fn encode_payload(&self) -> Vec<u8> {
+        let mut payload = vec![];
+        self.chain_id.encode(&mut payload);
+        self.address.encode(&mut payload);
+        self.nonce.encode(&mut payload);
+        self.y_parity.encode(&mut payload);
+        self.r.encode(&mut payload);
+        self.s.encode(&mut payload);
+        payload
+    }
+}
+
+impl Encodable for SignedAuthorization {
+    fn length(&self) -> usize {
+        list_length(self.encode_payload().len())
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_list_payload(&self.encode_payload(), out)
+    }
+}
+
+impl Decodable for SignedAuthorization {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let payload = &mut decode_list(buf)?;
+        let authorization = SignedAuthorization {
+            chain_id: Decodable::decode(payload)?,
+            address: Decodable::decode(payload)?,
+            nonce: Decodable::decode(payload)?,
+            y_parity: Decodable::decode(payload)?,
+            r: Decodable::decode(payload)?,
+            s: Decodable::decode(payload)?,
+        };
+        ensure_consumed(payload)?;
+        Ok(authorization)
+    }
+}
+
+/// A signed transaction of any type.
+///
+/// On the wire, legacy transactions are encoded as an RLP list, while typed transactions are
+/// encoded as an RLP string containing their
+/// [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718) envelope.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TypedTransaction {
+    Legacy(LegacyTransaction),
+    EIP2930(EIP2930Transaction),
+    EIP1559(EIP1559Transaction),
+    EIP4844(EIP4844Transaction),
+    EIP7702(EIP7702Transaction),
+}
+
+impl TypedTransaction {
+    /// Returns the EIP-2718 envelope of the transaction, which is the RLP list for legacy
+    /// transactions, and the type followed by the RLP list for typed transactions.
+    pub fn encoded_2718(&self) -> Vec<u8> {
+        let mut out = vec![];
+        match self {
+            TypedTransaction::Legacy(tx) => tx.encode(&mut out),
+            TypedTransaction::EIP2930(tx) => {
+                out.push(EIP2930Transaction::TYPE);
+                tx.encode(&mut out);
+            }
+            TypedTransaction::EIP1559(tx) => {
+                out.push(EIP1559Transaction::TYPE);
+                tx.encode(&mut out);
+            }
+            TypedTransaction::EIP4844(tx) => {
+                out.push(EIP4844Transaction::TYPE);
+                tx.encode(&mut out);
+            }
+            TypedTransaction::EIP7702(tx) => {
+                out.push(EIP7702Transaction::TYPE);
+                tx.encode(&mut out);
+            }
+        }
+        out
+    }
+
+    /// Decodes a transaction from its EIP-2718 envelope.
+    pub fn decode_2718(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let tx_type = *buf.first().ok_or(DecodeError::InputTooShort)?;
+        if tx_type >= 0xc0 {
+            return Ok(TypedTransaction::Legacy(Decodable::decode(buf)?));
+        }
+
+        *buf = &buf[1..];
+        match tx_type {
+            EIP2930Transaction::TYPE => Ok(TypedTransaction::EIP2930(Decodable::decode(buf)?)),
+            EIP1559Transaction::TYPE => Ok(TypedTransaction::EIP1559(Decodable::decode(buf)?)),
+            EIP4844Transaction::TYPE => Ok(TypedTransaction::EIP4844(Decodable::decode(buf)?)),
+            EIP7702Transaction::TYPE => Ok(TypedTransaction::EIP7702(Decodable::decode(buf)?)),
+            _ => Err(DecodeError::Custom("unknown transaction type")),
+        }
+    }
+
+    /// Returns the hash of the transaction.
+    pub fn hash(&self) -> H256 {
+        H256(keccak256(self.encoded_2718()))
+    }
+
+    /// Returns the hash that is signed by the sender.
+    pub fn sighash(&self) -> H256 {
+        match self {
+            TypedTransaction::Legacy(tx) => tx.sighash(),
+            TypedTransaction::EIP2930(tx) => tx.sighash(),
+            TypedTransaction::EIP1559(tx) => tx.sighash(),
+            TypedTransaction::EIP4844(tx) => tx.sighash(),
+            TypedTransaction::EIP7702(tx) => tx.sighash(),
+        }
+    }
+
+    /// Returns the signature of the transaction.
+    pub fn signature(&self) -> Signature {
+        let (odd_y_parity, r, s) = match self {
+            TypedTransaction::Legacy(tx) => return tx.signature,
+            TypedTransaction::EIP2930(tx) => (tx.odd_y_parity, tx.r, tx.s),
+            TypedTransaction::EIP1559(tx) => (tx.odd_y_parity, tx.r, tx.s),
+            TypedTransaction::EIP4844(tx) => (tx.odd_y_parity, tx.r, tx.s),
+            TypedTransaction::EIP7702(tx) => (tx.odd_y_parity, tx.r, tx.s),
+        };
+        Signature {
+            r: U256::from_big_endian(r.as_bytes()),
+            s: U256::from_big_endian(s.as_bytes()),
+            v: odd_y_parity.into(),
+        }
+    }
+
+    /// Recovers the address of the sender from the signature.
+    ///
+    /// Signatures with a high `s` value, a zero `r` or `s`, or a legacy `v` other than 27, 28 or
+    /// an [EIP-155](https://eips.ethereum.org/EIPS/eip-155) value are rejected.
+    pub fn recover(&self) -> Result<Address, SignatureError> {
+        let signature = self.signature();
+        if matches!(self, TypedTransaction::Legacy(_)) && !matches!(signature.v, 27 | 28 | 35..) {
+            return Err(SignatureError::RecoveryError);
+        }
+        check_signature_values(signature.r, signature.s)?;
+        signature.recover(self.sighash())
+    }
+}
+
+impl Encodable for TypedTransaction {
+    fn length(&self) -> usize {
+        let length = match self {
+            TypedTransaction::Legacy(tx) => return tx.length(),
+            TypedTransaction::EIP2930(tx) => tx.length(),
+            TypedTransaction::EIP1559(tx) => tx.length(),
+            TypedTransaction::EIP4844(tx) => tx.length(),
+            TypedTransaction::EIP7702(tx) => tx.length(),
+        };
+        // the type byte and the string header
+        1 + length + open_fastrlp::length_of_length(1 + length)
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        let envelope = self.encoded_2718();
+        if !matches!(self, TypedTransaction::Legacy(_)) {
+            RlpHeader {
+                list: false,
+                payload_length: envelope.len(),
+            }
+            .encode(out);
+        }
+        out.put_slice(&envelope);
+    }
+}
+
+impl Decodable for TypedTransaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        if buf.first().is_some_and(|byte| *byte >= 0xc0) {
+            return Ok(TypedTransaction::Legacy(Decodable::decode(buf)?));
+        }
+
+        let envelope = &mut decode_string(buf)?;
+        let tx = TypedTransaction::decode_2718(envelope)?;
+        if matches!(tx, TypedTransaction::Legacy(_)) {
+            return Err(DecodeError::UnexpectedList);
+        }
+        ensure_consumed(envelope)?;
+        Ok(tx)
+    }
+}
+
+/// Hashes the list with the given payload, prefixed by the transaction type if any.
+fn list_hash(tx_type: Option<u8>, payload: &[u8]) -> H256 {
+    let mut out = vec![];
+    out.extend(tx_type);
+    encode_list_payload(payload, &mut out);
+    H256(keccak256(out))
+}
+
+/// Encodes the signature of a typed transaction, where `r` and `s` are encoded as integers.
+fn encode_signature(odd_y_parity: bool, r: H256, s: H256, out: &mut Vec<u8>) {
+    odd_y_parity.encode(out);
+    U256::from_big_endian(r.as_bytes()).encode(out);
+    U256::from_big_endian(s.as_bytes()).encode(out);
+}
+
+fn decode_h256(buf: &mut &[u8]) -> Result<H256, DecodeError> {
+    let value = U256::decode(buf)?;
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    Ok(H256(bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use ethers_core::{types::U256, utils::secret_key_to_address};
+    use hex_literal::hex;
+    use k256::ecdsa::SigningKey;
+    use open_fastrlp::{Decodable, Encodable};
+
+    use super::{SignedAuthorization, SECP256K1N_HALF};
+    use crate::{EIP7702Transaction, Transactions, TypedTransaction};
+
+    fn sign_authorization(key: &SigningKey, nonce: u64) -> SignedAuthorization {
+        let mut authorization = SignedAuthorization {
+            chain_id: 1.into(),
+            address: [0x77; 20].into(),
+            nonce,
+            y_parity: 0,
+            r: U256::zero(),
+            s: U256::zero(),
+        };
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(authorization.sighash().as_bytes())
+            .unwrap();
+        authorization.y_parity = recovery_id.to_byte();
+        authorization.r = U256::from_big_endian(&signature.r().to_bytes());
+        authorization.s = U256::from_big_endian(&signature.s().to_bytes());
+        authorization
+    }
+
+    #[test]
+    fn set_code_transaction() {
+        let key = SigningKey::from_slice(&hex!(
+            "4646464646464646464646464646464646464646464646464646464646464646"
+        ))
+        .unwrap();
+        let authority = secret_key_to_address(&key);
+        let authorization = sign_authorization(&key, 3);
+        assert_eq!(authorization.recover_authority().unwrap(), authority);
+
+        let tx = TypedTransaction::EIP7702(EIP7702Transaction {
+            chain_id: 1,
+            nonce: 2u64.into(),
+            max_priority_fee_per_gas: 1u64.into(),
+            max_fee_per_gas: 2u64.into(),
+            gas_limit: 50_000u64.into(),
+            to: authority,
+            value: U256::zero(),
+            input: Default::default(),
+            access_list: Default::default(),
+            authorization_list: vec![authorization.clone()],
+            odd_y_parity: false,
+            r: [0x22; 32].into(),
+            s: [0x33; 32].into(),
+        });
+        let txs: Transactions = Transactions(vec![tx.clone()]);
+        let mut encoded = vec![];
+        txs.encode(&mut encoded);
+        assert_eq!(encoded.len(), txs.length());
+        assert_eq!(Transactions::decode(&mut &encoded[..]).unwrap(), txs);
+        assert_eq!(
+            TypedTransaction::decode_2718(&mut &tx.encoded_2718()[..]).unwrap(),
+            tx
+        );
+
+        // a tampered authorization recovers to another authority
+        let tampered = SignedAuthorization {
+            nonce: 4,
+            ..authorization.clone()
+        };
+        assert_ne!(tampered.recover_authority().unwrap(), authority);
+
+        // invalid signatures are rejected when recovering rather than when decoding
+        for invalid in [
+            SignedAuthorization {
+                y_parity: 2,
+                ..authorization.clone()
+            },
+            SignedAuthorization {
+                s: SECP256K1N_HALF + 1,
+                ..authorization.clone()
+            },
+        ] {
+            let mut encoded = vec![];
+            invalid.encode(&mut encoded);
+            assert_eq!(
+                SignedAuthorization::decode(&mut &encoded[..]).unwrap(),
+                invalid
+            );
+            assert!(invalid.recover_authority().is_err());
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use ethers_core::types::Address;
+use open_fastrlp::{RlpDecodable, RlpEncodable};
+use serde::{Deserialize, Serialize};
+
+/// A withdrawal from the beacon chain to the execution layer, added in Shanghai (EIP-4895).
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, RlpEncodable, RlpDecodable,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    /// The monotonically increasing index of the withdrawal.
+    pub index: u64,
+    /// The index of the validator the withdrawal is for.
+    pub validator_index: u64,
+    /// The address the withdrawn ether is sent to.
+    pub address: Address,
+    /// The withdrawn amount, in gwei.
+    pub amount: u64,
+}
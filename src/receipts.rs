@@ -1,5 +1,11 @@
-use anvil_core::eth::receipt::TypedReceipt;
+use ethers_core::{
+    types::{Bloom, H256},
+    utils::keccak256,
+};
 use open_fastrlp::{RlpDecodableWrapper, RlpEncodableWrapper};
+use thiserror::Error;
+
+use crate::{trie, EthereumPrimitives, Header, NetworkPrimitives, TypedReceipt};
 
 /// A request for transaction receipts from the given block hashes.
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper)]
@@ -11,17 +17,120 @@ pub struct GetReceipts(
 /// The response to [`GetReceipts`], containing receipt lists that correspond to each block
 /// requested.
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper)]
-pub struct Receipts(
+pub struct Receipts<N: NetworkPrimitives = EthereumPrimitives>(
     /// Each receipt hash should correspond to a block hash in the request.
-    pub Vec<Vec<TypedReceipt>>,
+    pub Vec<Vec<N::Receipt>>,
 );
 
+impl Receipts {
+    /// Check each receipt list against the header of the block it was requested for, in request
+    /// order. If `check_bloom` is set, the logs bloom of the header is checked against the logs
+    /// of the receipts as well.
+    ///
+    /// The response may contain fewer receipt lists than headers, in which case only the lists
+    /// which were returned are checked.
+    ///
+    /// # Errors
+    /// Returns a [`ReceiptsError`] for the first receipt list which does not match its header.
+    pub fn validate(&self, headers: &[Header], check_bloom: bool) -> Result<(), ReceiptsError> {
+        if self.0.len() > headers.len() {
+            return Err(ReceiptsError::TooManyReceipts {
+                requested: headers.len(),
+                received: self.0.len(),
+            });
+        }
+
+        for (index, (receipts, header)) in self.0.iter().zip(headers).enumerate() {
+            let receipts_root = trie::ordered_trie_root(receipts.iter().map(trie::envelope));
+            if receipts_root != header.receipts_root {
+                return Err(ReceiptsError::ReceiptsRoot {
+                    index,
+                    expected: header.receipts_root,
+                    got: receipts_root,
+                });
+            }
+
+            if check_bloom {
+                let logs_bloom = logs_bloom(receipts);
+                if logs_bloom != header.logs_bloom {
+                    return Err(ReceiptsError::LogsBloom {
+                        index,
+                        expected: Box::new(header.logs_bloom),
+                        got: Box::new(logs_bloom),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Computes the bloom filter of the logs in the given receipts, as found in the block header.
+fn logs_bloom(receipts: &[TypedReceipt]) -> Bloom {
+    let mut bloom = Bloom::zero();
+    for receipt in receipts {
+        for log in &receipt.receipt().logs {
+            accrue(&mut bloom, log.address.as_bytes());
+            for topic in &log.topics {
+                accrue(&mut bloom, topic.as_bytes());
+            }
+        }
+    }
+    bloom
+}
+
+/// Adds an address or topic to the bloom filter, by setting the three bits given by the low 11
+/// bits of the first three pairs of bytes of its hash.
+fn accrue(bloom: &mut Bloom, input: &[u8]) {
+    let hash = keccak256(input);
+    for pair in hash[..6].chunks(2) {
+        let bit = (usize::from(pair[0]) << 8 | usize::from(pair[1])) & 0x7ff;
+        bloom.0[255 - bit / 8] |= 1 << (bit % 8);
+    }
+}
+
+/// Ways in which a [`Receipts`] response can fail to match the headers of the requested blocks.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ReceiptsError {
+    /// The response contains more receipt lists than were requested.
+    #[error("received {received} receipt lists, but only {requested} were requested")]
+    TooManyReceipts {
+        /// The number of requested receipt lists.
+        requested: usize,
+        /// The number of receipt lists in the response.
+        received: usize,
+    },
+    /// The receipts root of a receipt list does not match its header.
+    #[error("receipts root mismatch for receipt list {index}: expected {expected:?}, got {got:?}")]
+    ReceiptsRoot {
+        /// The index of the receipt list in the response.
+        index: usize,
+        /// The receipts root in the header.
+        expected: H256,
+        /// The receipts root computed from the receipt list.
+        got: H256,
+    },
+    /// The logs bloom of a receipt list does not match its header.
+    #[error("logs bloom mismatch for receipt list {index}")]
+    LogsBloom {
+        /// The index of the receipt list in the response.
+        index: usize,
+        /// The logs bloom in the header.
+        expected: Box<Bloom>,
+        /// The logs bloom computed from the logs of the receipt list.
+        got: Box<Bloom>,
+    },
+}
+
 #[cfg(test)]
 mod test {
-    use anvil_core::eth::receipt::{EIP658Receipt, Log, TypedReceipt};
+    use ethers_core::types::{Bloom, H256, U256};
     use hex_literal::hex;
 
-    use crate::{message::RequestPair, GetReceipts, Receipts};
+    use crate::{
+        message::RequestPair, EIP658Receipt, GetReceipts, Header, Log, Receipts, ReceiptsError,
+        TypedReceipt,
+    };
     use open_fastrlp::{Decodable, Encodable};
 
     #[test]
@@ -119,4 +228,76 @@ mod test {
             }
         );
     }
+
+    fn header(receipts_root: H256, logs_bloom: Bloom) -> Header {
+        Header {
+            parent_hash: Default::default(),
+            ommers_hash: Default::default(),
+            beneficiary: Default::default(),
+            state_root: Default::default(),
+            transactions_root: Default::default(),
+            receipts_root,
+            logs_bloom,
+            difficulty: U256::zero(),
+            number: U256::one(),
+            gas_limit: 30_000_000u64.into(),
+            gas_used: U256::one(),
+            timestamp: 0,
+            extra_data: Default::default(),
+            mix_hash: Default::default(),
+            nonce: Default::default(),
+            base_fee_per_gas: None,
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            requests_hash: None,
+        }
+    }
+
+    #[test]
+    fn validate_receipts() {
+        // the receipts from the EIP-2481 test vector, whose logs bloom is left empty
+        let data = hex!("f90172820457f9016cf90169f901668001b9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f85ff85d940000000000000000000000000000000000000011f842a0000000000000000000000000000000000000000000000000000000000000deada0000000000000000000000000000000000000000000000000000000000000beef830100ff");
+        let receipts = RequestPair::<Receipts>::decode(&mut &data[..])
+            .unwrap()
+            .message;
+        let receipts_root = H256(hex!(
+            "9ad801074a6dc0c2de8e50802603f38d721cb630ab9ae9f0b6eb8dbc7479911a"
+        ));
+        let logs_bloom = Bloom(hex!("00000000000010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000014000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000004000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000"));
+
+        let headers = [header(receipts_root, logs_bloom)];
+        assert_eq!(receipts.validate(&headers, true), Ok(()));
+        assert_eq!(Receipts(vec![]).validate(&headers, true), Ok(()));
+        assert_eq!(
+            Receipts(vec![vec![], vec![]]).validate(&headers, false),
+            Err(ReceiptsError::TooManyReceipts {
+                requested: 1,
+                received: 2
+            })
+        );
+
+        // the header bloom is only checked when requested
+        let headers = [header(receipts_root, Bloom::zero())];
+        assert_eq!(receipts.validate(&headers, false), Ok(()));
+        assert_eq!(
+            receipts.validate(&headers, true),
+            Err(ReceiptsError::LogsBloom {
+                index: 0,
+                expected: Box::new(Bloom::zero()),
+                got: Box::new(logs_bloom)
+            })
+        );
+
+        // receipts from another block
+        assert_eq!(
+            Receipts(vec![vec![]]).validate(&headers, false),
+            Err(ReceiptsError::ReceiptsRoot {
+                index: 0,
+                expected: receipts_root,
+                got: crate::trie::EMPTY_ROOT
+            })
+        );
+    }
 }
@@ -1,6 +1,7 @@
 use crate::{
-    GetBlockBodies, GetBlockHeaders, GetNodeData, GetPooledTransactions, GetReceipts, NewBlock,
-    NewBlockHashes, NewPooledTransactionHashes, RequestPair, Status, Transactions,
+    EthereumPrimitives, GetBlockBodies, GetBlockHeaders, GetNodeData, GetPooledTransactions,
+    GetReceipts, NetworkPrimitives, NewBlock, NewBlockHashes, NewPooledTransactionHashes,
+    RequestPair, Status, Transactions,
 };
 
 // This type is analogous to the `zebra_network::Request` type.
@@ -12,7 +13,7 @@ use crate::{
 /// recommended!) to match on the expected responses and treat the others as
 /// `unreachable!()`, since their return indicates a bug in the network code.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub enum Request {
+pub enum Request<N: NetworkPrimitives = EthereumPrimitives> {
     /// The [`Status`](super::Status) message sent as part of the eth protocol handshake.
     ///
     /// # Response
@@ -33,14 +34,14 @@ pub enum Request {
     /// # Response
     ///
     /// Return [`Response::Nil`](super::Response::Nil).
-    NewBlock(Box<NewBlock>),
+    NewBlock(Box<NewBlock<N>>),
 
     /// A list of observed transactions to be broadcasted.
     ///
     /// # Response
     ///
     /// Returns [`Response::Nil`](super::Response::Nil).
-    Transactions(Transactions),
+    Transactions(Transactions<N>),
 
     /// A list of observed transaction hashes to be broadcasted.
     ///
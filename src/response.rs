@@ -1,11 +1,12 @@
 use crate::{
-    BlockBodies, BlockHeaders, NodeData, PooledTransactions, Receipts, RequestPair, Status,
+    BlockBodies, BlockHeaders, EthereumPrimitives, NetworkPrimitives, NodeData, PooledTransactions,
+    Receipts, RequestPair, Status,
 };
 
 // This type is analogous to the `zebra_network::Response` type.
 /// An ethereum network response for version 66.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub enum Response {
+pub enum Response<N: NetworkPrimitives = EthereumPrimitives> {
     /// The request does not have a response.
     Nil,
 
@@ -13,17 +14,17 @@ pub enum Response {
     Status(Status),
 
     /// The response to a [`Request::GetBlockHeaders`](super::Request::GetBlockHeaders) request.
-    BlockHeaders(RequestPair<BlockHeaders>),
+    BlockHeaders(RequestPair<BlockHeaders<N>>),
 
     /// The response to a [`Request::GetBlockBodies`](super::Request::GetBlockBodies) request.
-    BlockBodies(RequestPair<BlockBodies>),
+    BlockBodies(RequestPair<BlockBodies<N>>),
 
     /// The response to a [`Request::GetPooledTransactions`](super::Request::GetPooledTransactions) request.
-    PooledTransactions(RequestPair<PooledTransactions>),
+    PooledTransactions(RequestPair<PooledTransactions<N>>),
 
     /// The response to a [`Request::GetNodeData`](super::Request::GetNodeData) request.
     NodeData(RequestPair<NodeData>),
 
     /// The response to a [`Request::GetReceipts`](super::Request::GetReceipts) request.
-    Receipts(RequestPair<Receipts>),
+    Receipts(RequestPair<Receipts<N>>),
 }
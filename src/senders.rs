@@ -0,0 +1,154 @@
+use ethers_core::types::Address;
+use thiserror::Error;
+
+use crate::{NewBlock, PooledTransactions, Transactions, TypedTransaction};
+
+/// The signature of a transaction in a batch could not be recovered to a sender.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[error("invalid signature for transaction {index}")]
+pub struct InvalidSignature {
+    /// The index of the transaction in the batch.
+    pub index: usize,
+}
+
+/// Recovers the sender of each transaction, in order.
+pub fn recover_senders(txs: &[TypedTransaction]) -> Vec<Result<Address, InvalidSignature>> {
+    txs.iter()
+        .enumerate()
+        .map(|(index, tx)| recover_sender(index, tx))
+        .collect()
+}
+
+/// Recovers the sender of each transaction in parallel, in order.
+///
+/// This is worth it for large batches, such as the transactions of a block.
+#[cfg(feature = "rayon")]
+pub fn par_recover_senders(txs: &[TypedTransaction]) -> Vec<Result<Address, InvalidSignature>> {
+    use rayon::prelude::*;
+
+    txs.par_iter()
+        .enumerate()
+        .map(|(index, tx)| recover_sender(index, tx))
+        .collect()
+}
+
+fn recover_sender(index: usize, tx: &TypedTransaction) -> Result<Address, InvalidSignature> {
+    tx.recover().map_err(|_| InvalidSignature { index })
+}
+
+impl Transactions {
+    /// Recovers the sender of each transaction, in order.
+    pub fn recover_senders(&self) -> Vec<Result<Address, InvalidSignature>> {
+        recover_senders(&self.0)
+    }
+
+    /// Recovers the sender of each transaction in parallel, in order.
+    #[cfg(feature = "rayon")]
+    pub fn par_recover_senders(&self) -> Vec<Result<Address, InvalidSignature>> {
+        par_recover_senders(&self.0)
+    }
+}
+
+impl PooledTransactions {
+    /// Recovers the sender of each transaction, in order.
+    pub fn recover_senders(&self) -> Vec<Result<Address, InvalidSignature>> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, tx)| recover_sender(index, &tx.transaction))
+            .collect()
+    }
+
+    /// Recovers the sender of each transaction in parallel, in order.
+    #[cfg(feature = "rayon")]
+    pub fn par_recover_senders(&self) -> Vec<Result<Address, InvalidSignature>> {
+        use rayon::prelude::*;
+
+        self.0
+            .par_iter()
+            .enumerate()
+            .map(|(index, tx)| recover_sender(index, &tx.transaction))
+            .collect()
+    }
+}
+
+impl NewBlock {
+    /// Recovers the sender of each transaction in the block, in order.
+    pub fn recover_senders(&self) -> Vec<Result<Address, InvalidSignature>> {
+        recover_senders(&self.block.transactions)
+    }
+
+    /// Recovers the sender of each transaction in the block in parallel, in order.
+    #[cfg(feature = "rayon")]
+    pub fn par_recover_senders(&self) -> Vec<Result<Address, InvalidSignature>> {
+        par_recover_senders(&self.block.transactions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ethers_core::types::{Address, U256};
+    use hex_literal::hex;
+    use open_fastrlp::Decodable;
+
+    use super::{recover_senders, InvalidSignature};
+    use crate::{message::RequestPair, PooledTransactions, Transactions, TypedTransaction};
+
+    fn senders<const N: usize>(addresses: [[u8; 20]; N]) -> Vec<Result<Address, InvalidSignature>> {
+        addresses
+            .into_iter()
+            .map(|address| Ok(address.into()))
+            .collect()
+    }
+
+    #[test]
+    fn recover_transaction_senders() {
+        let data = hex!("f90382f9016e82015a85013f2ed0c0830224ae941b6c9c20693afde803b27f8782156c0f892abc2d80b9010438ed173900000000000000000000000000000000000000000000000000000003d4409a530000000000000000000000000000000000000000000000314a79d7d845deec1e00000000000000000000000000000000000000000000000000000000000000a00000000000000000000000002bb8351ad8b1acb7f81649a1688171afdddc3f6a0000000000000000000000000000000000000000000000000000000062c901f00000000000000000000000000000000000000000000000000000000000000002000000000000000000000000c9882def23bc42d53895b8361d0b1edc7570bc6a0000000000000000000000004a846d300f793752ee8bd579192c477130c4b3698193a005475649559ac86feae75bdc2aff8bd2c5a1bcaa4b48fc56427b1a10eb41a0aaa02ed0cce378d0a6efaa72d5e1183f6126d66a353c98421689f0e8c3d473d4d9bdf9020e82155285013f2ed0c0833a0e98943f32d3eaabdcb3a9fe8101e58cf40621b7d43e1580b901a44ef2ba160000000000000000000000000000000000000000000000000004fcbb43000000000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000000050000000000000000000027068840c6252e2e86e545defb6da98b2a0e26d8c1ba00000000000000000000271055d398326f99059ff775485246999027b31979550000000000000000000026f70d9de8e770dd12369099e0fad4903a7b42a907e90000000000000000000027104a846d300f793752ee8bd579192c477130c4b3690000000000000000000026f2088c46b98fc06c96469670059ac1bf453b4f886b000000000000000000002710c9882def23bc42d53895b8361d0b1edc7570bc6a0000000000000000000026f2796acba6556f70a3c5756a0d8fd0a10251c210500000000000000000000027100efb5fd2402a0967b92551d6af54de148504a1158000000000000000000000000000000000000000000000000000000000000001000000000000000000000000bb4cdb9cbd36b01bd1cbaebf2de08d9173bc095c8194a07b3ffd773850417791c32549f4f47319d41b05136ae8c6d855bf35cce9bf8e68a042c5cfa584281a342e1c0434ee2ef554dbaa4f16ec7efa0fb5cd1d52436d2475");
+        let txs = Transactions::decode(&mut &data[..]).unwrap();
+        let expected = senders([
+            hex!("2bb8351ad8b1acb7f81649a1688171afdddc3f6a"),
+            hex!("44ad7f24847716182bb7f7950e3e911024df29c1"),
+        ]);
+        assert_eq!(txs.recover_senders(), expected);
+        #[cfg(feature = "rayon")]
+        assert_eq!(txs.par_recover_senders(), expected);
+
+        let data = hex!("f9022980f90225f8650f84832156008287fb94cf7f9e66af820a19257a2108375b180b0ec491678204d2802ca035b7bfeb9ad9ece2cbafaaf8e202e706b4cfaeb233f46198f00b44d4a566a981a0612638fb29427ca33b9a3be2a0a561beecfe0269655be160d35e72d366a6a860b87502f872041a8459682f008459682f0d8252089461815774383099e24810ab832a5b2a5425c154d58829a2241af62c000080c001a059e6b67f48fb32e7e570dfb11e042b5ad2e55e3ce3ce9cd989c7e06e07feeafda0016b83f4f980694ed2eee4d10667242b1f40dc406901b34125b008d334d47469f86b0384773594008398968094d3e8763675e4c425df46cc3b5c0f6cbdac39604687038d7ea4c68000802ba0ce6834447c0a4193c40382e6c57ae33b241379c5418caac9cdc18d786fd12071a03ca3ae86580e94550d7c071e3a02eadb5a77830947c9225165cf9100901bee88f86b01843b9aca00830186a094d3e8763675e4c425df46cc3b5c0f6cbdac3960468702769bb01b2a00802ba0e24d8bd32ad906d6f8b8d7741e08d1959df021698b19ee232feba15361587d0aa05406ad177223213df262cb66ccbb2f46bfdccfdfbbb5ffdda9e2c02d977631daf86b02843b9aca00830186a094d3e8763675e4c425df46cc3b5c0f6cbdac39604687038d7ea4c68000802ba00eb96ca19e8a77102767a41fc85a36afd5c61ccb09911cec5d3e86e193d9c5aea03a456401896b1b6055311536bf00a718568c744d8c1f9df59879e8350220ca18");
+        let txs = RequestPair::<PooledTransactions>::decode(&mut &data[..])
+            .unwrap()
+            .message;
+        let expected = senders([
+            hex!("d35bd31431b33b756f965af3c62776354d6e4bd8"),
+            hex!("9421de2177f0e810ca1d69a040a2169f8c7c8e4b"),
+            hex!("2efc0b963da6f672254b4e5eea754551fe191fd6"),
+            hex!("2efc0b963da6f672254b4e5eea754551fe191fd6"),
+            hex!("2efc0b963da6f672254b4e5eea754551fe191fd6"),
+        ]);
+        assert_eq!(txs.recover_senders(), expected);
+        #[cfg(feature = "rayon")]
+        assert_eq!(txs.par_recover_senders(), expected);
+        let mut txs = Vec::<TypedTransaction>::from(txs);
+
+        // the same signature with the high s value, which is malleable and rejected since EIP-2
+        let n = U256::from_big_endian(&hex!(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"
+        ));
+        if let TypedTransaction::Legacy(tx) = &mut txs[2] {
+            tx.signature.s = n - tx.signature.s;
+            tx.signature.v ^= 1;
+        }
+        // an s value of zero is never valid
+        if let TypedTransaction::Legacy(tx) = &mut txs[3] {
+            tx.signature.s = 0u64.into();
+        }
+        // legacy transactions have a v of 27, 28 or an EIP-155 value
+        if let TypedTransaction::Legacy(tx) = &mut txs[4] {
+            tx.signature.v = 0;
+        }
+        let recovered = recover_senders(&txs);
+        assert_eq!(recovered[..2], expected[..2]);
+        assert_eq!(recovered[2], Err(InvalidSignature { index: 2 }));
+        assert_eq!(recovered[3], Err(InvalidSignature { index: 3 }));
+        assert_eq!(recovered[4], Err(InvalidSignature { index: 4 }));
+    }
+}
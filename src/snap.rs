@@ -0,0 +1,627 @@
+use ethers_core::types::{H256, U256};
+use hex_literal::hex;
+use open_fastrlp::{
+    BufMut, Decodable, DecodeError, Encodable, RlpDecodable, RlpEncodable, EMPTY_STRING_CODE,
+};
+
+use crate::{
+    primitives::{decode_list, decode_string, encode_list_payload, ensure_consumed, list_length},
+    trie::EMPTY_ROOT,
+    RequestPair,
+};
+
+/// The name of the `snap` capability, used to sync the state by ranges of the state trie.
+pub const SNAP_PROTOCOL_NAME: &str = "snap";
+
+/// The version of the `snap` capability.
+pub const SNAP_PROTOCOL_VERSION: u64 = 1;
+
+/// The number of message ids used by version 1 of the `snap` capability.
+pub const SNAP_MESSAGE_COUNT: usize = 8;
+
+/// The hash of empty code, `keccak256("")`.
+pub const EMPTY_CODE_HASH: H256 = H256(hex!(
+    "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+));
+
+/// Represents message IDs for `snap` protocol messages.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapMessageID {
+    GetAccountRange = 0x00,
+    AccountRange = 0x01,
+    GetStorageRanges = 0x02,
+    StorageRanges = 0x03,
+    GetByteCodes = 0x04,
+    ByteCodes = 0x05,
+    GetTrieNodes = 0x06,
+    TrieNodes = 0x07,
+}
+
+impl Encodable for SnapMessageID {
+    fn length(&self) -> usize {
+        1
+    }
+    fn encode(&self, out: &mut dyn BufMut) {
+        out.put_u8(*self as u8);
+    }
+}
+
+impl Decodable for SnapMessageID {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let id = *buf.first().ok_or(DecodeError::InputTooShort)?;
+        let id = SnapMessageID::try_from(id as usize)
+            .map_err(|_| DecodeError::Custom("Invalid message ID"))?;
+        *buf = &buf[1..];
+        Ok(id)
+    }
+}
+
+impl TryFrom<usize> for SnapMessageID {
+    type Error = &'static str;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(SnapMessageID::GetAccountRange),
+            0x01 => Ok(SnapMessageID::AccountRange),
+            0x02 => Ok(SnapMessageID::GetStorageRanges),
+            0x03 => Ok(SnapMessageID::StorageRanges),
+            0x04 => Ok(SnapMessageID::GetByteCodes),
+            0x05 => Ok(SnapMessageID::ByteCodes),
+            0x06 => Ok(SnapMessageID::GetTrieNodes),
+            0x07 => Ok(SnapMessageID::TrieNodes),
+            _ => Err("Invalid message ID"),
+        }
+    }
+}
+
+/// Implements RLP for a `snap` message. The fields of `snap` messages directly follow the request
+/// id of the [`RequestPair`] instead of being wrapped in a list of their own, so the encoding is
+/// only a valid RLP item inside a [`RequestPair`].
+macro_rules! snap_message_rlp {
+    ($t:ident { $($field:ident),+ }) => {
+        impl Encodable for $t {
+            fn length(&self) -> usize {
+                0 $(+ self.$field.length())+
+            }
+            fn encode(&self, out: &mut dyn BufMut) {
+                $(self.$field.encode(out);)+
+            }
+        }
+
+        impl Decodable for $t {
+            fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+                Ok(Self {
+                    $($field: Decodable::decode(buf)?,)+
+                })
+            }
+        }
+    };
+}
+
+/// An account, as stored in the state trie.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct TrieAccount {
+    pub nonce: u64,
+    pub balance: U256,
+    /// The root of the storage trie of the account.
+    pub storage_root: H256,
+    /// The hash of the code of the account.
+    pub code_hash: H256,
+}
+
+impl Default for TrieAccount {
+    /// An account without balance, storage or code.
+    fn default() -> Self {
+        Self {
+            nonce: 0,
+            balance: U256::zero(),
+            storage_root: EMPTY_ROOT,
+            code_hash: EMPTY_CODE_HASH,
+        }
+    }
+}
+
+impl TrieAccount {
+    fn slim_payload_length(&self) -> usize {
+        let hash_length = |hash: &H256, empty: H256| {
+            if *hash == empty {
+                1
+            } else {
+                hash.length()
+            }
+        };
+        self.nonce.length()
+            + self.balance.length()
+            + hash_length(&self.storage_root, EMPTY_ROOT)
+            + hash_length(&self.code_hash, EMPTY_CODE_HASH)
+    }
+
+    /// Returns the length of the slim encoding of the account.
+    pub fn slim_length(&self) -> usize {
+        list_length(self.slim_payload_length())
+    }
+
+    /// Encodes the account in the slim format of `snap`, in which the empty storage root and the
+    /// empty code hash are encoded as empty strings.
+    pub fn encode_slim(&self, out: &mut dyn BufMut) {
+        let encode_hash = |hash: &H256, empty: H256, payload: &mut Vec<u8>| {
+            if *hash == empty {
+                payload.put_u8(EMPTY_STRING_CODE);
+            } else {
+                hash.encode(payload);
+            }
+        };
+        let mut payload = Vec::with_capacity(self.slim_payload_length());
+        self.nonce.encode(&mut payload);
+        self.balance.encode(&mut payload);
+        encode_hash(&self.storage_root, EMPTY_ROOT, &mut payload);
+        encode_hash(&self.code_hash, EMPTY_CODE_HASH, &mut payload);
+        encode_list_payload(&payload, out);
+    }
+
+    /// Decodes an account in the slim format of `snap`. Hashes which are not left empty are
+    /// accepted even if they are the empty ones.
+    pub fn decode_slim(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let decode_hash = |payload: &mut &[u8], empty: H256| {
+            let hash = decode_string(payload)?;
+            match hash.len() {
+                0 => Ok(empty),
+                32 => Ok(H256::from_slice(hash)),
+                _ => Err(DecodeError::UnexpectedLength),
+            }
+        };
+        let payload = &mut decode_list(buf)?;
+        let account = Self {
+            nonce: u64::decode(payload)?,
+            balance: U256::decode(payload)?,
+            storage_root: decode_hash(payload, EMPTY_ROOT)?,
+            code_hash: decode_hash(payload, EMPTY_CODE_HASH)?,
+        };
+        ensure_consumed(payload)?;
+        Ok(account)
+    }
+}
+
+/// A request for the accounts of a state trie, starting at a hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GetAccountRange {
+    /// The root of the state trie.
+    pub root_hash: H256,
+    /// The hash of the first account to return.
+    pub starting_hash: H256,
+    /// The hash after which no more accounts should be returned.
+    pub limit_hash: H256,
+    /// A soft limit on the size of the response.
+    pub response_bytes: u64,
+}
+
+snap_message_rlp!(GetAccountRange {
+    root_hash,
+    starting_hash,
+    limit_hash,
+    response_bytes
+});
+
+/// An account in an [`AccountRange`], encoded as `[hash, slim account]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountData {
+    /// The hash of the address of the account.
+    pub hash: H256,
+    pub account: TrieAccount,
+}
+
+impl AccountData {
+    fn payload_length(&self) -> usize {
+        self.hash.length() + self.account.slim_length()
+    }
+}
+
+impl Encodable for AccountData {
+    fn length(&self) -> usize {
+        list_length(self.payload_length())
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        let mut payload = Vec::with_capacity(self.payload_length());
+        self.hash.encode(&mut payload);
+        self.account.encode_slim(&mut payload);
+        encode_list_payload(&payload, out);
+    }
+}
+
+impl Decodable for AccountData {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let payload = &mut decode_list(buf)?;
+        let data = Self {
+            hash: H256::decode(payload)?,
+            account: TrieAccount::decode_slim(payload)?,
+        };
+        ensure_consumed(payload)?;
+        Ok(data)
+    }
+}
+
+/// The response to [`GetAccountRange`], containing consecutive accounts and the proof of the
+/// range.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountRange {
+    /// The accounts, in increasing order of hash.
+    pub accounts: Vec<AccountData>,
+    /// The trie nodes proving the first and last account, or the absence of accounts.
+    pub proof: Vec<bytes::Bytes>,
+}
+
+snap_message_rlp!(AccountRange { accounts, proof });
+
+/// A request for the storage slots of accounts of a state trie.
+///
+/// Only the range of the first account is limited by the starting and limit hashes, the storage
+/// of the following accounts is requested in full.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GetStorageRanges {
+    /// The root of the state trie.
+    pub root_hash: H256,
+    /// The hashes of the addresses of the accounts.
+    pub account_hashes: Vec<H256>,
+    /// The hash of the first slot to return, or empty for the first slot.
+    pub starting_hash: bytes::Bytes,
+    /// The hash after which no more slots should be returned, or empty for no limit.
+    pub limit_hash: bytes::Bytes,
+    /// A soft limit on the size of the response.
+    pub response_bytes: u64,
+}
+
+snap_message_rlp!(GetStorageRanges {
+    root_hash,
+    account_hashes,
+    starting_hash,
+    limit_hash,
+    response_bytes
+});
+
+/// A storage slot in a [`StorageRanges`].
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct StorageData {
+    /// The hash of the slot.
+    pub hash: H256,
+    /// The RLP encoded value of the slot.
+    pub data: bytes::Bytes,
+}
+
+/// The response to [`GetStorageRanges`], containing the slots of each account.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageRanges {
+    /// The slots of each returned account, in increasing order of hash.
+    pub slots: Vec<Vec<StorageData>>,
+    /// The trie nodes proving the range of the last account, if its storage is incomplete.
+    pub proof: Vec<bytes::Bytes>,
+}
+
+snap_message_rlp!(StorageRanges { slots, proof });
+
+/// A request for contract code by hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GetByteCodes {
+    /// The code hashes.
+    pub hashes: Vec<H256>,
+    /// A soft limit on the size of the response.
+    pub response_bytes: u64,
+}
+
+snap_message_rlp!(GetByteCodes {
+    hashes,
+    response_bytes
+});
+
+/// The response to [`GetByteCodes`], containing the codes in request order. Codes the peer does
+/// not have are skipped.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ByteCodes {
+    pub codes: Vec<bytes::Bytes>,
+}
+
+snap_message_rlp!(ByteCodes { codes });
+
+/// A request for trie nodes by path, used to heal the state after syncing ranges.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GetTrieNodes {
+    /// The root of the state trie.
+    pub root_hash: H256,
+    /// Groups of paths: the path of a node in the account trie, or the path of an account
+    /// followed by paths of nodes in its storage trie.
+    pub paths: Vec<Vec<bytes::Bytes>>,
+    /// A soft limit on the size of the response.
+    pub response_bytes: u64,
+}
+
+snap_message_rlp!(GetTrieNodes {
+    root_hash,
+    paths,
+    response_bytes
+});
+
+/// The response to [`GetTrieNodes`], containing the nodes in request order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrieNodes {
+    pub nodes: Vec<bytes::Bytes>,
+}
+
+snap_message_rlp!(TrieNodes { nodes });
+
+/// A message of the `snap` capability. Every message is a request or a response, framed with a
+/// request id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnapMessage {
+    GetAccountRange(RequestPair<GetAccountRange>),
+    AccountRange(RequestPair<AccountRange>),
+    GetStorageRanges(RequestPair<GetStorageRanges>),
+    StorageRanges(RequestPair<StorageRanges>),
+    GetByteCodes(RequestPair<GetByteCodes>),
+    ByteCodes(RequestPair<ByteCodes>),
+    GetTrieNodes(RequestPair<GetTrieNodes>),
+    TrieNodes(RequestPair<TrieNodes>),
+}
+
+impl SnapMessage {
+    /// Returns the message's ID.
+    pub fn message_id(&self) -> SnapMessageID {
+        match self {
+            SnapMessage::GetAccountRange(_) => SnapMessageID::GetAccountRange,
+            SnapMessage::AccountRange(_) => SnapMessageID::AccountRange,
+            SnapMessage::GetStorageRanges(_) => SnapMessageID::GetStorageRanges,
+            SnapMessage::StorageRanges(_) => SnapMessageID::StorageRanges,
+            SnapMessage::GetByteCodes(_) => SnapMessageID::GetByteCodes,
+            SnapMessage::ByteCodes(_) => SnapMessageID::ByteCodes,
+            SnapMessage::GetTrieNodes(_) => SnapMessageID::GetTrieNodes,
+            SnapMessage::TrieNodes(_) => SnapMessageID::TrieNodes,
+        }
+    }
+
+    /// Returns the request id of the message.
+    pub fn request_id(&self) -> u64 {
+        match self {
+            SnapMessage::GetAccountRange(request) => request.request_id,
+            SnapMessage::AccountRange(response) => response.request_id,
+            SnapMessage::GetStorageRanges(request) => request.request_id,
+            SnapMessage::StorageRanges(response) => response.request_id,
+            SnapMessage::GetByteCodes(request) => request.request_id,
+            SnapMessage::ByteCodes(response) => response.request_id,
+            SnapMessage::GetTrieNodes(request) => request.request_id,
+            SnapMessage::TrieNodes(response) => response.request_id,
+        }
+    }
+
+    /// Decodes the payload of a message with the given message id.
+    pub fn decode_message(
+        message_type: SnapMessageID,
+        buf: &mut &[u8],
+    ) -> Result<Self, DecodeError> {
+        Ok(match message_type {
+            SnapMessageID::GetAccountRange => {
+                SnapMessage::GetAccountRange(RequestPair::decode(buf)?)
+            }
+            SnapMessageID::AccountRange => SnapMessage::AccountRange(RequestPair::decode(buf)?),
+            SnapMessageID::GetStorageRanges => {
+                SnapMessage::GetStorageRanges(RequestPair::decode(buf)?)
+            }
+            SnapMessageID::StorageRanges => SnapMessage::StorageRanges(RequestPair::decode(buf)?),
+            SnapMessageID::GetByteCodes => SnapMessage::GetByteCodes(RequestPair::decode(buf)?),
+            SnapMessageID::ByteCodes => SnapMessage::ByteCodes(RequestPair::decode(buf)?),
+            SnapMessageID::GetTrieNodes => SnapMessage::GetTrieNodes(RequestPair::decode(buf)?),
+            SnapMessageID::TrieNodes => SnapMessage::TrieNodes(RequestPair::decode(buf)?),
+        })
+    }
+}
+
+/// Encodes the payload of the message, without the message id.
+impl Encodable for SnapMessage {
+    fn length(&self) -> usize {
+        match self {
+            SnapMessage::GetAccountRange(request) => request.length(),
+            SnapMessage::AccountRange(response) => response.length(),
+            SnapMessage::GetStorageRanges(request) => request.length(),
+            SnapMessage::StorageRanges(response) => response.length(),
+            SnapMessage::GetByteCodes(request) => request.length(),
+            SnapMessage::ByteCodes(response) => response.length(),
+            SnapMessage::GetTrieNodes(request) => request.length(),
+            SnapMessage::TrieNodes(response) => response.length(),
+        }
+    }
+
+    fn encode(&self, out: &mut dyn BufMut) {
+        match self {
+            SnapMessage::GetAccountRange(request) => request.encode(out),
+            SnapMessage::AccountRange(response) => response.encode(out),
+            SnapMessage::GetStorageRanges(request) => request.encode(out),
+            SnapMessage::StorageRanges(response) => response.encode(out),
+            SnapMessage::GetByteCodes(request) => request.encode(out),
+            SnapMessage::ByteCodes(response) => response.encode(out),
+            SnapMessage::GetTrieNodes(request) => request.encode(out),
+            SnapMessage::TrieNodes(response) => response.encode(out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ethers_core::types::H256;
+    use hex_literal::hex;
+    use open_fastrlp::{Decodable, Encodable};
+
+    use super::{
+        AccountData, AccountRange, ByteCodes, GetAccountRange, GetByteCodes, GetStorageRanges,
+        GetTrieNodes, SnapMessage, SnapMessageID, StorageData, StorageRanges, TrieAccount,
+        TrieNodes, EMPTY_CODE_HASH,
+    };
+    use crate::{message::RequestPair, trie::EMPTY_ROOT};
+
+    fn encode<T: Encodable>(value: &T) -> Vec<u8> {
+        let mut out = vec![];
+        value.encode(&mut out);
+        assert_eq!(out.len(), value.length());
+        out
+    }
+
+    fn roundtrip(message: SnapMessage) {
+        let encoded = encode(&message);
+        let mut id = &[message.message_id() as u8][..];
+        let id = SnapMessageID::decode(&mut id).unwrap();
+        let buf = &mut &encoded[..];
+        assert_eq!(SnapMessage::decode_message(id, buf).unwrap(), message);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn empty_code_hash() {
+        assert_eq!(EMPTY_CODE_HASH, H256(ethers_core::utils::keccak256([])));
+    }
+
+    #[test]
+    fn slim_account() {
+        let account = TrieAccount::default();
+        let mut slim = vec![];
+        account.encode_slim(&mut slim);
+        assert_eq!(slim, hex!("c480808080"));
+        assert_eq!(slim.len(), account.slim_length());
+        assert_eq!(TrieAccount::decode_slim(&mut &slim[..]).unwrap(), account);
+
+        // the full encoding keeps the hashes
+        let full = encode(&account);
+        assert_eq!(full.len(), 70);
+        assert_eq!(TrieAccount::decode(&mut &full[..]).unwrap(), account);
+        assert_eq!(TrieAccount::decode_slim(&mut &full[..]).unwrap(), account);
+
+        let account = TrieAccount {
+            nonce: 1,
+            balance: 1000.into(),
+            storage_root: H256::repeat_byte(0x11),
+            code_hash: EMPTY_CODE_HASH,
+        };
+        let mut slim = vec![];
+        account.encode_slim(&mut slim);
+        assert_eq!(
+            slim,
+            hex!("e6018203e8a0111111111111111111111111111111111111111111111111111111111111111180")
+        );
+        assert_eq!(slim.len(), account.slim_length());
+        assert_eq!(TrieAccount::decode_slim(&mut &slim[..]).unwrap(), account);
+
+        // hashes are either empty or 32 bytes
+        assert!(TrieAccount::decode_slim(&mut &hex!("c58080808101")[..]).is_err());
+        assert!(TrieAccount::decode_slim(&mut &hex!("c58080808080")[..]).is_err());
+    }
+
+    #[test]
+    fn get_byte_codes() {
+        let request = RequestPair {
+            request_id: 1337,
+            message: GetByteCodes {
+                hashes: vec![EMPTY_CODE_HASH],
+                response_bytes: 0x80000,
+            },
+        };
+        // the fields follow the request id directly
+        let encoded = encode(&request);
+        assert_eq!(
+            encoded,
+            hex!(
+                "e9820539e1a0c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a47083080000"
+            )
+        );
+        assert_eq!(
+            RequestPair::<GetByteCodes>::decode(&mut &encoded[..]).unwrap(),
+            request
+        );
+
+        // the list of the pair bounds the fields of the message
+        let mut short = encoded.clone();
+        short[0] -= 1;
+        assert!(RequestPair::<GetByteCodes>::decode(&mut &short[..]).is_err());
+        let mut trailing = encoded.clone();
+        trailing[0] += 1;
+        trailing.push(0x80);
+        assert!(RequestPair::<GetByteCodes>::decode(&mut &trailing[..]).is_err());
+        roundtrip(SnapMessage::GetByteCodes(request));
+    }
+
+    #[test]
+    fn messages() {
+        roundtrip(SnapMessage::GetAccountRange(RequestPair {
+            request_id: 1,
+            message: GetAccountRange {
+                root_hash: H256::repeat_byte(0x01),
+                starting_hash: H256::zero(),
+                limit_hash: H256::repeat_byte(0xff),
+                response_bytes: 512 * 1024,
+            },
+        }));
+        roundtrip(SnapMessage::AccountRange(RequestPair {
+            request_id: 1,
+            message: AccountRange {
+                accounts: vec![
+                    AccountData {
+                        hash: H256::repeat_byte(0x02),
+                        account: TrieAccount::default(),
+                    },
+                    AccountData {
+                        hash: H256::repeat_byte(0x03),
+                        account: TrieAccount {
+                            nonce: 7,
+                            balance: 1.into(),
+                            storage_root: EMPTY_ROOT,
+                            code_hash: H256::repeat_byte(0x04),
+                        },
+                    },
+                ],
+                proof: vec![hex!("c0").to_vec().into(), hex!("c180").to_vec().into()],
+            },
+        }));
+        roundtrip(SnapMessage::GetStorageRanges(RequestPair {
+            request_id: 2,
+            message: GetStorageRanges {
+                root_hash: H256::repeat_byte(0x01),
+                account_hashes: vec![H256::repeat_byte(0x02), H256::repeat_byte(0x03)],
+                starting_hash: Default::default(),
+                limit_hash: Default::default(),
+                response_bytes: 512 * 1024,
+            },
+        }));
+        roundtrip(SnapMessage::StorageRanges(RequestPair {
+            request_id: 2,
+            message: StorageRanges {
+                slots: vec![
+                    vec![StorageData {
+                        hash: H256::repeat_byte(0x05),
+                        data: hex!("01").to_vec().into(),
+                    }],
+                    vec![],
+                ],
+                proof: vec![],
+            },
+        }));
+        roundtrip(SnapMessage::ByteCodes(RequestPair {
+            request_id: 3,
+            message: ByteCodes {
+                codes: vec![hex!("6000").to_vec().into()],
+            },
+        }));
+        roundtrip(SnapMessage::GetTrieNodes(RequestPair {
+            request_id: 4,
+            message: GetTrieNodes {
+                root_hash: H256::repeat_byte(0x01),
+                paths: vec![
+                    vec![hex!("01").to_vec().into()],
+                    vec![[0x02; 32].to_vec().into(), hex!("").to_vec().into()],
+                ],
+                response_bytes: 512 * 1024,
+            },
+        }));
+        roundtrip(SnapMessage::TrieNodes(RequestPair {
+            request_id: 4,
+            message: TrieNodes { nodes: vec![] },
+        }));
+
+        assert!(SnapMessageID::decode(&mut &[0x08][..]).is_err());
+    }
+}
@@ -0,0 +1,430 @@
+use crate::{
+    forkid::{BlockNumber, ForkFilter, Head, Timestamp},
+    Chain, EthVersion, NamedChain, Status,
+};
+use ethers_core::types::U256;
+use hex_literal::hex;
+use primitive_types::H256;
+
+/// The DNS discovery tree of the Ethereum Foundation, see
+/// [EIP-1459](https://eips.ethereum.org/EIPS/eip-1459).
+macro_rules! ethdisco_tree {
+    ($network:literal) => {
+        concat!(
+            "enrtree://AKA3AM6LPBYEUDMVNU3BSVQJ5AD45Y7YPOHJLEF6W26QOE4VTUDPE@all.",
+            $network,
+            ".ethdisco.net"
+        )
+    };
+}
+
+const MAINNET_BOOTNODES: &[&str] = &[
+    "enode://d860a01f9722d78051619d1e2351aba3f43f943f6f00718d1b9baa4101932a1f5011f16bb2b1bb35db20d6fe28fa0bf09636d26a87d31de9ec6203eeedb1f666@18.138.108.67:30303",
+    "enode://22a8232c3abc76a16ae9d6c3b164f98775fe226f0917b0ca871128a74a8e9630b458460865bab457221f1d448dd9791d24c4e5d88786180ac185df813a68d4de@3.209.45.79:30303",
+    "enode://2b252ab6a1d0f971d9722cb839a42cb81db019ba44c08754628ab4a823487071b5695317c8ccd085219c3a03af063495b2f1da8d18218da2d6a82981b45e6ffc@65.108.70.101:30303",
+    "enode://4aeb4ab6c14b23e2c4cfdce879c04b0748a20d8e9b59e25ded2a08143e265c6c25936e74cbc8e641e3312ca288673d91f2f93f8e277de3cfa444ecdaaf982052@157.90.35.166:30303",
+];
+
+const SEPOLIA_BOOTNODES: &[&str] = &[
+    "enode://4e5e92199ee224a01932a377160aa432f31d0b351f84ab413a8e0a42f4f36476f8fb1cbe914af0d9aef0d51665c214cf653c651c4bbd9d5550a934f241f1682b@138.197.51.181:30303",
+    "enode://143e11fb766781d22d92a2e33f8f104cddae4411a122295ed1fdb6638de96a6ce65f5b7c964ba3763bba27961738fef7d3ecc739268f3e5e771fb4c87b6234ba@146.190.1.103:30303",
+    "enode://8b61dc2d06c3f96fddcbebb0efb29d60d3598650275dc469c22229d3e5620369b0d3dedafd929835fe7f489618f19f456fe7c0df572bf2d914a9f4e006f783a9@170.64.250.88:30303",
+    "enode://10d62eff032205fcef19497f35ca8477bea0eadfff6d769a147e895d8b2b8f8ae6341630c645c30f5df6e67547c03494ced3d9c5764e8622a26587b083b028e8@139.59.49.206:30303",
+    "enode://9e9492e2e8836114cc75f5b929784f4f46c324ad01daf87d956f98b3b6c5fcba95524d6e5cf9861dc96a2c8a171ea7105bb554a197455058de185fa870970c7c@138.68.123.152:30303",
+];
+
+const HOLESKY_BOOTNODES: &[&str] = &[
+    "enode://ac906289e4b7f12df423d654c5a962b6ebe5b3a74cc9e06292a85221f9a64a6f1cfdd6b714ed6dacef51578f92b34c60ee91e9ede9c7f8fadc4d347326d95e2b@146.190.13.128:30303",
+];
+
+const GOERLI_BOOTNODES: &[&str] = &[
+    "enode://011f758e6552d105183b1761c5e2dea0111bc20fd5f6422bc7f91e0fabbec9a6595caf6239b37feb773dddd3f87240d99d859431891e4a642cf2a0a9e6cbb98a@51.141.78.53:30303",
+    "enode://176b9417f511d05b6b2cf3e34b756cf0a7096b3094572a8f6ef4cdcb9d1f9d00683bf0f83347eebdf3b81c3521c2332086d9592802230bf528eaf606a1d9677b@13.93.54.137:30303",
+    "enode://46add44b9f13965f7b9875ac6b85f016f341012d84f975377573800a863526f4da19ae2c620ec73d11591fa9510e992ecc03ad0751f53cc02f7c7ed6d55c7291@94.237.54.114:30313",
+    "enode://b5948a2d3e9d486c4d75bf32713221c2bd6cf86463302339299bd227dc2e276cd5a1c7ca4f43a0e9122fe9af884efed563bd2a1fd28661f3b5f5ad7bf1de5949@18.218.250.66:30303",
+];
+
+const BSC_BOOTNODES: &[&str] = &[
+    "enode://433c8bfdf53a3e2268ccb1b829e47f629793291cbddf0c76ae626da802f90532251fc558e2e0d10d6725e759088439bf1cd4714716b03a259a35d4b2e4acfa7f@52.69.102.73:30311",
+    "enode://571bee8fb902a625942f10a770ccf727ae2ba1bab2a2b64e121594a99c9437317f6166a395670a00b7d93647eacafe598b6bbcef15b40b6d1a10243865a3e80f@35.73.84.120:30311",
+    "enode://fac42fb0ba082b7d1eebded216db42161163d42e4f52c9e47716946d64468a62da4ba0b1cac0df5e8bf1e5284861d757339751c33d51dfef318be5168803d0b5@18.203.152.54:30311",
+    "enode://3063d1c9e1b824cfbb7c7b6abafa34faec6bb4e7e06941d218d760acdd7963b274278c5c3e63914bd6d1b58504c59ec5522c56f883baceb8538674b92da48a96@34.250.32.100:30311",
+    "enode://ad78c64a4ade83692488aa42e4c94084516e555d3f340d9802c2bf106a3df8868bc46eae083d2de4018f40e8d9a9952c32a0943cd68855a9bc9fd07aac982a6d@34.204.214.24:30311",
+    "enode://5db798deb67df75d073f8e2953dad283148133acb520625ea804c9c4ad09a35f13592a762d8f89056248f3889f6dcc33490c145774ea4ff2966982294909b37a@107.20.191.97:30311",
+];
+
+const BSC_TESTNET_BOOTNODES: &[&str] = &[
+    "enode://0637d1e62026e0c8685b1db0ca1c767c78c95c3fab64abc468d1a64b12ca4b530b46b8f80c915aec96f74f7ffc5999e8ad6d1484476f420f0c10e3d42361914b@52.199.214.252:30311",
+    "enode://df1e8eb59e42cad3c4551b2a53e31a7e55a2fdde1287babd1e94b0836550b489ba16c40932e4dacb16cba346bd442c432265a299c4aca63ee7bb0f832b9f45eb@52.51.80.128:30311",
+];
+
+/// The parameters needed to join the p2p network of a chain: its genesis, fork schedule and
+/// initial peers.
+///
+/// # Example
+/// ```
+/// use ethers_core::types::U256;
+/// use ethp2p::{Chain, EthVersion, Head, NamedChain, NetworkSpec};
+///
+/// let spec = NetworkSpec::from_chain(Chain::Named(NamedChain::Mainnet)).unwrap();
+/// let head = Head {
+///     number: 20_000_000,
+///     timestamp: 1_710_338_135,
+/// };
+/// let status = spec.status(EthVersion::Eth67, head, [0; 32], U256::zero());
+/// assert_eq!(status.forkid, spec.fork_filter(head).current());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NetworkSpec {
+    /// The chain of the network.
+    pub chain: Chain,
+
+    /// The hash of the genesis block.
+    pub genesis_hash: H256,
+
+    /// The timestamp of the genesis block.
+    pub genesis_timestamp: Timestamp,
+
+    /// The blocks at which forks activate, as used in the
+    /// [EIP-2124](https://eips.ethereum.org/EIPS/eip-2124) fork id. Forks active at genesis are
+    /// omitted, and forks that activate at the same block are only listed once.
+    pub block_forks: &'static [BlockNumber],
+
+    /// The timestamps at which forks activate, as used in the
+    /// [EIP-6122](https://eips.ethereum.org/EIPS/eip-6122) fork id.
+    pub time_forks: &'static [Timestamp],
+
+    /// The `enode://` urls of the bootnodes of the network.
+    pub bootnodes: &'static [&'static str],
+
+    /// The `enrtree://` url of the [EIP-1459](https://eips.ethereum.org/EIPS/eip-1459) DNS
+    /// discovery tree of the network, if there is one.
+    pub dns_tree: Option<&'static str>,
+}
+
+impl NetworkSpec {
+    /// The Ethereum mainnet.
+    pub fn mainnet() -> Self {
+        Self {
+            chain: Chain::Named(NamedChain::Mainnet),
+            genesis_hash: H256(hex!(
+                "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+            )),
+            genesis_timestamp: 0,
+            block_forks: &[
+                1_150_000,  // Homestead
+                1_920_000,  // DAO
+                2_463_000,  // Tangerine Whistle
+                2_675_000,  // Spurious Dragon
+                4_370_000,  // Byzantium
+                7_280_000,  // Constantinople, Petersburg
+                9_069_000,  // Istanbul
+                9_200_000,  // Muir Glacier
+                12_244_000, // Berlin
+                12_965_000, // London
+                13_773_000, // Arrow Glacier
+                15_050_000, // Gray Glacier
+            ],
+            time_forks: &[
+                1_681_338_455, // Shanghai
+                1_710_338_135, // Cancun
+                1_746_612_311, // Prague
+                1_764_798_551, // Osaka
+                1_765_290_071, // BPO1
+                1_767_747_671, // BPO2
+            ],
+            bootnodes: MAINNET_BOOTNODES,
+            dns_tree: Some(ethdisco_tree!("mainnet")),
+        }
+    }
+
+    /// The Sepolia testnet.
+    pub fn sepolia() -> Self {
+        Self {
+            chain: Chain::Named(NamedChain::Sepolia),
+            genesis_hash: H256(hex!(
+                "25a5cc106eea7138acab33231d7160d69cb777ee0c2c553fcddf5138993e6dd9"
+            )),
+            genesis_timestamp: 1_633_267_481,
+            block_forks: &[
+                1_735_371, // Merge netsplit
+            ],
+            time_forks: &[
+                1_677_557_088, // Shanghai
+                1_706_655_072, // Cancun
+                1_741_159_776, // Prague
+                1_760_427_360, // Osaka
+                1_761_017_184, // BPO1
+                1_761_607_008, // BPO2
+            ],
+            bootnodes: SEPOLIA_BOOTNODES,
+            dns_tree: Some(ethdisco_tree!("sepolia")),
+        }
+    }
+
+    /// The Holesky testnet.
+    pub fn holesky() -> Self {
+        Self {
+            chain: Chain::Named(NamedChain::Holesky),
+            genesis_hash: H256(hex!(
+                "b5f7f912443c940f21fd611f12828d75b534364ed9e95ca4e307729a4661bde4"
+            )),
+            genesis_timestamp: 1_695_902_100,
+            block_forks: &[],
+            time_forks: &[
+                1_696_000_704, // Shanghai
+                1_707_305_664, // Cancun
+                1_740_434_112, // Prague
+                1_759_308_480, // Osaka
+                1_759_800_000, // BPO1
+                1_760_389_824, // BPO2
+            ],
+            bootnodes: HOLESKY_BOOTNODES,
+            dns_tree: Some(ethdisco_tree!("holesky")),
+        }
+    }
+
+    /// The Goerli testnet.
+    pub fn goerli() -> Self {
+        Self {
+            chain: Chain::Named(NamedChain::Goerli),
+            genesis_hash: H256(hex!(
+                "bf7e331f7f7c1dd2e05159666b3bf8bc7a8a3a9eb1d518969eab529dd9b88c1a"
+            )),
+            genesis_timestamp: 1_548_854_791,
+            block_forks: &[
+                1_561_651, // Istanbul
+                4_460_644, // Berlin
+                5_062_605, // London
+            ],
+            time_forks: &[
+                1_678_832_736, // Shanghai
+                1_705_473_120, // Cancun
+            ],
+            bootnodes: GOERLI_BOOTNODES,
+            dns_tree: Some(ethdisco_tree!("goerli")),
+        }
+    }
+
+    /// The BNB Smart Chain mainnet.
+    pub fn bsc() -> Self {
+        Self {
+            chain: Chain::Named(NamedChain::BinanceSmartChain),
+            genesis_hash: H256(hex!(
+                "0d21840abff46b96c84b2ac9e10e4f5cdaeb5693cb665db62a2f3b02d2d57b5b"
+            )),
+            genesis_timestamp: 1_587_390_414,
+            block_forks: &[
+                5_184_000,  // MirrorSync
+                13_082_000, // Bruno
+                18_907_621, // Euler
+                21_962_149, // Nano
+                22_107_423, // Moran
+                23_846_001, // Gibbs
+                27_281_024, // Planck
+                29_020_050, // Luban
+                30_720_096, // Plato
+                31_302_048, // Berlin, London, Hertz
+                34_140_700, // HertzFix
+            ],
+            time_forks: &[
+                1_705_996_800, // Shanghai, Kepler
+                1_713_419_340, // Feynman, FeynmanFix
+                1_718_863_500, // Cancun, Haber
+                1_727_316_120, // HaberFix
+                1_727_317_200, // Bohr
+                1_742_436_600, // Pascal, Prague
+                1_745_903_100, // Lorentz
+                1_751_250_600, // Maxwell
+            ],
+            bootnodes: BSC_BOOTNODES,
+            dns_tree: None,
+        }
+    }
+
+    /// The BNB Smart Chain testnet, also known as Chapel.
+    pub fn bsc_testnet() -> Self {
+        Self {
+            chain: Chain::Named(NamedChain::BinanceSmartChainTestnet),
+            genesis_hash: H256(hex!(
+                "6d3c66c5357ec91d5c43af47e234a939b22557cbb552dc45bebbceeed90fbe34"
+            )),
+            genesis_timestamp: 1_587_390_414,
+            block_forks: &[
+                1_010_000,  // Ramanujan
+                1_014_369,  // Niels
+                5_582_500,  // MirrorSync
+                13_837_000, // Bruno
+                19_203_503, // Euler
+                22_800_220, // Gibbs
+                23_482_428, // Nano
+                23_603_940, // Moran
+                28_196_022, // Planck
+                29_295_050, // Luban
+                29_861_024, // Plato
+                31_103_030, // Berlin, London, Hertz
+                35_682_300, // HertzFix
+            ],
+            time_forks: &[
+                1_702_972_800, // Shanghai, Kepler
+                1_710_136_800, // Feynman
+                1_711_342_800, // FeynmanFix
+                1_713_330_442, // Cancun
+                1_716_962_820, // Haber
+                1_719_986_788, // HaberFix
+                1_724_116_996, // Bohr
+                1_740_452_880, // Pascal, Prague
+                1_744_097_580, // Lorentz
+                1_748_243_100, // Maxwell
+            ],
+            bootnodes: BSC_TESTNET_BOOTNODES,
+            dns_tree: None,
+        }
+    }
+
+    /// Returns the spec of a known chain.
+    pub fn from_chain(chain: Chain) -> Option<Self> {
+        let named = match chain {
+            Chain::Named(named) => named,
+            Chain::Id(id) => NamedChain::try_from(id).ok()?,
+        };
+
+        match named {
+            NamedChain::Mainnet => Some(Self::mainnet()),
+            NamedChain::Sepolia => Some(Self::sepolia()),
+            NamedChain::Holesky => Some(Self::holesky()),
+            NamedChain::Goerli => Some(Self::goerli()),
+            NamedChain::BinanceSmartChain => Some(Self::bsc()),
+            NamedChain::BinanceSmartChainTestnet => Some(Self::bsc_testnet()),
+            _ => None,
+        }
+    }
+
+    /// Creates a [`ForkFilter`] for the network at the given head.
+    pub fn fork_filter(&self, head: Head) -> ForkFilter {
+        ForkFilter::new(
+            head,
+            self.genesis_hash,
+            self.genesis_timestamp,
+            self.block_forks.iter().copied(),
+            self.time_forks.iter().copied(),
+        )
+    }
+
+    /// Creates the [`Status`] message announcing the given head on this network.
+    pub fn status(
+        &self,
+        version: EthVersion,
+        head: Head,
+        blockhash: [u8; 32],
+        total_difficulty: U256,
+    ) -> Status {
+        Status {
+            version: version as u8,
+            chain: self.chain,
+            total_difficulty,
+            blockhash,
+            genesis: self.genesis_hash.0,
+            forkid: self.fork_filter(head).current(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NetworkSpec;
+    use crate::forkid::{ForkHash, ForkId, Head};
+    use crate::{Chain, NamedChain};
+    use hex_literal::hex;
+    use k256::ecdsa::VerifyingKey;
+
+    fn assert_fork_ids(spec: NetworkSpec, cases: &[(u64, u64, [u8; 4], u64)]) {
+        for &(number, timestamp, hash, next) in cases {
+            assert_eq!(
+                spec.fork_filter(Head { number, timestamp }).current(),
+                ForkId {
+                    hash: ForkHash(hash),
+                    next
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn mainnet_fork_ids() {
+        assert_fork_ids(
+            NetworkSpec::mainnet(),
+            &[
+                (0, 0, hex!("fc64ec04"), 1_150_000),
+                (7_280_000, 0, hex!("668db0af"), 9_069_000),
+                (12_965_000, 0, hex!("b715077d"), 13_773_000),
+                (15_050_000, 0, hex!("f0afd0e3"), 1_681_338_455),
+                (20_000_000, 1_681_338_455, hex!("dce96c2d"), 1_710_338_135),
+                (20_000_000, 1_710_338_135, hex!("9f3d2254"), 1_746_612_311),
+                (20_000_000, 1_746_612_311, hex!("c376cf8b"), 1_764_798_551),
+                (20_000_000, 1_767_747_671, hex!("07c9462e"), 0),
+            ],
+        );
+    }
+
+    #[test]
+    fn testnet_fork_ids() {
+        assert_fork_ids(
+            NetworkSpec::goerli(),
+            &[
+                (0, 0, hex!("a3f5ab08"), 1_561_651),
+                (5_062_605, 0, hex!("b8c6299d"), 1_678_832_736),
+                (6_000_000, 1_705_473_120, hex!("70cc14e2"), 0),
+            ],
+        );
+        assert_fork_ids(
+            NetworkSpec::sepolia(),
+            &[
+                (0, 0, hex!("fe3366e7"), 1_735_371),
+                (1_735_371, 0, hex!("b96cbd13"), 1_677_557_088),
+                (1_735_371, 1_706_655_072, hex!("88cf81d9"), 1_741_159_776),
+                (1_735_371, 1_741_159_776, hex!("ed88b5fd"), 1_760_427_360),
+                (1_735_371, 1_761_607_008, hex!("268956b6"), 0),
+            ],
+        );
+        assert_fork_ids(
+            NetworkSpec::holesky(),
+            &[
+                (0, 0, hex!("c61a6098"), 1_696_000_704),
+                (0, 1_707_305_664, hex!("9b192ad0"), 1_740_434_112),
+                (0, 1_740_434_112, hex!("dfbd9bed"), 1_759_308_480),
+            ],
+        );
+    }
+
+    #[test]
+    fn from_chain() {
+        for spec in [
+            NetworkSpec::mainnet(),
+            NetworkSpec::sepolia(),
+            NetworkSpec::holesky(),
+            NetworkSpec::goerli(),
+            NetworkSpec::bsc(),
+            NetworkSpec::bsc_testnet(),
+        ] {
+            assert_eq!(NetworkSpec::from_chain(spec.chain), Some(spec));
+            assert_eq!(
+                NetworkSpec::from_chain(Chain::Id(spec.chain.id())),
+                Some(spec)
+            );
+            for bootnode in spec.bootnodes {
+                let (id, _) = bootnode
+                    .strip_prefix("enode://")
+                    .and_then(|enode| enode.split_once('@'))
+                    .unwrap();
+                // the node id is an uncompressed public key without its prefix
+                let public_key = [&[0x04], &hex::decode(id).unwrap()[..]].concat();
+                assert!(VerifyingKey::from_sec1_bytes(&public_key).is_ok());
+            }
+        }
+        assert_eq!(
+            NetworkSpec::from_chain(Chain::Named(NamedChain::Optimism)),
+            None
+        );
+    }
+}
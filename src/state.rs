@@ -1,3 +1,6 @@
+use std::collections::{HashMap, HashSet};
+
+use ethers_core::utils::keccak256;
 use open_fastrlp::{RlpDecodableWrapper, RlpEncodableWrapper};
 
 /// A request for state tree nodes corresponding to the given hashes.
@@ -14,11 +17,76 @@ pub struct GetNodeData(pub Vec<[u8; 32]>);
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper)]
 pub struct NodeData(pub Vec<bytes::Bytes>);
 
+impl NodeData {
+    /// Match the returned items to the hashes of the request, by hashing each item with
+    /// `keccak256`.
+    ///
+    /// Peers may return any subset of the requested items, so the position of an item in the
+    /// response is not used. Items which were not requested or are returned twice are reported as
+    /// [`NodeDataViolation`]s.
+    pub fn match_request(&self, request: &GetNodeData) -> MatchedNodeData {
+        let requested = request.0.iter().collect::<HashSet<_>>();
+        let mut returned = HashMap::with_capacity(self.0.len());
+        let mut violations = vec![];
+        for data in &self.0 {
+            let hash = keccak256(data);
+            if !requested.contains(&hash) {
+                violations.push(NodeDataViolation::Unrequested(hash));
+            } else if returned.insert(hash, data.clone()).is_some() {
+                violations.push(NodeDataViolation::Duplicate(hash));
+            }
+        }
+
+        MatchedNodeData {
+            nodes: request
+                .0
+                .iter()
+                .map(|hash| (*hash, returned.get(hash).cloned()))
+                .collect(),
+            violations,
+        }
+    }
+}
+
+/// A [`NodeData`] response matched to its [`GetNodeData`] request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchedNodeData {
+    /// Each requested hash in request order, with its data if the peer returned it.
+    pub nodes: Vec<([u8; 32], Option<bytes::Bytes>)>,
+    /// The ways in which the response violates the protocol, which can be used for peer scoring.
+    pub violations: Vec<NodeDataViolation>,
+}
+
+/// A way in which a [`NodeData`] response violates the protocol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeDataViolation {
+    /// An item with the given hash was returned, but not requested.
+    Unrequested([u8; 32]),
+    /// The item with the given hash was returned more than once.
+    Duplicate([u8; 32]),
+}
+
+impl MatchedNodeData {
+    /// Returns the requested hashes which the peer did not return data for.
+    pub fn missing(&self) -> impl Iterator<Item = &[u8; 32]> + '_ {
+        self.nodes
+            .iter()
+            .filter(|(_, data)| data.is_none())
+            .map(|(hash, _)| hash)
+    }
+
+    /// Returns whether the response contains every requested item, and nothing else.
+    pub fn is_complete(&self) -> bool {
+        self.violations.is_empty() && self.nodes.iter().all(|(_, data)| data.is_some())
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use ethers_core::utils::keccak256;
     use hex_literal::hex;
 
-    use crate::{message::RequestPair, GetNodeData, NodeData};
+    use crate::{message::RequestPair, GetNodeData, NodeData, NodeDataViolation};
     use open_fastrlp::{Decodable, Encodable};
 
     #[test]
@@ -86,4 +154,44 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn match_node_data() {
+        let nodes = [
+            bytes::Bytes::from_static(&hex!("deadc0de")),
+            bytes::Bytes::from_static(&hex!("feedbeef")),
+            bytes::Bytes::from_static(&hex!("c0ffee")),
+        ];
+        let request = GetNodeData(nodes.iter().map(keccak256).collect());
+
+        // a subset, out of order, with an item that was not requested and a duplicate
+        let response = NodeData(vec![
+            nodes[2].clone(),
+            bytes::Bytes::from_static(&hex!("baadf00d")),
+            nodes[0].clone(),
+            nodes[2].clone(),
+        ]);
+        let matched = response.match_request(&request);
+        assert_eq!(
+            matched.nodes,
+            vec![
+                (request.0[0], Some(nodes[0].clone())),
+                (request.0[1], None),
+                (request.0[2], Some(nodes[2].clone())),
+            ]
+        );
+        assert_eq!(
+            matched.violations,
+            vec![
+                NodeDataViolation::Unrequested(keccak256(hex!("baadf00d"))),
+                NodeDataViolation::Duplicate(request.0[2]),
+            ]
+        );
+        assert_eq!(matched.missing().collect::<Vec<_>>(), vec![&request.0[1]]);
+        assert!(!matched.is_complete());
+
+        let matched = NodeData(nodes.to_vec()).match_request(&request);
+        assert!(matched.is_complete());
+        assert_eq!(matched.missing().count(), 0);
+    }
 }
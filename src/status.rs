@@ -1,6 +1,6 @@
 use super::forkid::ForkId;
-use ethers::types::U256;
-use foundry_config::Chain;
+use crate::Chain;
+use ethers_core::types::U256;
 use open_fastrlp::{RlpDecodable, RlpEncodable};
 use std::fmt::{Debug, Display};
 
@@ -87,11 +87,27 @@ impl Debug for Status {
     }
 }
 
+/// The extension of the handshake sent by BNB Smart Chain peers right after the [`Status`], with
+/// message id [`EthMessageID::UpgradeStatus`](crate::EthMessageID::UpgradeStatus).
+///
+/// BNB Smart Chain peers disconnect peers which do not send it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct UpgradeStatus {
+    pub extension: UpgradeStatusExtension,
+}
+
+/// The fields of an [`UpgradeStatus`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct UpgradeStatusExtension {
+    /// Whether the peer should not broadcast transactions to the sender, for example because it
+    /// is a relay which does not maintain a transaction pool.
+    pub disable_peer_tx_broadcast: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use crate::forkid::{ForkHash, ForkId};
-    use ethers::prelude::Chain as NamedChain;
-    use foundry_config::Chain;
+    use crate::{Chain, NamedChain};
     use hex_literal::hex;
     use open_fastrlp::{Decodable, Encodable};
 
@@ -105,7 +121,7 @@ mod tests {
             // ethers versions arent the same due to patches, so using Id here
             chain: Chain::Named(NamedChain::Mainnet),
             // total_difficulty: Uint::from(36206751599115524359527u128),
-            total_difficulty: ethers::types::U256::from(36206751599115524359527u128),
+            total_difficulty: ethers_core::types::U256::from(36206751599115524359527u128),
             blockhash: hex!("feb27336ca7923f8fab3bd617fcb6e75841538f71c1bcfc267d7838489d9e13d"),
             genesis: hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"),
             forkid: ForkId {
@@ -126,7 +142,7 @@ mod tests {
             version: EthVersion::Eth67 as u8,
             // ethers versions arent the same due to patches, so using Id here
             chain: Chain::Named(NamedChain::Mainnet),
-            total_difficulty: ethers::types::U256::from(36206751599115524359527u128),
+            total_difficulty: ethers_core::types::U256::from(36206751599115524359527u128),
             blockhash: hex!("feb27336ca7923f8fab3bd617fcb6e75841538f71c1bcfc267d7838489d9e13d"),
             genesis: hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"),
             forkid: ForkId {
@@ -144,7 +160,7 @@ mod tests {
         let status = Status {
             version: EthVersion::Eth66 as u8,
             chain: Chain::Named(NamedChain::BinanceSmartChain),
-            total_difficulty: ethers::types::U256::from(37851386u64),
+            total_difficulty: ethers_core::types::U256::from(37851386u64),
             blockhash: hex!("f8514c4680ef27700751b08f37645309ce65a449616a3ea966bf39dd935bb27b"),
             genesis: hex!("0d21840abff46b96c84b2ac9e10e4f5cdaeb5693cb665db62a2f3b02d2d57b5b"),
             forkid: ForkId {
@@ -164,7 +180,7 @@ mod tests {
         let expected = Status {
             version: EthVersion::Eth66 as u8,
             chain: Chain::Named(NamedChain::BinanceSmartChain),
-            total_difficulty: ethers::types::U256::from(37851386u64),
+            total_difficulty: ethers_core::types::U256::from(37851386u64),
             blockhash: hex!("f8514c4680ef27700751b08f37645309ce65a449616a3ea966bf39dd935bb27b"),
             genesis: hex!("0d21840abff46b96c84b2ac9e10e4f5cdaeb5693cb665db62a2f3b02d2d57b5b"),
             forkid: ForkId {
@@ -182,7 +198,7 @@ mod tests {
         let expected = Status {
             version: EthVersion::Eth66 as u8,
             chain: Chain::Id(2100),
-            total_difficulty: ethers::types::U256::from(
+            total_difficulty: ethers_core::types::U256::from(
                 "0x000000000000000000000000006d68fcffffffffffffffffffffffffdeab81b8",
             ),
             blockhash: hex!("523e8163a6d620a4cc152c547a05f28a03fec91a2a615194cb86df9731372c0c"),
@@ -195,4 +211,23 @@ mod tests {
         let status = Status::decode(&mut &data[..]).unwrap();
         assert_eq!(status, expected);
     }
+
+    #[test]
+    fn upgrade_status() {
+        use crate::{UpgradeStatus, UpgradeStatusExtension};
+
+        let status = UpgradeStatus {
+            extension: UpgradeStatusExtension {
+                disable_peer_tx_broadcast: true,
+            },
+        };
+        let mut encoded = vec![];
+        status.encode(&mut encoded);
+        assert_eq!(encoded, hex!("c2c101"));
+        assert_eq!(UpgradeStatus::decode(&mut &encoded[..]).unwrap(), status);
+
+        let mut encoded = vec![];
+        UpgradeStatus::default().encode(&mut encoded);
+        assert_eq!(encoded, hex!("c2c180"));
+    }
 }
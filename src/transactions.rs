@@ -1,6 +1,9 @@
-use anvil_core::eth::transaction::TypedTransaction;
+use std::collections::{HashMap, HashSet};
+
 use open_fastrlp::{RlpDecodableWrapper, RlpEncodableWrapper};
 
+use crate::{EthereumPrimitives, NetworkPrimitives, PooledTransaction, TypedTransaction};
+
 /// A list of transaction hashes that the peer would like transaction bodies for.
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper)]
 pub struct GetPooledTransactions(
@@ -25,52 +28,117 @@ where
 /// corresponds to a requested hash. Hashes may need to be re-requested if the bodies are not
 /// included in the response.
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper)]
-pub struct PooledTransactions(
-    /// The transaction bodies, each of which should correspond to a requested hash.
-    pub Vec<TypedTransaction>,
+pub struct PooledTransactions<N: NetworkPrimitives = EthereumPrimitives>(
+    /// The transaction bodies, each of which should correspond to a requested hash. Blob
+    /// transactions are in their network form, which includes the blobs.
+    pub Vec<N::PooledTransaction>,
 );
 
 impl PooledTransactions {
     /// Given a list of hashes, split the hashes into those that match a transaction in the
     /// response, and those that do not.
-    /// Assumes the transactions are in the same order as the request's hashes.
+    ///
+    /// See [`PooledTransactions::match_request`] for a matcher which also reports unrequested
+    /// and duplicate transactions.
     pub fn split_transactions_by_hashes<T: Clone + Into<[u8; 32]>>(
         &self,
         hashes: Vec<T>,
     ) -> (Vec<[u8; 32]>, Vec<[u8; 32]>) {
-        let mut matched_hashes = Vec::new();
-        let mut missing_hashes = Vec::new();
-        // If a hash fails to verify, move on to the next hash but remain on the current
-        // transaction.
-        let mut txs = self.0.iter().peekable();
-        for current_hash in hashes {
-            let tx_hash = match txs.peek() {
-                Some(tx) => tx.hash().0,
-                // done verifying requested hashes - all transactions are matched
-                None => return (matched_hashes, missing_hashes),
+        let request = GetPooledTransactions::from(hashes);
+        let matched = self.match_request(&request);
+        let mut seen = HashSet::new();
+        let matched_hashes = request
+            .0
+            .into_iter()
+            .filter(|hash| matched.transactions.contains_key(hash) && seen.insert(*hash))
+            .collect();
+        (matched_hashes, matched.missing)
+    }
+
+    /// Match the transactions of the response to the hashes of the request.
+    ///
+    /// The response may contain any subset of the requested transactions, but they should be in
+    /// request order. Transactions which were not requested, are returned twice, or are out of
+    /// order are reported as [`PooledTransactionsViolation`]s.
+    pub fn match_request(&self, request: &GetPooledTransactions) -> MatchedTransactions {
+        // the position of the first occurrence of each requested hash
+        let mut positions = HashMap::with_capacity(request.0.len());
+        for (position, hash) in request.0.iter().enumerate() {
+            positions.entry(*hash).or_insert(position);
+        }
+
+        let mut transactions = HashMap::with_capacity(self.0.len());
+        let mut violations = vec![];
+        let mut last_position = None;
+        for tx in &self.0 {
+            let hash = tx.hash().0;
+            let position = match positions.get(&hash) {
+                Some(position) => *position,
+                None => {
+                    violations.push(PooledTransactionsViolation::Unrequested(hash));
+                    continue;
+                }
             };
-            if tx_hash == current_hash.into() {
-                matched_hashes.push(tx_hash);
-                txs.next();
-            } else {
-                missing_hashes.push(tx_hash);
+            if transactions.contains_key(&hash) {
+                violations.push(PooledTransactionsViolation::Duplicate(hash));
+                continue;
+            }
+            if last_position.is_some_and(|last| position < last) {
+                violations.push(PooledTransactionsViolation::OutOfOrder(hash));
             }
+            last_position = Some(position);
+            transactions.insert(hash, tx.clone());
         }
 
-        // TODO: return an error if there are any transactions that do not match a hash.
-        (matched_hashes, missing_hashes)
+        let mut seen = HashSet::new();
+        let missing = request
+            .0
+            .iter()
+            .filter(|hash| !transactions.contains_key(*hash) && seen.insert(**hash))
+            .copied()
+            .collect();
+
+        MatchedTransactions {
+            transactions,
+            missing,
+            violations,
+        }
     }
 }
 
+/// A [`PooledTransactions`] response matched to its [`GetPooledTransactions`] request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchedTransactions {
+    /// The requested transactions which were returned, keyed by hash.
+    pub transactions: HashMap<[u8; 32], PooledTransaction>,
+    /// The requested hashes which were not returned, in request order. These may need to be
+    /// requested again.
+    pub missing: Vec<[u8; 32]>,
+    /// The ways in which the response violates the protocol, which can be used for peer scoring.
+    pub violations: Vec<PooledTransactionsViolation>,
+}
+
+/// A way in which a [`PooledTransactions`] response violates the protocol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PooledTransactionsViolation {
+    /// A transaction with the given hash was returned, but not requested.
+    Unrequested([u8; 32]),
+    /// The transaction with the given hash was returned more than once.
+    Duplicate([u8; 32]),
+    /// The transaction with the given hash was returned after a transaction which was requested
+    /// later.
+    OutOfOrder([u8; 32]),
+}
+
 impl From<Vec<TypedTransaction>> for PooledTransactions {
     fn from(txs: Vec<TypedTransaction>) -> Self {
-        PooledTransactions(txs)
+        PooledTransactions(txs.into_iter().map(Into::into).collect())
     }
 }
 
 impl From<PooledTransactions> for Vec<TypedTransaction> {
     fn from(txs: PooledTransactions) -> Self {
-        txs.0
+        txs.0.into_iter().map(|tx| tx.transaction).collect()
     }
 }
 
@@ -78,16 +146,16 @@ impl From<PooledTransactions> for Vec<TypedTransaction> {
 mod test {
     use std::str::FromStr;
 
-    use anvil_core::eth::transaction::{
-        EIP1559Transaction, LegacyTransaction, TransactionKind, TypedTransaction,
-    };
-    use ethers::{
-        prelude::{Bytes, Signature, H256, U256},
+    use ethers_core::{
         types::transaction::eip2930::AccessList,
+        types::{Bytes, Signature, H256, U256},
     };
     use hex_literal::hex;
 
-    use crate::{message::RequestPair, GetPooledTransactions, PooledTransactions};
+    use crate::{
+        message::RequestPair, EIP1559Transaction, GetPooledTransactions, LegacyTransaction,
+        PooledTransactions, PooledTransactionsViolation, TransactionKind, TypedTransaction,
+    };
     use open_fastrlp::{Decodable, Encodable};
 
     #[test]
@@ -494,4 +562,54 @@ mod test {
         assert_eq!(encoded_str.len(), expected_str.len());
         assert_eq!(encoded_str, expected_str);
     }
+
+    #[test]
+    fn match_pooled_transactions() {
+        let data = hex!("f9022980f90225f8650f84832156008287fb94cf7f9e66af820a19257a2108375b180b0ec491678204d2802ca035b7bfeb9ad9ece2cbafaaf8e202e706b4cfaeb233f46198f00b44d4a566a981a0612638fb29427ca33b9a3be2a0a561beecfe0269655be160d35e72d366a6a860b87502f872041a8459682f008459682f0d8252089461815774383099e24810ab832a5b2a5425c154d58829a2241af62c000080c001a059e6b67f48fb32e7e570dfb11e042b5ad2e55e3ce3ce9cd989c7e06e07feeafda0016b83f4f980694ed2eee4d10667242b1f40dc406901b34125b008d334d47469f86b0384773594008398968094d3e8763675e4c425df46cc3b5c0f6cbdac39604687038d7ea4c68000802ba0ce6834447c0a4193c40382e6c57ae33b241379c5418caac9cdc18d786fd12071a03ca3ae86580e94550d7c071e3a02eadb5a77830947c9225165cf9100901bee88f86b01843b9aca00830186a094d3e8763675e4c425df46cc3b5c0f6cbdac3960468702769bb01b2a00802ba0e24d8bd32ad906d6f8b8d7741e08d1959df021698b19ee232feba15361587d0aa05406ad177223213df262cb66ccbb2f46bfdccfdfbbb5ffdda9e2c02d977631daf86b02843b9aca00830186a094d3e8763675e4c425df46cc3b5c0f6cbdac39604687038d7ea4c68000802ba00eb96ca19e8a77102767a41fc85a36afd5c61ccb09911cec5d3e86e193d9c5aea03a456401896b1b6055311536bf00a718568c744d8c1f9df59879e8350220ca18");
+        let txs = RequestPair::<PooledTransactions>::decode(&mut &data[..])
+            .unwrap()
+            .message
+            .0;
+        let hashes = txs.iter().map(|tx| tx.hash().0).collect::<Vec<_>>();
+        let request = GetPooledTransactions(hashes.clone());
+
+        // a subset with skipped hashes, including the last one
+        let response = PooledTransactions(vec![txs[0].clone(), txs[2].clone()]);
+        let matched = response.match_request(&request);
+        assert_eq!(matched.transactions.len(), 2);
+        assert_eq!(matched.transactions[&hashes[2]], txs[2]);
+        assert_eq!(matched.missing, vec![hashes[1], hashes[3], hashes[4]]);
+        assert!(matched.violations.is_empty());
+        assert_eq!(
+            response.split_transactions_by_hashes(hashes.clone()),
+            (
+                vec![hashes[0], hashes[2]],
+                vec![hashes[1], hashes[3], hashes[4]]
+            )
+        );
+
+        // duplicate, out of order and unrequested transactions
+        let response = PooledTransactions(vec![
+            txs[1].clone(),
+            txs[1].clone(),
+            txs[0].clone(),
+            txs[4].clone(),
+        ]);
+        let matched = response.match_request(&GetPooledTransactions(hashes[..4].to_vec()));
+        assert_eq!(matched.transactions.len(), 2);
+        assert_eq!(matched.missing, vec![hashes[2], hashes[3]]);
+        assert_eq!(
+            matched.violations,
+            vec![
+                PooledTransactionsViolation::Duplicate(hashes[1]),
+                PooledTransactionsViolation::OutOfOrder(hashes[0]),
+                PooledTransactionsViolation::Unrequested(hashes[4]),
+            ]
+        );
+
+        // an empty response
+        let matched = PooledTransactions(vec![]).match_request(&request);
+        assert!(matched.transactions.is_empty());
+        assert_eq!(matched.missing, hashes);
+    }
 }
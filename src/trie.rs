@@ -0,0 +1,232 @@
+//! Root hash computation for the
+//! [Merkle Patricia Trie](https://ethereum.org/en/developers/docs/data-structures-and-encoding/patricia-merkle-trie/),
+//! as used for the state, transaction and receipt roots.
+use ethers_core::{types::H256, utils::keccak256};
+use hex_literal::hex;
+use open_fastrlp::{Encodable, Header};
+
+/// The root of an empty trie, `keccak256(rlp(""))`.
+pub const EMPTY_ROOT: H256 = H256(hex!(
+    "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+));
+
+/// Computes the root of the trie containing the given key-value pairs.
+///
+/// If a key occurs more than once, the last value is used.
+pub fn trie_root<I, K, V>(items: I) -> H256
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    let mut items = items
+        .into_iter()
+        .map(|(key, value)| (to_nibbles(key.as_ref()), value.as_ref().to_vec()))
+        .collect::<Vec<_>>();
+    // stable, so later duplicates stay last
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    items.reverse();
+    items.dedup_by(|a, b| a.0 == b.0);
+    items.reverse();
+
+    if items.is_empty() {
+        return EMPTY_ROOT;
+    }
+    H256(keccak256(encode_node(&items, 0)))
+}
+
+/// Computes the root of a secure trie, where each key is hashed with `keccak256` before it is
+/// inserted. This is used for the state and storage tries.
+pub fn sec_trie_root<I, K, V>(items: I) -> H256
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    trie_root(
+        items
+            .into_iter()
+            .map(|(key, value)| (keccak256(key.as_ref()), value)),
+    )
+}
+
+/// Computes the root of a trie keyed by the RLP encoded index of each value. This is used for
+/// the transaction, receipt and withdrawal roots.
+pub fn ordered_trie_root<I, V>(values: I) -> H256
+where
+    I: IntoIterator<Item = V>,
+    V: AsRef<[u8]>,
+{
+    trie_root(values.into_iter().enumerate().map(|(index, value)| {
+        let mut key = vec![];
+        index.encode(&mut key);
+        (key, value)
+    }))
+}
+
+/// Returns the EIP-2718 envelope of a transaction or receipt, which is what the transactions
+/// and receipts tries contain.
+///
+/// On the wire, typed transactions and receipts are additionally wrapped in an RLP string, which
+/// is removed here.
+pub(crate) fn envelope<T: Encodable>(item: &T) -> Vec<u8> {
+    let mut encoded = vec![];
+    item.encode(&mut encoded);
+    let mut payload = &encoded[..];
+    match Header::decode(&mut payload) {
+        Ok(header) if !header.list => payload.to_vec(),
+        _ => encoded,
+    }
+}
+
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// Encodes a path with the hex prefix encoding.
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 0x20 } else { 0x00 };
+    let mut out = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        out.push(flag | 0x10 | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        out.push(flag);
+        nibbles
+    };
+    out.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    out
+}
+
+/// Encodes a list of already encoded items.
+fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload_length = items.iter().map(Vec::len).sum();
+    let mut out = vec![];
+    Header {
+        list: true,
+        payload_length,
+    }
+    .encode(&mut out);
+    for item in items {
+        out.extend_from_slice(item);
+    }
+    out
+}
+
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut out = vec![];
+    bytes.encode(&mut out);
+    out
+}
+
+/// Returns how a child node is referenced from its parent: nodes shorter than 32 bytes are
+/// embedded, others are referenced by their hash.
+fn node_reference(encoded: Vec<u8>) -> Vec<u8> {
+    if encoded.len() < 32 {
+        encoded
+    } else {
+        encode_bytes(&keccak256(encoded))
+    }
+}
+
+/// Encodes the node containing the given items, which are sorted by key and share their first
+/// `depth` nibbles.
+fn encode_node(items: &[(Vec<u8>, Vec<u8>)], depth: usize) -> Vec<u8> {
+    if let [(key, value)] = items {
+        return encode_list(&[
+            encode_bytes(&hex_prefix(&key[depth..], true)),
+            encode_bytes(value),
+        ]);
+    }
+
+    // the items are sorted, so the common prefix of all of them is that of the first and last
+    let first = &items[0].0;
+    let last = &items[items.len() - 1].0;
+    let common = first[depth..]
+        .iter()
+        .zip(&last[depth..])
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common > 0 {
+        return encode_list(&[
+            encode_bytes(&hex_prefix(&first[depth..depth + common], false)),
+            node_reference(encode_node(items, depth + common)),
+        ]);
+    }
+
+    let mut children = Vec::with_capacity(17);
+    // a key which ends here is stored in the value slot of the branch
+    let (value, mut rest) = match items.split_first() {
+        Some(((key, value), rest)) if key.len() == depth => (encode_bytes(value), rest),
+        _ => (encode_bytes(&[]), items),
+    };
+    for nibble in 0..16 {
+        let end = rest
+            .iter()
+            .position(|(key, _)| key[depth] != nibble)
+            .unwrap_or(rest.len());
+        let (child, remaining) = rest.split_at(end);
+        children.push(if child.is_empty() {
+            encode_bytes(&[])
+        } else {
+            node_reference(encode_node(child, depth + 1))
+        });
+        rest = remaining;
+    }
+    children.push(value);
+    encode_list(&children)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ordered_trie_root, sec_trie_root, trie_root, EMPTY_ROOT};
+    use ethers_core::types::H256;
+    use hex_literal::hex;
+
+    #[test]
+    fn empty_root() {
+        assert_eq!(trie_root(Vec::<(Vec<u8>, Vec<u8>)>::new()), EMPTY_ROOT);
+        assert_eq!(ordered_trie_root(Vec::<Vec<u8>>::new()), EMPTY_ROOT);
+    }
+
+    #[test]
+    // Test vectors from the ethereum/tests `TrieTests`
+    fn trie_tests() {
+        assert_eq!(
+            trie_root([
+                ("doe", "reindeer"),
+                ("dog", "puppy"),
+                ("dogglesworth", "cat"),
+            ]),
+            H256(hex!(
+                "8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+            ))
+        );
+        // later values replace earlier ones
+        assert_eq!(
+            trie_root([
+                ("do", "verb"),
+                ("horse", "stallion"),
+                ("doge", "coin"),
+                ("dog", "puppy"),
+                ("doge", "coin"),
+            ]),
+            H256(hex!(
+                "5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"
+            ))
+        );
+    }
+
+    #[test]
+    fn secure_trie() {
+        // a storage trie with a single slot set to 1
+        assert_eq!(
+            sec_trie_root([([0u8; 32], [0x01])]),
+            H256(hex!(
+                "821e2556a290c86405f8160a2d662042a431ba456b9db265c79bb837c04be5f0"
+            ))
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Genesis hash tests for [`Genesis`]
+use ethers_core::types::{Address, H256};
+use ethp2p::{Clique, CliqueSnapshot, ForkHash, ForkId, Genesis, Head, NetworkSpec};
+use hex_literal::hex;
+use std::{fs, path::PathBuf};
+
+#[test]
+fn goerli_genesis_hash() {
+    let genesis_path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata/goerli_genesis.json");
+    let data = fs::read_to_string(genesis_path).expect("Unable to read file");
+    let genesis = Genesis::from_json(&data).unwrap();
+
+    assert_eq!(
+        genesis.state_root(),
+        H256(hex!(
+            "5d6cded585e73c4e322c30c2f782a336316f17dd85a4863b9d838d2d4b8b3008"
+        ))
+    );
+    assert_eq!(genesis.hash(), NetworkSpec::goerli().genesis_hash);
+
+    let head = Head {
+        number: 0,
+        timestamp: genesis.timestamp,
+    };
+    assert_eq!(
+        genesis.fork_filter(head).current(),
+        ForkId {
+            hash: ForkHash(hex!("a3f5ab08")),
+            next: 1_561_651
+        }
+    );
+    assert_eq!(
+        genesis.fork_filter(head),
+        NetworkSpec::goerli().fork_filter(head)
+    );
+}
+
+#[test]
+fn goerli_genesis_signers() {
+    let genesis_path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata/goerli_genesis.json");
+    let data = fs::read_to_string(genesis_path).expect("Unable to read file");
+    let genesis = Genesis::from_json(&data).unwrap();
+    let header = genesis.header();
+    assert_eq!(header.hash(), NetworkSpec::goerli().genesis_hash);
+
+    let snapshot = CliqueSnapshot::new(Clique::goerli(), &header).unwrap();
+    assert_eq!(snapshot.hash(), header.hash());
+    assert!(snapshot.signers().eq(&[Address::from(hex!(
+        "e0a2bd4258d2768837baa26a28fe71dc079f84c7"
+    ))]));
+}
+
+#[test]
+fn zero_difficulty_genesis_hash() {
+    let genesis = Genesis::from_json(
+        r#"{
+            "config": {
+                "chainId": 1337,
+                "homesteadBlock": 0,
+                "eip150Block": 0,
+                "eip155Block": 0,
+                "eip158Block": 0,
+                "byzantiumBlock": 0,
+                "constantinopleBlock": 0,
+                "petersburgBlock": 0,
+                "istanbulBlock": 0,
+                "berlinBlock": 0,
+                "londonBlock": 0,
+                "shanghaiTime": 0,
+                "cancunTime": 0
+            },
+            "difficulty": "0x0",
+            "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "gasLimit": "0x1c9c380",
+            "timestamp": "1700000000",
+            "alloc": {
+                "0x00000000000000000000000000000000000000aa": { "balance": "1000000000000000000" }
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        genesis.state_root(),
+        H256(hex!(
+            "95ffff1af791dc6c2380fcb9a055e60457fcf804094ebfbb58296532b15a0203"
+        ))
+    );
+    assert_eq!(
+        genesis.hash(),
+        H256(hex!(
+            "2ab15e878fd56a09fff3304a5fdc21b878968db79730daceb57800b5cbc5712d"
+        ))
+    );
+}